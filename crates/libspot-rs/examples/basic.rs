@@ -54,6 +54,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         discard_anomalies: true, // flag anomalies
         level: 0.998,            // tail quantile
         max_excess: 200,         // data points to keep
+        ..SpotConfig::default()
     };
 
     // Create and initialize SPOT detector
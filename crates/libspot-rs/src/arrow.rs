@@ -0,0 +1,154 @@
+//! Apache Arrow integration (requires the `arrow` feature)
+//!
+//! [`SpotDetector::score_batch_arrow`] steps the detector over a
+//! [`Float64Array`] and returns the per-value results as a [`RecordBatch`], so
+//! SPOT can be used as a transform between Arrow-based pipeline stages.
+
+use std::sync::Arc;
+
+use ::arrow::array::{DictionaryArray, Float64Array, Float64Builder, Int8Array, StringArray};
+use ::arrow::datatypes::{DataType, Field, Int8Type, Schema, SchemaRef};
+use ::arrow::error::{ArrowError, Result};
+use ::arrow::record_batch::RecordBatch;
+
+use crate::online::OnlineDetector;
+use crate::spot::SpotDetector;
+
+/// Dictionary values of the `status` column, indexed by the [`SpotStatus`](crate::SpotStatus) code
+const STATUS_NAMES: [&str; 3] = ["Normal", "Excess", "Anomaly"];
+
+impl SpotDetector {
+    /// Schema of the batches returned by [`score_batch_arrow`](Self::score_batch_arrow)
+    ///
+    /// | column              | type                     | content                                   |
+    /// |---------------------|--------------------------|-------------------------------------------|
+    /// | `value`             | `Float64`                | the input value                           |
+    /// | `status`            | `Dictionary(Int8, Utf8)` | [`SpotStatus`](crate::SpotStatus) name, keyed by its code |
+    /// | `anomaly_threshold` | `Float64`                | anomaly threshold after the step          |
+    /// | `score`             | `Float64`                | [`OnlineDetector::score`] before the step |
+    ///
+    /// Null input values produce null `status`, `anomaly_threshold` and `score`.
+    pub fn arrow_schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("value", DataType::Float64, true),
+            Field::new(
+                "status",
+                DataType::Dictionary(Box::new(DataType::Int8), Box::new(DataType::Utf8)),
+                true,
+            ),
+            Field::new("anomaly_threshold", DataType::Float64, true),
+            Field::new("score", DataType::Float64, true),
+        ]))
+    }
+
+    /// Step the detector over `values` and collect the results in a [`RecordBatch`]
+    ///
+    /// The detector must have been fitted. Null entries are skipped without
+    /// updating the model. A NaN value aborts the batch with an
+    /// [`ArrowError::ExternalError`] wrapping [`SpotError::DataIsNaN`](crate::SpotError::DataIsNaN);
+    /// the values before it have already been processed.
+    pub fn score_batch_arrow(&mut self, values: &Float64Array) -> Result<RecordBatch> {
+        let len = values.len();
+        let mut status_keys: Vec<Option<i8>> = Vec::with_capacity(len);
+        let mut thresholds = Float64Builder::with_capacity(len);
+        let mut scores = Float64Builder::with_capacity(len);
+
+        for value in values.iter() {
+            let Some(value) = value else {
+                status_keys.push(None);
+                thresholds.append_null();
+                scores.append_null();
+                continue;
+            };
+
+            let score = OnlineDetector::score(self, value);
+            let status = self
+                .step(value)
+                .map_err(|e| ArrowError::ExternalError(Box::new(e)))?;
+            status_keys.push(Some(status as i8));
+            thresholds.append_value(self.anomaly_threshold());
+            scores.append_value(score);
+        }
+
+        let status = DictionaryArray::<Int8Type>::try_new(
+            Int8Array::from(status_keys),
+            Arc::new(StringArray::from(STATUS_NAMES.to_vec())),
+        )?;
+
+        RecordBatch::try_new(
+            Self::arrow_schema(),
+            vec![
+                Arc::new(values.clone()),
+                Arc::new(status),
+                Arc::new(thresholds.finish()),
+                Arc::new(scores.finish()),
+            ],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SpotConfig;
+    use crate::status::SpotStatus;
+    use crate::testing::exponential_data;
+    use ::arrow::array::{Array, AsArray};
+    use ::arrow::datatypes::Float64Type;
+
+    #[test]
+    fn test_score_batch_arrow_schema_and_rows() {
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        spot.fit(&exponential_data(10_000, 7)).unwrap();
+
+        let input = Float64Array::from(vec![Some(0.5), None, Some(7.0), Some(50.0)]);
+        let batch = spot.score_batch_arrow(&input).unwrap();
+
+        assert_eq!(batch.schema(), SpotDetector::arrow_schema());
+        assert_eq!(batch.num_rows(), 4);
+        assert_eq!(batch.num_columns(), 4);
+        // The null is skipped and the anomaly discarded from the counters
+        assert_eq!(spot.n(), 10_002);
+
+        let status = batch
+            .column_by_name("status")
+            .unwrap()
+            .as_dictionary::<Int8Type>();
+        let keys: Vec<Option<i8>> = status.keys().iter().collect();
+        assert_eq!(
+            keys,
+            vec![
+                Some(SpotStatus::Normal as i8),
+                None,
+                Some(SpotStatus::Excess as i8),
+                Some(SpotStatus::Anomaly as i8),
+            ]
+        );
+
+        let scores = batch
+            .column_by_name("score")
+            .unwrap()
+            .as_primitive::<Float64Type>();
+        assert_eq!(scores.value(0), 0.0);
+        assert!(scores.is_null(1));
+        assert!(scores.value(3) > scores.value(2));
+
+        let thresholds = batch
+            .column_by_name("anomaly_threshold")
+            .unwrap()
+            .as_primitive::<Float64Type>();
+        assert_eq!(thresholds.value(3), spot.anomaly_threshold());
+    }
+
+    #[test]
+    fn test_score_batch_arrow_nan_is_error() {
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        spot.fit(&exponential_data(10_000, 7)).unwrap();
+
+        let input = Float64Array::from(vec![0.5, f64::NAN]);
+        assert!(matches!(
+            spot.score_batch_arrow(&input),
+            Err(ArrowError::ExternalError(_))
+        ));
+    }
+}
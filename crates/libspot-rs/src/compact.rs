@@ -0,0 +1,114 @@
+//! Compact serialized form of SPOT models
+//!
+//! A serialized [`SpotDetector`] embeds its whole excess buffer, i.e.
+//! `max_excess` values. A [`CompactSpot`] only keeps what the thresholds and
+//! quantiles are computed from, at the cost of the excesses themselves.
+
+use crate::config::SpotConfig;
+use crate::error::SpotResult;
+use crate::float::Float;
+use crate::peaks::Peaks;
+use crate::spot::SpotDetector;
+
+/// Sufficient statistics of a fitted SPOT detector, without the excess buffer
+///
+/// Built with [`SpotDetector::to_compact`] and turned back into a detector with
+/// [`SpotDetector::from_compact`]. The restored detector has the same
+/// thresholds, quantiles and probabilities, but an empty tail buffer:
+///
+/// - [`excess_values`](SpotDetector::excess_values) is empty and the peak
+///   statistics are reset (the ones of the dropped excesses are kept here for
+///   reference only);
+/// - the tail is refitted on the excesses seen after the restore alone, once
+///   there are [`min_peaks`](SpotConfig::min_peaks) of them, so the model
+///   does not evolve as the original one would;
+/// - the warmup and the excess threshold tracking state are not kept.
+///
+/// Use it to ship a model for scoring, and the full [`SpotDetector`] to
+/// checkpoint one that keeps learning.
+///
+/// ```
+/// use libspot_rs::{CompactSpot, SpotConfig, SpotDetector};
+///
+/// let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+/// let data: Vec<f64> = (0..1000).map(|i| (i as f64) / 100.0).collect();
+/// spot.fit(&data).unwrap();
+///
+/// let json = serde_json::to_string(&spot.to_compact()).unwrap();
+/// let compact: CompactSpot = serde_json::from_str(&json).unwrap();
+/// let loaded = SpotDetector::from_compact(compact).unwrap();
+/// assert_eq!(loaded.anomaly_threshold(), spot.anomaly_threshold());
+/// assert!(loaded.excess_values().is_empty());
+/// ```
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(bound = "")]
+pub struct CompactSpot<F: Float = f64> {
+    /// Configuration of the detector
+    pub config: SpotConfig,
+    /// Number of observations
+    pub n: usize,
+    /// Number of excesses
+    pub nt: usize,
+    /// Excess threshold
+    #[serde(with = "crate::ser::nan_safe_float")]
+    pub excess_threshold: F,
+    /// Anomaly threshold
+    #[serde(with = "crate::ser::nan_safe_float")]
+    pub anomaly_threshold: F,
+    /// GPD gamma parameter
+    #[serde(with = "crate::ser::nan_safe_float")]
+    pub gamma: F,
+    /// GPD sigma parameter
+    #[serde(with = "crate::ser::nan_safe_float")]
+    pub sigma: F,
+    /// Sum of the dropped excesses
+    #[serde(with = "crate::ser::nan_safe_float")]
+    pub e: F,
+    /// Sum of the squares of the dropped excesses
+    #[serde(with = "crate::ser::nan_safe_float")]
+    pub e2: F,
+    /// Minimum of the dropped excesses
+    #[serde(with = "crate::ser::nan_safe_float")]
+    pub min: F,
+    /// Maximum of the dropped excesses
+    #[serde(with = "crate::ser::nan_safe_float")]
+    pub max: F,
+}
+
+impl<F: Float> SpotDetector<F> {
+    /// Get the [`CompactSpot`] of the detector, dropping the excess buffer
+    pub fn to_compact(&self) -> CompactSpot<F> {
+        let peaks = self.tail().peaks();
+        let (gamma, sigma) = self.tail_parameters();
+        CompactSpot {
+            config: self.config().unwrap_or_default(),
+            n: self.n(),
+            nt: self.nt(),
+            excess_threshold: self.excess_threshold(),
+            anomaly_threshold: self.anomaly_threshold(),
+            gamma,
+            sigma,
+            e: peaks.sum(),
+            e2: peaks.sum_squares(),
+            min: peaks.min(),
+            max: peaks.max(),
+        }
+    }
+
+    /// Rebuild a detector from a [`CompactSpot`], with an empty excess buffer
+    ///
+    /// Fails with the errors of [`SpotDetector::new`], and with
+    /// [`SpotError::CountersOutOfBounds`](crate::SpotError::CountersOutOfBounds)
+    /// if `nt > n`.
+    pub fn from_compact(compact: CompactSpot<F>) -> SpotResult<Self> {
+        let mut spot = Self::new_generic(compact.config)?;
+        let peaks = Peaks::new_generic(spot.tail().peaks().capacity())?;
+        spot.restore(
+            peaks,
+            (compact.gamma, compact.sigma),
+            (compact.excess_threshold, compact.anomaly_threshold),
+            (compact.n, compact.nt),
+        )?;
+        Ok(spot)
+    }
+}
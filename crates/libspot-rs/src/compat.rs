@@ -0,0 +1,217 @@
+//! Import of detectors from the C implementation
+//!
+//! The pure Rust structures mirror the C ones field by field, so the state of
+//! a C `struct Spot` (as wrapped by the FFI `libspot` crate) can be copied into
+//! a [`SpotDetector`] that then behaves exactly like the C detector.
+
+use alloc::vec::Vec;
+
+use crate::config::SpotConfig;
+use crate::error::SpotResult;
+use crate::peaks::Peaks;
+use crate::spot::SpotDetector;
+use crate::ubend::Ubend;
+
+/// Raw state of a C SPOT detector, read from its `struct Spot`
+///
+/// The parameters of the C detector (`q`, `level`, `low`, `discard_anomalies`
+/// and the size of the excess buffer) are passed separately as a
+/// [`SpotConfig`] to [`SpotDetector::from_c_layout`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CSpotLayout {
+    /// `spot.anomaly_threshold`
+    pub anomaly_threshold: f64,
+    /// `spot.excess_threshold`
+    pub excess_threshold: f64,
+    /// `spot.n`
+    pub n: usize,
+    /// `spot.nt`
+    pub nt: usize,
+    /// `spot.tail.gamma`
+    pub gamma: f64,
+    /// `spot.tail.sigma`
+    pub sigma: f64,
+    /// `spot.tail.peaks.e` (sum of the excesses)
+    pub e: f64,
+    /// `spot.tail.peaks.e2` (sum of the squared excesses)
+    pub e2: f64,
+    /// `spot.tail.peaks.min`
+    pub min: f64,
+    /// `spot.tail.peaks.max`
+    pub max: f64,
+    /// `spot.tail.peaks.container.cursor`
+    pub cursor: usize,
+    /// `spot.tail.peaks.container.filled` (non-zero in C)
+    pub filled: bool,
+    /// `spot.tail.peaks.container.last_erased_data`
+    pub last_erased_data: f64,
+    /// The whole excess buffer (`spot.tail.peaks.container.data`), of
+    /// `capacity` values in storage order
+    pub data: Vec<f64>,
+}
+
+impl SpotDetector {
+    /// Rebuild a detector from the state of a C detector
+    ///
+    /// The raw state is taken as is (the peak statistics are not recomputed), so
+    /// the detector continues exactly where the C one stopped. The C detector
+    /// has no [`min_peaks`](SpotConfig::min_peaks) and accepts infinite values,
+    /// so `min_peaks` is forced to 0 and
+    /// [`reject_infinite`](SpotConfig::reject_infinite) to `false`. The other
+    /// options that the C implementation lacks come from `config`; keep their
+    /// defaults for identical behavior.
+    ///
+    /// Fails with the errors of [`SpotDetector::new`], with
+    /// [`SpotError::ConfigMismatch`](crate::SpotError::ConfigMismatch) if the
+    /// buffer length differs from `config.max_excess`, and with
+    /// [`SpotError::CountersOutOfBounds`](crate::SpotError::CountersOutOfBounds)
+    /// if `nt > n` or the cursor is outside the buffer.
+    pub fn from_c_layout(config: SpotConfig, layout: CSpotLayout) -> SpotResult<Self> {
+        let config = SpotConfig {
+            min_peaks: 0,
+            reject_infinite: false,
+            ..config
+        };
+        let mut spot = Self::new(config)?;
+
+        let container = Ubend::from_raw_parts(
+            layout.data,
+            layout.cursor,
+            layout.filled,
+            layout.last_erased_data,
+        )?;
+        let peaks = Peaks::from_raw_parts(container, layout.e, layout.e2, layout.min, layout.max);
+        spot.restore(
+            peaks,
+            (layout.gamma, layout.sigma),
+            (layout.excess_threshold, layout.anomaly_threshold),
+            (layout.n, layout.nt),
+        )?;
+        Ok(spot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::SpotError;
+    use crate::testing::exponential_data;
+
+    /// Read the state of a detector as the FFI crate would read the C struct
+    fn layout_of(spot: &SpotDetector) -> CSpotLayout {
+        let peaks = spot.tail().peaks();
+        let container = peaks.container();
+        let (gamma, sigma) = spot.tail_parameters();
+        CSpotLayout {
+            anomaly_threshold: spot.anomaly_threshold(),
+            excess_threshold: spot.excess_threshold(),
+            n: spot.n(),
+            nt: spot.nt(),
+            gamma,
+            sigma,
+            e: peaks.sum(),
+            e2: peaks.sum_squares(),
+            min: peaks.min(),
+            max: peaks.max(),
+            cursor: container.cursor(),
+            filled: container.is_filled(),
+            last_erased_data: container.last_erased_data(),
+            data: container.raw_data().to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_from_c_layout_continues_like_the_original() {
+        let config = SpotConfig {
+            level: 0.98,
+            max_excess: 100,
+            ..SpotConfig::default()
+        };
+        let mut original = SpotDetector::new(config.clone()).unwrap();
+        original.fit(&exponential_data(5000, 1)).unwrap();
+        // Wrap the excess buffer around
+        for x in exponential_data(5000, 2) {
+            original.step(x).unwrap();
+        }
+        assert!(original.tail().peaks().is_full());
+
+        let mut imported = SpotDetector::from_c_layout(config, layout_of(&original)).unwrap();
+        for q in [1e-3, 1e-4, 1e-6] {
+            assert!((imported.quantile(q) - original.quantile(q)).abs() < 1e-9);
+        }
+
+        for x in exponential_data(5000, 3) {
+            assert_eq!(imported.step(x), original.step(x));
+        }
+        assert_eq!(imported.anomaly_threshold(), original.anomaly_threshold());
+        assert_eq!(imported.tail_parameters(), original.tail_parameters());
+    }
+
+    #[test]
+    fn test_from_c_layout_forces_c_behavior() {
+        let config = SpotConfig::default();
+        assert!(config.min_peaks > 0 && config.reject_infinite);
+        let mut original = SpotDetector::new(SpotConfig {
+            min_peaks: 0,
+            reject_infinite: false,
+            ..config.clone()
+        })
+        .unwrap();
+        original.fit(&exponential_data(1000, 1)).unwrap();
+
+        let mut imported = SpotDetector::from_c_layout(config, layout_of(&original)).unwrap();
+        let imported_config = imported.config().unwrap();
+        assert_eq!(imported_config.min_peaks, 0);
+        assert!(!imported_config.reject_infinite);
+        for x in exponential_data(1000, 2)
+            .into_iter()
+            .chain([f64::INFINITY, 1.0])
+        {
+            assert_eq!(imported.step(x), original.step(x));
+        }
+    }
+
+    #[test]
+    fn test_from_c_layout_errors() {
+        let config = SpotConfig {
+            max_excess: 10,
+            ..SpotConfig::default()
+        };
+        let mut spot = SpotDetector::new(config.clone()).unwrap();
+        spot.fit(&exponential_data(5000, 1)).unwrap();
+        let layout = layout_of(&spot);
+
+        let other = SpotConfig {
+            max_excess: 20,
+            ..config.clone()
+        };
+        assert_eq!(
+            SpotDetector::from_c_layout(other, layout.clone()).unwrap_err(),
+            SpotError::ConfigMismatch
+        );
+        let counters = CSpotLayout {
+            nt: layout.n + 1,
+            ..layout.clone()
+        };
+        assert_eq!(
+            SpotDetector::from_c_layout(config.clone(), counters).unwrap_err(),
+            SpotError::CountersOutOfBounds
+        );
+        let cursor = CSpotLayout {
+            cursor: 10,
+            ..layout.clone()
+        };
+        assert_eq!(
+            SpotDetector::from_c_layout(config.clone(), cursor).unwrap_err(),
+            SpotError::CountersOutOfBounds
+        );
+        let level = SpotConfig {
+            level: 1.0,
+            ..config
+        };
+        assert_eq!(
+            SpotDetector::from_c_layout(level, layout).unwrap_err(),
+            SpotError::LevelOutOfBounds
+        );
+    }
+}
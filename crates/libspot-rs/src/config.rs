@@ -1,7 +1,14 @@
 //! Configuration types for SPOT detector
 
+use crate::error::{SpotError, SpotResult};
+use crate::estimator::{FitStrategy, GrimshawOptions};
+
 /// Configuration parameters for SPOT detector
 ///
+/// New options are added as public fields, so build the configuration with
+/// [`SpotConfig::builder`] or end struct literals with `..SpotConfig::default()`
+/// to keep compiling across versions (see the changelog).
+///
 /// # Serialization
 ///
 /// When the `serde` feature is enabled, this struct can be serialized and deserialized.
@@ -27,13 +34,171 @@ pub struct SpotConfig {
     /// Whether to discard anomalies from model updates
     pub discard_anomalies: bool,
     /// Excess level - high quantile that delimits the tail (must be between 0 and 1)
+    ///
+    /// With `level == 0.0` the whole distribution is modeled as the tail: the
+    /// excess threshold is the smallest training value (the largest one for
+    /// the lower tail), so every other training value is an excess.
     pub level: f64,
     /// Maximum number of excess data points to keep
     pub max_excess: usize,
+    /// Whether to keep re-estimating the excess threshold from recent data
+    ///
+    /// When enabled, a [`P2Estimator`](crate::P2Estimator) is fed every value passed to
+    /// `step`. Each time it has seen as many values as the training set passed to
+    /// `fit`, the excess threshold is moved to its estimate and a new window starts.
+    /// This follows slow drifts in the *location* of the data, while the shape of the
+    /// tail is still learned from the excesses.
+    ///
+    /// The stored excesses are kept as they are when the threshold moves (an
+    /// excess over the old location is taken as an excess of the same size over
+    /// the new one), so the GPD and the counters `n` and `nt` are unchanged and
+    /// the anomaly threshold moves along with the excess threshold. A window
+    /// without a P2 estimate (fewer than 5 values) leaves the threshold as is.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub track_excess_threshold: bool,
+    /// Whether the tail fit also considers the Probability-Weighted Moments estimator
+    ///
+    /// PWM joins MoM and Grimshaw in the "best log-likelihood" selection. It is
+    /// more stable on small tails, but disabled by default since the C
+    /// implementation only has the first two and results would differ from it.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pwm_estimator: bool,
+    /// Minimum number of peaks in the tail before `step` refits the GPD
+    ///
+    /// With fewer peaks the estimators can return degenerate parameters, so an
+    /// excess only joins the tail and the previous anomaly threshold is kept.
+    /// Set it to 0 to refit on every excess like the C implementation.
+    ///
+    /// Configurations serialized before this option existed load with 0, which
+    /// was their behavior.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub min_peaks: usize,
+    /// Root finding options of the Grimshaw estimator
+    ///
+    /// `None` (the default) keeps the constants of the C implementation.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub grimshaw_options: Option<GrimshawOptions>,
+    /// Number of `step` calls after `fit` during which no anomaly is flagged
+    ///
+    /// Right after fitting, the GPD is estimated on few excesses and the
+    /// anomaly threshold can be too tight. During the warmup, values beyond
+    /// it are processed as excesses (updating the tail) instead of being
+    /// flagged and discarded. The default, 0, disables the warmup.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub warmup_steps: usize,
+    /// Whether `fit` and `step` reject infinite values with
+    /// [`SpotError::DataIsInfinite`]
+    ///
+    /// An infinite value would set the min/max of the excesses to infinity and
+    /// poison every later GPD fit. When disabled, infinite values are accepted
+    /// but kept out of the model: `fit` skips them and `step` flags those on
+    /// the side of the tail as anomalies (the others are normal), as if they
+    /// were clamped beyond any threshold, without counting them.
+    #[cfg_attr(feature = "serde", serde(default = "default_reject_infinite"))]
+    pub reject_infinite: bool,
+    /// Growth of the excess buffer on streams with many excesses
+    ///
+    /// `None` (the default) keeps `max_excess` fixed, like the C implementation.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub adaptive_tail: Option<AdaptiveTail>,
+    /// Estimators tried when fitting the tail
+    ///
+    /// The default, [`FitStrategy::Both`], matches the C implementation.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fit_strategy: FitStrategy,
+    /// Smallest gamma a tail fit may select, typically `Some(-0.5)`
+    ///
+    /// See [`Tail::with_min_gamma`](crate::Tail::with_min_gamma). `None` (the
+    /// default) keeps every estimate like the C implementation.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub min_gamma: Option<f64>,
+    /// Number of recent `step` verdicts kept to monitor the observed anomaly rate
+    ///
+    /// See [`SpotDetector::observed_anomaly_rate`](crate::SpotDetector::observed_anomaly_rate).
+    /// The default, 0, keeps no verdicts.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub anomaly_rate_window: usize,
+    /// Whether the tail quantiles avoid the cancellation of the C formulas
+    /// for 0 < |gamma| < 1e-4
+    ///
+    /// See [`Tail::with_stable_small_gamma`](crate::Tail::with_stable_small_gamma).
+    /// The default, `false`, keeps the C formulas.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub stable_small_gamma: bool,
+}
+
+/// Policy growing the excess buffer of a detector beyond
+/// [`SpotConfig::max_excess`]
+///
+/// A fixed buffer is too small when excesses are frequent: it only spans the
+/// last few hundred of them and the GPD fit forgets quickly. With this policy,
+/// each time `step` is about to push an excess into a full buffer while the
+/// excess rate `nt / n` is above `min_excess_rate`, the buffer capacity is
+/// doubled (up to `max_excess`) instead of erasing the oldest excess. The
+/// capacity never shrinks back.
+///
+/// # Serialization
+///
+/// When the `serde` feature is enabled, this struct can be serialized and deserialized.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdaptiveTail {
+    /// Capacity the buffer may grow to
+    ///
+    /// A ceiling below [`SpotConfig::max_excess`] disables the growth.
+    pub max_excess: usize,
+    /// Excess rate above which a full buffer grows
+    pub min_excess_rate: f64,
+}
+
+/// Relative tolerance of [`SpotConfig::compatible_with`] on `q` and `level`
+const COMPATIBILITY_TOLERANCE: f64 = 1e-9;
+
+/// Whether `a` and `b` are equal up to [`COMPATIBILITY_TOLERANCE`]
+fn approx_equal(a: f64, b: f64) -> bool {
+    num_traits::Float::abs(a - b)
+        <= COMPATIBILITY_TOLERANCE * num_traits::Float::abs(a).max(num_traits::Float::abs(b))
+}
+
+impl AdaptiveTail {
+    /// Capacity after growing a full buffer of `capacity` excesses at the given
+    /// excess rate, or `None` if it keeps its size
+    pub(crate) fn grown_capacity(&self, capacity: usize, excess_rate: f64) -> Option<usize> {
+        if capacity < self.max_excess && excess_rate > self.min_excess_rate {
+            Some(capacity.saturating_mul(2).min(self.max_excess))
+        } else {
+            None
+        }
+    }
+}
+
+/// Default for [`SpotConfig::min_peaks`]
+pub(crate) fn default_min_peaks() -> usize {
+    10
+}
+
+/// Default for [`SpotConfig::reject_infinite`]
+pub(crate) fn default_reject_infinite() -> bool {
+    true
 }
 
 impl Default for SpotConfig {
-    /// Default configuration that matches the C implementation
+    /// Default configuration, close to the C implementation
+    ///
+    /// The parameters are those of the C library, but two safety options
+    /// change its behavior:
+    ///
+    /// - [`min_peaks`](Self::min_peaks) is 10: while the tail holds fewer
+    ///   excesses, `step` adds them to the tail without refitting the GPD,
+    ///   and a degenerate `fit` fails with
+    ///   [`SpotError::TooFewExcesses`] instead of
+    ///   [`SpotError::AnomalyThresholdIsNaN`]. C refits on every excess.
+    /// - [`reject_infinite`](Self::reject_infinite) is `true`: infinite values
+    ///   fail with [`SpotError::DataIsInfinite`], where C takes them into the
+    ///   model.
+    ///
+    /// With `min_peaks: 0` and `reject_infinite: false`, the detector matches
+    /// the C library bit for bit.
     fn default() -> Self {
         Self {
             q: 0.0001,
@@ -41,10 +206,256 @@ impl Default for SpotConfig {
             discard_anomalies: true,
             level: 0.998,
             max_excess: 200,
+            track_excess_threshold: false,
+            pwm_estimator: false,
+            min_peaks: default_min_peaks(),
+            grimshaw_options: None,
+            warmup_steps: 0,
+            reject_infinite: default_reject_infinite(),
+            adaptive_tail: None,
+            fit_strategy: FitStrategy::Both,
+            min_gamma: None,
+            anomaly_rate_window: 0,
+            stable_small_gamma: false,
         }
     }
 }
 
+impl SpotConfig {
+    /// Start building a configuration from the defaults
+    ///
+    /// ```
+    /// use libspot_rs::SpotConfig;
+    ///
+    /// let config = SpotConfig::builder().level(0.99).q(0.001).build().unwrap();
+    /// assert_eq!(config.level, 0.99);
+    /// ```
+    pub fn builder() -> SpotConfigBuilder {
+        SpotConfigBuilder::default()
+    }
+
+    /// Configuration for streams where anomalies are rare (`q = 1e-5`, `level = 0.999`)
+    ///
+    /// The tail only holds the top 0.1% of the data, so `fit` needs tens of
+    /// thousands of training values to gather enough excesses.
+    pub fn conservative() -> Self {
+        Self {
+            q: 1e-5,
+            level: 0.999,
+            max_excess: 500,
+            ..Self::default()
+        }
+    }
+
+    /// Configuration flagging more anomalies (`q = 1e-3`, `level = 0.98`)
+    ///
+    /// The tail holds the top 2% of the data, so a few thousand training
+    /// values are enough.
+    pub fn sensitive() -> Self {
+        Self {
+            q: 1e-3,
+            level: 0.98,
+            ..Self::default()
+        }
+    }
+
+    /// Configuration for power-law data (`q = 1e-4`, `level = 0.99`)
+    ///
+    /// Heavy tails make the method of moments unreliable (it needs gamma < 0.5)
+    /// and the fit sensitive to a few large excesses, so this enables the
+    /// [PWM estimator](Self::pwm_estimator), keeps more excesses and waits for
+    /// more of them before refitting.
+    pub fn for_heavy_tail() -> Self {
+        Self {
+            q: 1e-4,
+            level: 0.99,
+            max_excess: 1000,
+            pwm_estimator: true,
+            min_peaks: 30,
+            ..Self::default()
+        }
+    }
+
+    /// Whether a model built with `other` can be combined with or substituted
+    /// for one built with this configuration
+    ///
+    /// `q` and `level` must match up to a relative tolerance of `1e-9` (e.g.
+    /// after a round trip through a text format), and `low_tail` and
+    /// `max_excess` exactly. The other options, such as `discard_anomalies`,
+    /// only change how the model is updated and may differ.
+    ///
+    /// ```
+    /// use libspot_rs::SpotConfig;
+    ///
+    /// let config = SpotConfig::default();
+    /// let other = SpotConfig { discard_anomalies: false, ..SpotConfig::default() };
+    /// assert!(config.compatible_with(&other));
+    /// assert!(!config.compatible_with(&SpotConfig { level: 0.99, ..other }));
+    /// ```
+    pub fn compatible_with(&self, other: &SpotConfig) -> bool {
+        approx_equal(self.q, other.q)
+            && approx_equal(self.level, other.level)
+            && self.low_tail == other.low_tail
+            && self.max_excess == other.max_excess
+    }
+
+    /// Check the configuration without building a detector
+    ///
+    /// Fails with [`SpotError::LevelOutOfBounds`] unless `0 <= level < 1`, with
+    /// [`SpotError::QOutOfBounds`] unless `0 < q < 1 - level` (so for a NaN
+    /// level or q), with [`SpotError::MemoryAllocationFailed`] if `max_excess`
+    /// is 0, with [`SpotError::InvalidGrimshawOptions`] unless the Grimshaw
+    /// options (if set) have a positive epsilon and `max_iter`, and with
+    /// [`SpotError::ExcessRateOutOfBounds`] unless the
+    /// [`AdaptiveTail::min_excess_rate`] (if set) is in `[0, 1]`, in that
+    /// order. These are the checks (and errors) of
+    /// [`SpotDetector::new`](crate::SpotDetector::new), which calls it.
+    ///
+    /// ```
+    /// use libspot_rs::{SpotConfig, SpotError};
+    ///
+    /// let config = SpotConfig { level: 0.9, q: 0.2, ..SpotConfig::default() };
+    /// assert_eq!(config.validate(), Err(SpotError::QOutOfBounds));
+    /// ```
+    pub fn validate(&self) -> SpotResult<()> {
+        if !(0.0..1.0).contains(&self.level) {
+            return Err(SpotError::LevelOutOfBounds);
+        }
+        if !(self.q > 0.0 && self.q < 1.0 - self.level) {
+            return Err(SpotError::QOutOfBounds);
+        }
+        if self.max_excess == 0 {
+            return Err(SpotError::MemoryAllocationFailed);
+        }
+        if let Some(options) = self.grimshaw_options {
+            if !(options.epsilon > 0.0 && options.max_iter > 0) {
+                return Err(SpotError::InvalidGrimshawOptions);
+            }
+        }
+        if let Some(policy) = self.adaptive_tail {
+            if !(0.0..=1.0).contains(&policy.min_excess_rate) {
+                return Err(SpotError::ExcessRateOutOfBounds);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Builder for [`SpotConfig`], validating the parameters in [`build`](Self::build)
+///
+/// Fields that are not set keep their [`SpotConfig::default`] value.
+#[derive(Debug, Clone, Default)]
+pub struct SpotConfigBuilder {
+    config: SpotConfig,
+}
+
+impl SpotConfigBuilder {
+    /// Set the anomaly probability threshold
+    pub fn q(mut self, q: f64) -> Self {
+        self.config.q = q;
+        self
+    }
+
+    /// Set the excess level
+    pub fn level(mut self, level: f64) -> Self {
+        self.config.level = level;
+        self
+    }
+
+    /// Set the maximum number of excess data points to keep
+    pub fn max_excess(mut self, max_excess: usize) -> Self {
+        self.config.max_excess = max_excess;
+        self
+    }
+
+    /// Set whether to observe the lower tail
+    pub fn low_tail(mut self, low_tail: bool) -> Self {
+        self.config.low_tail = low_tail;
+        self
+    }
+
+    /// Set whether to discard anomalies from model updates
+    pub fn discard_anomalies(mut self, discard_anomalies: bool) -> Self {
+        self.config.discard_anomalies = discard_anomalies;
+        self
+    }
+
+    /// Set whether to keep re-estimating the excess threshold from recent data
+    pub fn track_excess_threshold(mut self, track_excess_threshold: bool) -> Self {
+        self.config.track_excess_threshold = track_excess_threshold;
+        self
+    }
+
+    /// Set whether the tail fit also considers the PWM estimator
+    pub fn pwm_estimator(mut self, pwm_estimator: bool) -> Self {
+        self.config.pwm_estimator = pwm_estimator;
+        self
+    }
+
+    /// Set the minimum number of peaks before the GPD is refitted in `step`
+    pub fn min_peaks(mut self, min_peaks: usize) -> Self {
+        self.config.min_peaks = min_peaks;
+        self
+    }
+
+    /// Set the root finding options of the Grimshaw estimator
+    pub fn grimshaw_options(mut self, grimshaw_options: GrimshawOptions) -> Self {
+        self.config.grimshaw_options = Some(grimshaw_options);
+        self
+    }
+
+    /// Set [`SpotConfig::warmup_steps`]
+    pub fn warmup_steps(mut self, warmup_steps: usize) -> Self {
+        self.config.warmup_steps = warmup_steps;
+        self
+    }
+
+    /// Set whether infinite values are rejected (see [`SpotConfig::reject_infinite`])
+    pub fn reject_infinite(mut self, reject_infinite: bool) -> Self {
+        self.config.reject_infinite = reject_infinite;
+        self
+    }
+
+    /// Set the growth policy of the excess buffer (see [`AdaptiveTail`])
+    pub fn adaptive_tail(mut self, adaptive_tail: AdaptiveTail) -> Self {
+        self.config.adaptive_tail = Some(adaptive_tail);
+        self
+    }
+
+    /// Set the estimators tried when fitting the tail
+    pub fn fit_strategy(mut self, fit_strategy: FitStrategy) -> Self {
+        self.config.fit_strategy = fit_strategy;
+        self
+    }
+
+    /// Set the smallest gamma a tail fit may select
+    pub fn min_gamma(mut self, min_gamma: f64) -> Self {
+        self.config.min_gamma = Some(min_gamma);
+        self
+    }
+
+    /// Set the number of recent verdicts used to monitor the anomaly rate
+    pub fn anomaly_rate_window(mut self, anomaly_rate_window: usize) -> Self {
+        self.config.anomaly_rate_window = anomaly_rate_window;
+        self
+    }
+
+    /// Set whether the tail quantiles avoid the cancellation of the C formulas
+    /// for gamma near 0 (see [`SpotConfig::stable_small_gamma`])
+    pub fn stable_small_gamma(mut self, stable_small_gamma: bool) -> Self {
+        self.config.stable_small_gamma = stable_small_gamma;
+        self
+    }
+
+    /// Validate and return the configuration
+    ///
+    /// Fails with the errors of [`SpotConfig::validate`].
+    pub fn build(self) -> SpotResult<SpotConfig> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -59,6 +470,15 @@ mod tests {
         assert!(config.discard_anomalies);
         assert_relative_eq!(config.level, 0.998);
         assert_eq!(config.max_excess, 200);
+        assert!(!config.track_excess_threshold);
+        assert!(!config.pwm_estimator);
+        assert_eq!(config.min_peaks, 10);
+        assert_eq!(config.warmup_steps, 0);
+        assert!(config.reject_infinite);
+        assert_eq!(config.adaptive_tail, None);
+        assert_eq!(config.fit_strategy, FitStrategy::Both);
+        assert_eq!(config.min_gamma, None);
+        assert_eq!(config.anomaly_rate_window, 0);
     }
 
     #[test]
@@ -72,4 +492,266 @@ mod tests {
         assert_relative_eq!(config1.level, config2.level);
         assert_eq!(config1.max_excess, config2.max_excess);
     }
+
+    #[test]
+    fn test_spot_config_builder() {
+        let config = SpotConfig::builder()
+            .q(0.001)
+            .level(0.99)
+            .max_excess(100)
+            .low_tail(true)
+            .discard_anomalies(false)
+            .warmup_steps(50)
+            .reject_infinite(false)
+            .adaptive_tail(AdaptiveTail {
+                max_excess: 800,
+                min_excess_rate: 0.05,
+            })
+            .fit_strategy(FitStrategy::Auto)
+            .min_gamma(-0.5)
+            .anomaly_rate_window(1000)
+            .stable_small_gamma(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            config,
+            SpotConfig {
+                q: 0.001,
+                low_tail: true,
+                discard_anomalies: false,
+                level: 0.99,
+                max_excess: 100,
+                track_excess_threshold: false,
+                pwm_estimator: false,
+                min_peaks: 10,
+                grimshaw_options: None,
+                warmup_steps: 50,
+                reject_infinite: false,
+                adaptive_tail: Some(AdaptiveTail {
+                    max_excess: 800,
+                    min_excess_rate: 0.05,
+                }),
+                fit_strategy: FitStrategy::Auto,
+                min_gamma: Some(-0.5),
+                anomaly_rate_window: 1000,
+                stable_small_gamma: true,
+            }
+        );
+        assert_eq!(SpotConfig::builder().build(), Ok(SpotConfig::default()));
+    }
+
+    #[test]
+    fn test_spot_config_builder_validation() {
+        assert_eq!(
+            SpotConfig::builder().level(0.9).q(0.1).build(),
+            Err(SpotError::QOutOfBounds)
+        );
+        assert_eq!(
+            SpotConfig::builder().q(0.0).build(),
+            Err(SpotError::QOutOfBounds)
+        );
+        assert_eq!(
+            SpotConfig::builder().level(1.0).build(),
+            Err(SpotError::LevelOutOfBounds)
+        );
+        assert_eq!(
+            SpotConfig::builder().max_excess(0).build(),
+            Err(SpotError::MemoryAllocationFailed)
+        );
+    }
+
+    #[test]
+    fn test_spot_config_validate_matches_detector_new() {
+        let cases = [
+            (SpotConfig::default(), Ok(())),
+            (
+                SpotConfig {
+                    level: -0.1,
+                    ..SpotConfig::default()
+                },
+                Err(SpotError::LevelOutOfBounds),
+            ),
+            (
+                SpotConfig {
+                    level: 1.0,
+                    ..SpotConfig::default()
+                },
+                Err(SpotError::LevelOutOfBounds),
+            ),
+            (
+                SpotConfig {
+                    q: 0.0,
+                    ..SpotConfig::default()
+                },
+                Err(SpotError::QOutOfBounds),
+            ),
+            (
+                SpotConfig {
+                    level: 0.9,
+                    q: 0.1,
+                    ..SpotConfig::default()
+                },
+                Err(SpotError::QOutOfBounds),
+            ),
+            (
+                SpotConfig {
+                    max_excess: 0,
+                    ..SpotConfig::default()
+                },
+                Err(SpotError::MemoryAllocationFailed),
+            ),
+            (
+                SpotConfig {
+                    level: f64::NAN,
+                    ..SpotConfig::default()
+                },
+                Err(SpotError::LevelOutOfBounds),
+            ),
+            (
+                SpotConfig {
+                    q: f64::NAN,
+                    ..SpotConfig::default()
+                },
+                Err(SpotError::QOutOfBounds),
+            ),
+            (
+                SpotConfig {
+                    grimshaw_options: Some(GrimshawOptions {
+                        epsilon: f64::NAN,
+                        ..GrimshawOptions::default()
+                    }),
+                    ..SpotConfig::default()
+                },
+                Err(SpotError::InvalidGrimshawOptions),
+            ),
+            (
+                SpotConfig {
+                    grimshaw_options: Some(GrimshawOptions {
+                        epsilon: 0.0,
+                        ..GrimshawOptions::default()
+                    }),
+                    ..SpotConfig::default()
+                },
+                Err(SpotError::InvalidGrimshawOptions),
+            ),
+            (
+                SpotConfig {
+                    grimshaw_options: Some(GrimshawOptions {
+                        max_iter: 0,
+                        ..GrimshawOptions::default()
+                    }),
+                    ..SpotConfig::default()
+                },
+                Err(SpotError::InvalidGrimshawOptions),
+            ),
+            (
+                SpotConfig {
+                    adaptive_tail: Some(AdaptiveTail {
+                        max_excess: 400,
+                        min_excess_rate: f64::NAN,
+                    }),
+                    ..SpotConfig::default()
+                },
+                Err(SpotError::ExcessRateOutOfBounds),
+            ),
+            (
+                SpotConfig {
+                    adaptive_tail: Some(AdaptiveTail {
+                        max_excess: 400,
+                        min_excess_rate: -0.1,
+                    }),
+                    ..SpotConfig::default()
+                },
+                Err(SpotError::ExcessRateOutOfBounds),
+            ),
+            (
+                // Bounds are checked before the capacity
+                SpotConfig {
+                    q: 0.5,
+                    max_excess: 0,
+                    ..SpotConfig::default()
+                },
+                Err(SpotError::QOutOfBounds),
+            ),
+        ];
+
+        for (config, expected) in cases {
+            assert_eq!(config.validate(), expected);
+            assert_eq!(
+                crate::SpotDetector::new(config.clone()).map(|_| ()),
+                expected
+            );
+            assert_eq!(crate::TwoTailedSpot::new(config).map(|_| ()), expected);
+        }
+    }
+
+    #[test]
+    fn test_spot_config_presets() {
+        for config in [
+            SpotConfig::conservative(),
+            SpotConfig::sensitive(),
+            SpotConfig::for_heavy_tail(),
+        ] {
+            assert_eq!(config.validate(), Ok(()));
+        }
+
+        // Exponential quantiles in a scrambled order
+        let n = 20_000;
+        let data: Vec<f64> = (0..n)
+            .map(|i| -(1.0 - ((i * 7919) % n) as f64 / n as f64).ln())
+            .collect();
+        let threshold = |config| {
+            let mut spot = crate::SpotDetector::new(config).unwrap();
+            spot.fit(&data).unwrap();
+            spot.anomaly_threshold()
+        };
+        assert!(threshold(SpotConfig::conservative()) > threshold(SpotConfig::sensitive()));
+    }
+
+    #[test]
+    fn test_spot_config_compatible_with() {
+        let config = SpotConfig::default();
+        assert!(config.compatible_with(&config));
+
+        // Update options may differ
+        let other = SpotConfig {
+            discard_anomalies: false,
+            min_peaks: 20,
+            warmup_steps: 100,
+            ..SpotConfig::default()
+        };
+        assert!(config.compatible_with(&other));
+        assert!(other.compatible_with(&config));
+
+        // Tolerance on q: 1e-9 relative
+        let q = config.q;
+        let within = SpotConfig {
+            q: q * (1.0 + 0.9e-9),
+            ..SpotConfig::default()
+        };
+        let beyond = SpotConfig {
+            q: q * (1.0 + 1.1e-9),
+            ..SpotConfig::default()
+        };
+        assert!(config.compatible_with(&within));
+        assert!(!config.compatible_with(&beyond));
+
+        for other in [
+            SpotConfig {
+                level: 0.99,
+                ..SpotConfig::default()
+            },
+            SpotConfig {
+                low_tail: true,
+                ..SpotConfig::default()
+            },
+            SpotConfig {
+                max_excess: 100,
+                ..SpotConfig::default()
+            },
+        ] {
+            assert!(!config.compatible_with(&other));
+        }
+    }
 }
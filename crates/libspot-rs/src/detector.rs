@@ -0,0 +1,137 @@
+//! Backend-agnostic detector interface
+//!
+//! The [`AnomalyDetector`] trait captures the `fit`/`step`/thresholds API shared
+//! by the SPOT variants of this crate, so that code can be written once and run
+//! against any of them, including behind a `Box<dyn AnomalyDetector>`. The FFI
+//! bindings of the `libspot` crate have the same methods but do not implement
+//! the trait, since neither crate depends on the other.
+
+use crate::dspot::DSpotDetector;
+use crate::error::SpotResult;
+use crate::rolling::RollingSpot;
+use crate::spot::SpotDetector;
+use crate::status::SpotStatus;
+
+/// A detector that is fitted on a batch and then classifies values one by one
+///
+/// The trait is object safe.
+///
+/// ```
+/// use libspot_rs::{AnomalyDetector, SpotConfig, SpotDetector, SpotStatus};
+///
+/// let mut detector: Box<dyn AnomalyDetector> =
+///     Box::new(SpotDetector::new(SpotConfig::default()).unwrap());
+/// let data: Vec<f64> = (0..1000).map(|i| ((i * 37) % 1000) as f64 / 100.0).collect();
+/// detector.fit(&data).unwrap();
+/// assert_eq!(detector.step(50.0), Ok(SpotStatus::Anomaly));
+/// ```
+pub trait AnomalyDetector {
+    /// Fit the model using initial training data
+    fn fit(&mut self, data: &[f64]) -> SpotResult<()>;
+
+    /// Classify a single value, updating the model
+    fn step(&mut self, value: f64) -> SpotResult<SpotStatus>;
+
+    /// Get the current anomaly threshold
+    fn anomaly_threshold(&self) -> f64;
+
+    /// Get the current excess threshold
+    fn excess_threshold(&self) -> f64;
+}
+
+impl AnomalyDetector for SpotDetector {
+    fn fit(&mut self, data: &[f64]) -> SpotResult<()> {
+        SpotDetector::fit(self, data)
+    }
+
+    fn step(&mut self, value: f64) -> SpotResult<SpotStatus> {
+        SpotDetector::step(self, value)
+    }
+
+    fn anomaly_threshold(&self) -> f64 {
+        SpotDetector::anomaly_threshold(self)
+    }
+
+    fn excess_threshold(&self) -> f64 {
+        SpotDetector::excess_threshold(self)
+    }
+}
+
+impl AnomalyDetector for RollingSpot {
+    fn fit(&mut self, data: &[f64]) -> SpotResult<()> {
+        RollingSpot::fit(self, data)
+    }
+
+    fn step(&mut self, value: f64) -> SpotResult<SpotStatus> {
+        RollingSpot::step(self, value)
+    }
+
+    fn anomaly_threshold(&self) -> f64 {
+        RollingSpot::anomaly_threshold(self)
+    }
+
+    fn excess_threshold(&self) -> f64 {
+        RollingSpot::excess_threshold(self)
+    }
+}
+
+/// The thresholds apply to the residuals, not to the raw values
+impl AnomalyDetector for DSpotDetector {
+    fn fit(&mut self, data: &[f64]) -> SpotResult<()> {
+        DSpotDetector::fit(self, data)
+    }
+
+    fn step(&mut self, value: f64) -> SpotResult<SpotStatus> {
+        DSpotDetector::step(self, value)
+    }
+
+    fn anomaly_threshold(&self) -> f64 {
+        DSpotDetector::anomaly_threshold(self)
+    }
+
+    fn excess_threshold(&self) -> f64 {
+        DSpotDetector::excess_threshold(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SpotConfig;
+    use crate::dspot::DSpotConfig;
+    use crate::error::SpotError;
+    use crate::rolling::RollingSpotConfig;
+    use crate::testing::exponential_data;
+    use alloc::boxed::Box;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_boxed_detectors_fit_and_step() {
+        let mut detectors: Vec<Box<dyn AnomalyDetector>> = vec![
+            Box::new(SpotDetector::new(SpotConfig::default()).unwrap()),
+            Box::new(RollingSpot::new(RollingSpotConfig::default()).unwrap()),
+            Box::new(DSpotDetector::new(DSpotConfig::default()).unwrap()),
+        ];
+        let data = exponential_data(10_000, 7);
+
+        for detector in &mut detectors {
+            detector.fit(&data).unwrap();
+            assert!(detector.excess_threshold().is_finite());
+            assert!(detector.anomaly_threshold() > detector.excess_threshold());
+            assert_eq!(detector.step(f64::NAN), Err(SpotError::DataIsNaN));
+            for &x in &data[..100] {
+                detector.step(x).unwrap();
+            }
+        }
+
+        // The trait object behaves like the concrete detector
+        let mut plain = SpotDetector::new(SpotConfig::default()).unwrap();
+        plain.fit(&data).unwrap();
+        for &x in &data[..100] {
+            plain.step(x).unwrap();
+        }
+        assert_eq!(detectors[0].anomaly_threshold(), plain.anomaly_threshold());
+        assert_eq!(detectors[0].step(100.0), Ok(SpotStatus::Anomaly));
+    }
+}
@@ -0,0 +1,250 @@
+//! Drift SPOT (DSPOT) detector
+//!
+//! DSPOT runs the SPOT algorithm on the residuals of the stream with respect to a
+//! local baseline: the mean of the last `depth` values. This removes slow drifts
+//! that would otherwise push the whole stream above the fixed excess threshold
+//! learned by [`SpotDetector::fit`].
+
+use alloc::vec::Vec;
+
+use crate::config::SpotConfig;
+use crate::error::{SpotError, SpotResult};
+use crate::spot::{finite_training_data, SpotDetector};
+use crate::status::SpotStatus;
+use crate::ubend::Ubend;
+
+/// Configuration parameters for the DSPOT detector
+///
+/// # Serialization
+///
+/// When the `serde` feature is enabled, this struct can be serialized and deserialized.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DSpotConfig {
+    /// Number of past values used to compute the local mean (must be positive)
+    pub depth: usize,
+    /// Configuration of the SPOT detector run on the residuals
+    pub spot: SpotConfig,
+}
+
+impl Default for DSpotConfig {
+    fn default() -> Self {
+        Self {
+            depth: 10,
+            spot: SpotConfig::default(),
+        }
+    }
+}
+
+/// SPOT detector applied to the deviations from a moving average
+///
+/// # Serialization
+///
+/// When the `serde` feature is enabled, this struct can be serialized and deserialized.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DSpotDetector {
+    /// Last `depth` values of the stream
+    window: Ubend,
+    /// Sum of the values in the window
+    sum: f64,
+    /// SPOT detector working on the residuals
+    spot: SpotDetector,
+}
+
+impl DSpotDetector {
+    /// Create a new DSPOT detector with the given configuration
+    pub fn new(config: DSpotConfig) -> SpotResult<Self> {
+        if config.depth == 0 {
+            return Err(SpotError::DepthOutOfBounds);
+        }
+
+        Ok(Self {
+            window: Ubend::new(config.depth)?,
+            sum: 0.0,
+            spot: SpotDetector::new(config.spot)?,
+        })
+    }
+
+    /// Fit the model using initial training data
+    ///
+    /// The first `depth` values only seed the moving average: the underlying
+    /// SPOT detector is fitted on the residuals of the remaining ones, so `data`
+    /// must be longer than `depth`.
+    pub fn fit(&mut self, data: &[f64]) -> SpotResult<()> {
+        let reject_infinite = self.spot.config().is_some_and(|c| c.reject_infinite);
+        let data = &*finite_training_data(data, reject_infinite)?;
+        let depth = self.depth();
+        if data.len() <= depth {
+            return Err(SpotError::DepthOutOfBounds);
+        }
+
+        self.window.clear();
+        self.sum = 0.0;
+        for &value in &data[..depth] {
+            self.push(value);
+        }
+
+        let mut residuals = Vec::with_capacity(data.len() - depth);
+        for &value in &data[depth..] {
+            residuals.push(value - self.baseline());
+            self.push(value);
+        }
+
+        self.spot.fit(&residuals)
+    }
+
+    /// Process a new data point and return its classification
+    ///
+    /// The value is classified by its deviation from the current moving average
+    /// and then enters the window, unless it is an anomaly and the configuration
+    /// discards anomalies.
+    pub fn step(&mut self, value: f64) -> SpotResult<SpotStatus> {
+        if value.is_nan() {
+            return Err(SpotError::DataIsNaN);
+        }
+
+        let status = self.spot.step(value - self.baseline())?;
+        let discarded = status == SpotStatus::Anomaly
+            && self.spot.config().is_some_and(|c| c.discard_anomalies);
+        // Infinite values accepted by the detector would poison the average
+        if !discarded && value.is_finite() {
+            self.push(value);
+        }
+
+        Ok(status)
+    }
+
+    /// Add a value to the window and update the running sum
+    fn push(&mut self, value: f64) {
+        let erased = self.window.push(value);
+        self.sum += value;
+        if !erased.is_nan() {
+            self.sum -= erased;
+        }
+        // Recompute the sum once per cycle to avoid accumulating rounding errors
+        if self.window.cursor() == 0 {
+            self.sum = self.window.iter().sum();
+        }
+    }
+
+    /// Get the current moving average the values are compared against
+    pub fn baseline(&self) -> f64 {
+        self.sum / self.window.size() as f64
+    }
+
+    /// Get the number of values used for the moving average
+    pub fn depth(&self) -> usize {
+        self.window.capacity()
+    }
+
+    /// Get the current anomaly threshold on the scale of the input values
+    pub fn anomaly_threshold(&self) -> f64 {
+        self.baseline() + self.spot.anomaly_threshold()
+    }
+
+    /// Get the current excess threshold on the scale of the input values
+    pub fn excess_threshold(&self) -> f64 {
+        self.baseline() + self.spot.excess_threshold()
+    }
+
+    /// Get the underlying SPOT detector working on the residuals
+    pub fn spot(&self) -> &SpotDetector {
+        &self.spot
+    }
+
+    /// Get the current configuration (reconstructed)
+    pub fn config(&self) -> Option<DSpotConfig> {
+        Some(DSpotConfig {
+            depth: self.depth(),
+            spot: self.spot.config()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::exponential_data;
+    use approx::assert_relative_eq;
+
+    /// Exponential noise on top of a slow linear drift
+    fn drifting_data(n: usize, slope: f64) -> Vec<f64> {
+        exponential_data(n, 11)
+            .into_iter()
+            .enumerate()
+            .map(|(i, x)| slope * i as f64 + x)
+            .collect()
+    }
+
+    #[test]
+    fn test_dspot_invalid_depth() {
+        let config = DSpotConfig {
+            depth: 0,
+            ..DSpotConfig::default()
+        };
+        assert_eq!(
+            DSpotDetector::new(config).unwrap_err(),
+            SpotError::DepthOutOfBounds
+        );
+
+        let mut dspot = DSpotDetector::new(DSpotConfig::default()).unwrap();
+        assert_eq!(
+            dspot.fit(&[1.0; 10]).unwrap_err(),
+            SpotError::DepthOutOfBounds
+        );
+    }
+
+    #[test]
+    fn test_dspot_baseline_is_window_mean() {
+        let mut dspot = DSpotDetector::new(DSpotConfig::default()).unwrap();
+        let data = drifting_data(5_000, 0.0);
+        dspot.fit(&data).unwrap();
+
+        let expected = data[data.len() - 10..].iter().sum::<f64>() / 10.0;
+        assert_relative_eq!(dspot.baseline(), expected, epsilon = 1e-12);
+        assert_eq!(dspot.step(f64::NAN), Err(SpotError::DataIsNaN));
+    }
+
+    /// Count the values classified as `status`
+    fn count(statuses: &[SpotStatus], status: SpotStatus) -> usize {
+        statuses.iter().filter(|&&s| s == status).count()
+    }
+
+    #[test]
+    fn test_dspot_handles_linear_drift() {
+        let data = drifting_data(40_000, 0.001);
+        let (train, stream) = data.split_at(10_000);
+
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        spot.fit(train).unwrap();
+        let spot_statuses: Vec<SpotStatus> =
+            stream.iter().map(|&x| spot.step(x).unwrap()).collect();
+
+        let config = DSpotConfig {
+            depth: 50,
+            ..DSpotConfig::default()
+        };
+        let mut dspot = DSpotDetector::new(config).unwrap();
+        dspot.fit(train).unwrap();
+        let dspot_statuses: Vec<SpotStatus> =
+            stream.iter().map(|&x| dspot.step(x).unwrap()).collect();
+
+        // The drift moves the whole stream above the fixed SPOT excess threshold,
+        // while DSPOT keeps the tail rate close to 1 - level.
+        let spot_normal = count(&spot_statuses, SpotStatus::Normal);
+        let dspot_normal = count(&dspot_statuses, SpotStatus::Normal);
+        assert!(spot_normal < stream.len() / 2, "spot normal: {spot_normal}");
+        assert!(
+            dspot_normal > stream.len() * 99 / 100,
+            "dspot normal: {dspot_normal}"
+        );
+
+        let spot_anomalies = count(&spot_statuses, SpotStatus::Anomaly);
+        let dspot_anomalies = count(&dspot_statuses, SpotStatus::Anomaly);
+        assert!(
+            dspot_anomalies < spot_anomalies,
+            "spot: {spot_anomalies}, dspot: {dspot_anomalies}"
+        );
+    }
+}
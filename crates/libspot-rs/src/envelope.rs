@@ -0,0 +1,91 @@
+//! Versioned container for serialized SPOT models
+//!
+//! A [`ModelEnvelope`] wraps a detector with the version of its serialized
+//! layout and the version of the crate that wrote it, so that a model shipped
+//! to another process (possibly another language, through CBOR or JSON) can be
+//! checked before use.
+
+use alloc::string::String;
+
+use crate::config::SpotConfig;
+use crate::error::{SpotError, SpotResult};
+use crate::float::Float;
+use crate::spot::SpotDetector;
+
+/// Version of the serialized model layout written by this crate
+///
+/// The [model schema version](crate::model_schema_version) of the crate.
+/// [`SpotDetector::load_envelope`] rejects envelopes written with a newer one.
+pub const MODEL_ENVELOPE_VERSION: u32 = crate::MODEL_SCHEMA_VERSION;
+
+/// Serializable model with its format and crate versions
+///
+/// [`SpotDetector::save_envelope`] borrows the detector (`M = &SpotDetector`),
+/// while deserialization produces an owned one (`M = SpotDetector`) to pass to
+/// [`SpotDetector::load_envelope`]. The envelope works with any serde format.
+///
+/// # Example
+///
+/// ```
+/// use libspot_rs::{ModelEnvelope, SpotConfig, SpotDetector};
+///
+/// let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+/// let data: Vec<f64> = (0..1000).map(|i| (i as f64) / 100.0).collect();
+/// spot.fit(&data).unwrap();
+///
+/// let json = serde_json::to_string(&spot.save_envelope()).unwrap();
+/// let envelope: ModelEnvelope = serde_json::from_str(&json).unwrap();
+/// let loaded = SpotDetector::load_envelope(envelope).unwrap();
+/// assert_eq!(loaded.anomaly_threshold(), spot.anomaly_threshold());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ModelEnvelope<M = SpotDetector> {
+    /// Version of the serialized model layout ([`MODEL_ENVELOPE_VERSION`] when written)
+    pub version: u32,
+    /// Version of the crate that wrote the model
+    pub crate_version: String,
+    /// The model itself
+    pub model: M,
+}
+
+impl<F: Float> SpotDetector<F> {
+    /// Wrap the detector in a versioned envelope for serialization
+    pub fn save_envelope(&self) -> ModelEnvelope<&Self> {
+        ModelEnvelope {
+            version: MODEL_ENVELOPE_VERSION,
+            crate_version: crate::version(),
+            model: self,
+        }
+    }
+
+    /// Unwrap a detector from a deserialized envelope
+    ///
+    /// Fails with [`SpotError::VersionMismatch`] if the envelope was written
+    /// with a newer [`MODEL_ENVELOPE_VERSION`].
+    pub fn load_envelope(envelope: ModelEnvelope<Self>) -> SpotResult<Self> {
+        if envelope.version > MODEL_ENVELOPE_VERSION {
+            return Err(SpotError::VersionMismatch {
+                found: envelope.version,
+                expected: MODEL_ENVELOPE_VERSION,
+            });
+        }
+        Ok(envelope.model)
+    }
+
+    /// Unwrap a detector from a deserialized envelope, checking that it was
+    /// built with a configuration [compatible](SpotConfig::compatible_with)
+    /// with `config`
+    ///
+    /// Fails like [`load_envelope`](Self::load_envelope), and with
+    /// [`SpotError::ConfigMismatch`] if the configurations are not compatible.
+    pub fn load_envelope_for(
+        envelope: ModelEnvelope<Self>,
+        config: &SpotConfig,
+    ) -> SpotResult<Self> {
+        let model = Self::load_envelope(envelope)?;
+        if !model.is_compatible_with(config) {
+            return Err(SpotError::ConfigMismatch);
+        }
+        Ok(model)
+    }
+}
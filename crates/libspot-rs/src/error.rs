@@ -2,7 +2,9 @@
 //!
 //! This module defines error types that match the C implementation exactly.
 
-use std::fmt;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io;
 
 /// Result type for SPOT operations
 pub type SpotResult<T> = Result<T, SpotError>;
@@ -16,17 +18,80 @@ pub type SpotResult<T> = Result<T, SpotError>;
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SpotError {
     /// Memory allocation failed
-    MemoryAllocationFailed = 1000,
+    MemoryAllocationFailed,
     /// The level parameter must be between 0 and 1
-    LevelOutOfBounds = 1001,
+    LevelOutOfBounds,
     /// The q parameter must be between 0 and 1-level
-    QOutOfBounds = 1002,
+    QOutOfBounds,
     /// The excess threshold has not been initialized
-    ExcessThresholdIsNaN = 1003,
+    ExcessThresholdIsNaN,
     /// The anomaly threshold has not been initialized
-    AnomalyThresholdIsNaN = 1004,
+    AnomalyThresholdIsNaN,
     /// The input data is NaN
-    DataIsNaN = 1005,
+    DataIsNaN,
+    /// The validation fraction must be between 0 and 1 (pure Rust only)
+    ValidationFractionOutOfBounds,
+    /// The counters must satisfy nt <= n with n > 0 (pure Rust only)
+    CountersOutOfBounds,
+    /// The DSPOT depth must be positive and lower than the training size (pure Rust only)
+    DepthOutOfBounds,
+    /// The tail holds too few excesses to be fitted (pure Rust only)
+    ///
+    /// Returned by `fit` when the fit is degenerate and the tail holds less
+    /// than [`SpotConfig::min_peaks`](crate::SpotConfig::min_peaks) excesses.
+    TooFewExcesses {
+        /// Number of excesses in the tail
+        have: usize,
+        /// Minimum number of excesses required
+        need: usize,
+    },
+    /// The serialized model was written with an unsupported format version (pure Rust only)
+    VersionMismatch {
+        /// Format version of the serialized model
+        found: u32,
+        /// Format version supported by this crate
+        expected: u32,
+    },
+    /// The detectors were built with different configurations (pure Rust only)
+    ConfigMismatch,
+    /// The integer is not a valid [`SpotStatus`](crate::SpotStatus) code (pure Rust only)
+    InvalidStatusCode(i32),
+    /// The decay factor must be in (0, 1] (pure Rust only)
+    DecayOutOfBounds,
+    /// The parameter cannot be changed once the detector is fitted (pure Rust only)
+    AlreadyFitted,
+    /// The input data is infinite (pure Rust only)
+    ///
+    /// Returned unless [`SpotConfig::reject_infinite`](crate::SpotConfig::reject_infinite)
+    /// is disabled.
+    DataIsInfinite,
+    /// The weights must match the values one to one, be finite and non-negative,
+    /// and not all be zero (pure Rust only)
+    InvalidWeights,
+    /// A serialized model could not be read, with the kind of format error
+    /// (pure Rust only)
+    Deserialization(DeserializationKind),
+    /// The GPD parameters must be finite with a positive sigma (pure Rust only)
+    InvalidGpdParameters,
+    /// The operation needs a fitted detector (pure Rust only)
+    NotFitted,
+    /// The Grimshaw options need a positive epsilon and a positive number of
+    /// iterations (pure Rust only)
+    InvalidGrimshawOptions,
+    /// The excess rate of the adaptive tail policy must be in [0, 1] (pure Rust only)
+    ExcessRateOutOfBounds,
+    /// The training data must be non-empty with a finite, positive standard
+    /// deviation (pure Rust only)
+    DegenerateData,
+    /// The configuration sets an option the detector does not support (pure Rust only)
+    UnsupportedOption,
+    /// An I/O error occurred in the surrounding pipeline (pure Rust only, requires `std`)
+    ///
+    /// This variant cannot be serialized since [`io::ErrorKind`] does not
+    /// implement the serde traits.
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Io(io::ErrorKind),
 }
 
 impl SpotError {
@@ -39,6 +104,29 @@ impl SpotError {
             1003 => SpotError::ExcessThresholdIsNaN,
             1004 => SpotError::AnomalyThresholdIsNaN,
             1005 => SpotError::DataIsNaN,
+            1006 => SpotError::ValidationFractionOutOfBounds,
+            #[cfg(feature = "std")]
+            1007 => SpotError::Io(io::ErrorKind::Other),
+            1008 => SpotError::CountersOutOfBounds,
+            1009 => SpotError::DepthOutOfBounds,
+            1010 => SpotError::TooFewExcesses { have: 0, need: 0 },
+            1011 => SpotError::VersionMismatch {
+                found: 0,
+                expected: 0,
+            },
+            1012 => SpotError::ConfigMismatch,
+            1013 => SpotError::InvalidStatusCode(0),
+            1014 => SpotError::DecayOutOfBounds,
+            1015 => SpotError::AlreadyFitted,
+            1016 => SpotError::DataIsInfinite,
+            1017 => SpotError::InvalidWeights,
+            1018 => SpotError::Deserialization(DeserializationKind::Data),
+            1019 => SpotError::InvalidGpdParameters,
+            1020 => SpotError::NotFitted,
+            1021 => SpotError::InvalidGrimshawOptions,
+            1022 => SpotError::ExcessRateOutOfBounds,
+            1023 => SpotError::DegenerateData,
+            1024 => SpotError::UnsupportedOption,
             _ => SpotError::MemoryAllocationFailed, // Default fallback
         }
     }
@@ -54,22 +142,157 @@ impl SpotError {
             SpotError::ExcessThresholdIsNaN => "The excess threshold has not been initialized",
             SpotError::AnomalyThresholdIsNaN => "The anomaly threshold has not been initialized",
             SpotError::DataIsNaN => "The input data is NaN",
+            SpotError::ValidationFractionOutOfBounds => {
+                "The validation fraction must be between 0 and 1 (exclusive)"
+            }
+            #[cfg(feature = "std")]
+            SpotError::Io(_) => "An I/O error occurred",
+            SpotError::CountersOutOfBounds => {
+                "The number of excesses must not exceed the (positive) number of observations"
+            }
+            SpotError::DepthOutOfBounds => {
+                "The depth must be positive and lower than the number of training points"
+            }
+            SpotError::TooFewExcesses { .. } => "The tail holds too few excesses to be fitted",
+            SpotError::VersionMismatch { .. } => {
+                "The model was serialized with an unsupported format version"
+            }
+            SpotError::ConfigMismatch => "The detectors have different configurations",
+            SpotError::InvalidStatusCode(_) => "The status code must be 0, 1 or 2",
+            SpotError::DecayOutOfBounds => "The decay factor must be in (0, 1]",
+            SpotError::AlreadyFitted => {
+                "The parameter cannot be changed once the detector is fitted (reset it first)"
+            }
+            SpotError::DataIsInfinite => "The input data is infinite",
+            SpotError::InvalidWeights => {
+                "The weights must be as many as the values, finite, non-negative and not all zero"
+            }
+            SpotError::Deserialization(_) => "The serialized model could not be read",
+            SpotError::InvalidGpdParameters => {
+                "The GPD parameters must be finite and sigma must be positive"
+            }
+            SpotError::NotFitted => "The detector must be fitted first",
+            SpotError::InvalidGrimshawOptions => {
+                "The Grimshaw epsilon and maximum number of iterations must be positive"
+            }
+            SpotError::ExcessRateOutOfBounds => "The minimum excess rate must be in [0, 1]",
+            SpotError::DegenerateData => {
+                "The training data must be non-empty with a finite, positive standard deviation"
+            }
+            SpotError::UnsupportedOption => {
+                "The configuration sets an option the detector does not support"
+            }
         }
     }
 
     /// Get error code
     pub fn code(&self) -> i32 {
-        *self as i32
+        match self {
+            SpotError::MemoryAllocationFailed => 1000,
+            SpotError::LevelOutOfBounds => 1001,
+            SpotError::QOutOfBounds => 1002,
+            SpotError::ExcessThresholdIsNaN => 1003,
+            SpotError::AnomalyThresholdIsNaN => 1004,
+            SpotError::DataIsNaN => 1005,
+            SpotError::ValidationFractionOutOfBounds => 1006,
+            #[cfg(feature = "std")]
+            SpotError::Io(_) => 1007,
+            SpotError::CountersOutOfBounds => 1008,
+            SpotError::DepthOutOfBounds => 1009,
+            SpotError::TooFewExcesses { .. } => 1010,
+            SpotError::VersionMismatch { .. } => 1011,
+            SpotError::ConfigMismatch => 1012,
+            SpotError::InvalidStatusCode(_) => 1013,
+            SpotError::DecayOutOfBounds => 1014,
+            SpotError::AlreadyFitted => 1015,
+            SpotError::DataIsInfinite => 1016,
+            SpotError::InvalidWeights => 1017,
+            SpotError::Deserialization(_) => 1018,
+            SpotError::InvalidGpdParameters => 1019,
+            SpotError::NotFitted => 1020,
+            SpotError::InvalidGrimshawOptions => 1021,
+            SpotError::ExcessRateOutOfBounds => 1022,
+            SpotError::DegenerateData => 1023,
+            SpotError::UnsupportedOption => 1024,
+        }
     }
 }
 
 impl fmt::Display for SpotError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.message())
+        match self {
+            #[cfg(feature = "std")]
+            SpotError::Io(kind) => write!(f, "{}: {}", self.message(), kind),
+            SpotError::TooFewExcesses { have, need } => {
+                write!(f, "{} ({} < {})", self.message(), have, need)
+            }
+            SpotError::VersionMismatch { found, expected } => {
+                write!(f, "{} ({}, expected {})", self.message(), found, expected)
+            }
+            SpotError::InvalidStatusCode(code) => write!(f, "{} (got {})", self.message(), code),
+            SpotError::Deserialization(kind) => write!(f, "{} ({})", self.message(), kind),
+            _ => write!(f, "{}", self.message()),
+        }
+    }
+}
+
+impl core::error::Error for SpotError {}
+
+/// Kind of format error of [`SpotError::Deserialization`]
+///
+/// # Serialization
+///
+/// When the `serde` feature is enabled, this enum can be serialized and deserialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DeserializationKind {
+    /// The input is not valid in the format (e.g. malformed JSON)
+    Syntax,
+    /// The input is valid but is not a serialized model (e.g. a missing field,
+    /// or a model written with a newer
+    /// [model schema version](crate::model_schema_version))
+    Data,
+    /// The input ends in the middle of a value
+    Eof,
+    /// The input could not be read
+    Io,
+}
+
+impl fmt::Display for DeserializationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self {
+            DeserializationKind::Syntax => "syntax error",
+            DeserializationKind::Data => "invalid data",
+            DeserializationKind::Eof => "unexpected end of input",
+            DeserializationKind::Io => "I/O error",
+        };
+        f.write_str(kind)
     }
 }
 
-impl std::error::Error for SpotError {}
+/// Wrap the error as [`io::ErrorKind::InvalidData`], keeping it as the inner error
+#[cfg(feature = "std")]
+impl From<SpotError> for io::Error {
+    fn from(error: SpotError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, error)
+    }
+}
+
+/// Recover the original [`SpotError`] if the I/O error wraps one, otherwise
+/// map it to [`SpotError::Io`]
+#[cfg(feature = "std")]
+impl From<io::Error> for SpotError {
+    fn from(error: io::Error) -> Self {
+        let kind = error.kind();
+        match error
+            .into_inner()
+            .map(|inner| inner.downcast::<SpotError>())
+        {
+            Some(Ok(spot_error)) => *spot_error,
+            _ => SpotError::Io(kind),
+        }
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -83,6 +306,34 @@ mod tests {
         assert_eq!(SpotError::ExcessThresholdIsNaN.code(), 1003);
         assert_eq!(SpotError::AnomalyThresholdIsNaN.code(), 1004);
         assert_eq!(SpotError::DataIsNaN.code(), 1005);
+        assert_eq!(SpotError::ValidationFractionOutOfBounds.code(), 1006);
+        assert_eq!(SpotError::CountersOutOfBounds.code(), 1008);
+        assert_eq!(SpotError::DepthOutOfBounds.code(), 1009);
+        assert_eq!(SpotError::TooFewExcesses { have: 2, need: 10 }.code(), 1010);
+        assert_eq!(
+            SpotError::VersionMismatch {
+                found: 2,
+                expected: 1
+            }
+            .code(),
+            1011
+        );
+        assert_eq!(SpotError::ConfigMismatch.code(), 1012);
+        assert_eq!(SpotError::InvalidStatusCode(3).code(), 1013);
+        assert_eq!(SpotError::DecayOutOfBounds.code(), 1014);
+        assert_eq!(SpotError::AlreadyFitted.code(), 1015);
+        assert_eq!(SpotError::DataIsInfinite.code(), 1016);
+        assert_eq!(SpotError::InvalidWeights.code(), 1017);
+        assert_eq!(
+            SpotError::Deserialization(DeserializationKind::Eof).code(),
+            1018
+        );
+        assert_eq!(SpotError::InvalidGpdParameters.code(), 1019);
+        assert_eq!(SpotError::NotFitted.code(), 1020);
+        assert_eq!(SpotError::InvalidGrimshawOptions.code(), 1021);
+        assert_eq!(SpotError::ExcessRateOutOfBounds.code(), 1022);
+        assert_eq!(SpotError::DegenerateData.code(), 1023);
+        assert_eq!(SpotError::UnsupportedOption.code(), 1024);
     }
 
     #[test]
@@ -99,6 +350,26 @@ mod tests {
             SpotError::AnomalyThresholdIsNaN
         );
         assert_eq!(SpotError::from_code(-1005), SpotError::DataIsNaN);
+        assert_eq!(
+            SpotError::from_code(-1006),
+            SpotError::ValidationFractionOutOfBounds
+        );
+        assert_eq!(SpotError::from_code(-1008), SpotError::CountersOutOfBounds);
+        assert_eq!(
+            SpotError::from_code(-1010),
+            SpotError::TooFewExcesses { have: 0, need: 0 }
+        );
+        assert_eq!(SpotError::from_code(-1020), SpotError::NotFitted);
+        assert_eq!(
+            SpotError::from_code(-1021),
+            SpotError::InvalidGrimshawOptions
+        );
+        assert_eq!(
+            SpotError::from_code(-1022),
+            SpotError::ExcessRateOutOfBounds
+        );
+        assert_eq!(SpotError::from_code(-1023), SpotError::DegenerateData);
+        assert_eq!(SpotError::from_code(-1024), SpotError::UnsupportedOption);
     }
 
     #[test]
@@ -117,5 +388,69 @@ mod tests {
     fn test_error_display() {
         let error = SpotError::DataIsNaN;
         assert_eq!(format!("{}", error), "The input data is NaN");
+
+        let error = SpotError::TooFewExcesses { have: 2, need: 10 };
+        assert_eq!(
+            format!("{}", error),
+            "The tail holds too few excesses to be fitted (2 < 10)"
+        );
+
+        let error = SpotError::InvalidStatusCode(7);
+        assert_eq!(
+            format!("{}", error),
+            "The status code must be 0, 1 or 2 (got 7)"
+        );
+
+        let error = SpotError::Deserialization(DeserializationKind::Syntax);
+        assert_eq!(
+            format!("{}", error),
+            "The serialized model could not be read (syntax error)"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_io_error_display() {
+        let error = SpotError::Io(io::ErrorKind::UnexpectedEof);
+        assert_eq!(
+            format!("{}", error),
+            "An I/O error occurred: unexpected end of file"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_io_error_roundtrip() {
+        let io_error: io::Error = SpotError::DataIsNaN.into();
+        assert_eq!(io_error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(io_error.to_string(), "The input data is NaN");
+
+        let back: SpotError = io_error.into();
+        assert_eq!(back, SpotError::DataIsNaN);
+        assert_eq!(back.to_string(), "The input data is NaN");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_from_foreign_io_error() {
+        let io_error = io::Error::new(io::ErrorKind::NotFound, "missing file");
+        let error: SpotError = io_error.into();
+        assert_eq!(error, SpotError::Io(io::ErrorKind::NotFound));
+        assert_eq!(error.code(), 1007);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_question_mark_in_io_result() {
+        fn process(value: f64) -> io::Result<f64> {
+            if value.is_nan() {
+                Err(SpotError::DataIsNaN)?;
+            }
+            Ok(value)
+        }
+
+        let err = process(f64::NAN).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), SpotError::DataIsNaN.message());
     }
 }
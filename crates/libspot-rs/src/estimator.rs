@@ -1,11 +1,18 @@
 //! GPD parameter estimators
 //!
-//! This module implements Method of Moments (MoM) and Grimshaw estimators
-//! for Generalized Pareto Distribution parameters.
+//! This module implements Method of Moments (MoM), Grimshaw and
+//! Probability-Weighted Moments (PWM) estimators for Generalized Pareto
+//! Distribution parameters.
 
+use alloc::vec::Vec;
+
+use core::cmp::Ordering;
+
+use crate::float::Float;
 use crate::math::{xlog, xmin};
 
 use crate::peaks::Peaks;
+use crate::ubend::{RingBuffer, Ubend};
 
 /// Default epsilon for Brent's method
 const BRENT_DEFAULT_EPSILON: f64 = 2.0e-8;
@@ -13,54 +20,219 @@ const BRENT_DEFAULT_EPSILON: f64 = 2.0e-8;
 /// Maximum iterations for Brent's method
 const BRENT_ITMAX: usize = 200;
 
+/// Epsilon for Brent's method, no finer than the precision of `F`
+fn brent_epsilon<F: Float>() -> F {
+    F::of(BRENT_DEFAULT_EPSILON).max(F::epsilon())
+}
+
+/// Tuning of the root finding in the Grimshaw estimator ([`GrimshawEstimator`])
+///
+/// The defaults are the constants of the C implementation; changing them makes
+/// results diverge from it.
+///
+/// # Serialization
+///
+/// When the `serde` feature is enabled, this struct can be serialized and deserialized.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GrimshawOptions {
+    /// Convergence tolerance of Brent's method, clamped to the machine epsilon of
+    /// the float type
+    pub epsilon: f64,
+    /// Maximum number of iterations of Brent's method
+    pub max_iter: usize,
+}
+
+impl Default for GrimshawOptions {
+    /// Options matching the C implementation
+    fn default() -> Self {
+        Self {
+            epsilon: BRENT_DEFAULT_EPSILON,
+            max_iter: BRENT_ITMAX,
+        }
+    }
+}
+
+/// Estimators tried by [`Tail::fit`](crate::Tail::fit) (besides PWM, which
+/// is added when enabled)
+///
+/// # Serialization
+///
+/// When the `serde` feature is enabled, this enum can be serialized and deserialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FitStrategy {
+    /// MoM and Grimshaw, keeping the best log-likelihood like the C implementation
+    #[default]
+    Both,
+    /// Method of Moments only
+    MomOnly,
+    /// Grimshaw's estimator only
+    GrimshawOnly,
+    /// Method of Moments below [`AUTO_MOM_MAX_PEAKS`](FitStrategy::AUTO_MOM_MAX_PEAKS)
+    /// excesses, where the likelihood is too flat for Grimshaw to pay off, and
+    /// Grimshaw from there on
+    Auto,
+}
+
+impl FitStrategy {
+    /// Number of excesses from which [`Auto`](FitStrategy::Auto) uses Grimshaw
+    pub const AUTO_MOM_MAX_PEAKS: usize = 20;
+
+    /// Whether `estimator` is tried on a tail of `size` excesses
+    pub(crate) fn tries(self, estimator: EstimatorKind, size: usize) -> bool {
+        match (self, estimator) {
+            (_, EstimatorKind::Pwm) | (FitStrategy::Both, _) => true,
+            (FitStrategy::MomOnly, kind) => kind == EstimatorKind::MomentMatch,
+            (FitStrategy::GrimshawOnly, kind) => kind == EstimatorKind::Grimshaw,
+            (FitStrategy::Auto, kind) => {
+                (kind == EstimatorKind::Grimshaw) == (size >= Self::AUTO_MOM_MAX_PEAKS)
+            }
+        }
+    }
+}
+
+/// GPD parameter estimator that produced a fit, see [`FitReport`](crate::FitReport)
+///
+/// # Serialization
+///
+/// When the `serde` feature is enabled, this enum can be serialized and deserialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EstimatorKind {
+    /// Method of Moments
+    MomentMatch,
+    /// Grimshaw's maximum likelihood estimator
+    Grimshaw,
+    /// Probability-Weighted Moments (pure Rust only)
+    Pwm,
+}
+
+impl EstimatorKind {
+    /// Run the estimator on the peaks, returning `(gamma, sigma, log_likelihood)`
+    pub(crate) fn estimate<F: Float, B: RingBuffer<F>>(self, peaks: &Peaks<F, B>) -> (F, F, F) {
+        match self {
+            EstimatorKind::MomentMatch => mom_estimator(peaks),
+            EstimatorKind::Grimshaw => grimshaw_estimator(peaks),
+            EstimatorKind::Pwm => pwm_estimator(peaks),
+        }
+    }
+}
+
+/// GPD fitting routine usable with [`Tail::fit_with`](crate::Tail::fit_with)
+///
+/// Implement it to plug a custom estimator (e.g. a regularized MLE) next to the
+/// built-in ones. `estimate` returns `(gamma, sigma, log_likelihood)`, with NaN
+/// values when the peaks cannot be fitted; the log-likelihood is what ranks the
+/// candidates, so it should be computed on the same peaks (see [`compute_log_likelihood`]).
+pub trait GpdEstimator<F: Float = f64, B: RingBuffer<F> = Ubend<F>> {
+    /// Estimate the GPD parameters of the peaks, returning `(gamma, sigma, log_likelihood)`
+    fn estimate(&self, peaks: &Peaks<F, B>) -> (F, F, F);
+}
+
+/// Method of Moments estimator
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MomEstimator;
+
+/// Grimshaw's maximum likelihood estimator, as in the C implementation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GrimshawEstimator;
+
+/// Probability-Weighted Moments estimator (Hosking & Wallis, 1987)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PwmEstimator;
+
+impl<F: Float, B: RingBuffer<F>> GpdEstimator<F, B> for MomEstimator {
+    fn estimate(&self, peaks: &Peaks<F, B>) -> (F, F, F) {
+        mom_estimator(peaks)
+    }
+}
+
+impl<F: Float, B: RingBuffer<F>> GpdEstimator<F, B> for GrimshawEstimator {
+    fn estimate(&self, peaks: &Peaks<F, B>) -> (F, F, F) {
+        grimshaw_estimator(peaks)
+    }
+}
+
+impl<F: Float, B: RingBuffer<F>> GpdEstimator<F, B> for PwmEstimator {
+    fn estimate(&self, peaks: &Peaks<F, B>) -> (F, F, F) {
+        pwm_estimator(peaks)
+    }
+}
+
+impl<F: Float, B: RingBuffer<F>> GpdEstimator<F, B> for EstimatorKind {
+    fn estimate(&self, peaks: &Peaks<F, B>) -> (F, F, F) {
+        EstimatorKind::estimate(*self, peaks)
+    }
+}
+
 /// Method of Moments estimator for GPD parameters
-pub fn mom_estimator(peaks: &Peaks) -> (f64, f64, f64) {
+pub fn mom_estimator<F: Float, B: RingBuffer<F>>(peaks: &Peaks<F, B>) -> (F, F, F) {
     let e = peaks.mean();
     let v = peaks.variance();
 
-    if e.is_nan() || v.is_nan() || v <= 0.0 {
-        return (f64::NAN, f64::NAN, f64::NAN);
+    if e.is_nan() || v.is_nan() || v <= F::zero() {
+        return (F::nan(), F::nan(), F::nan());
     }
 
+    let half = F::of(0.5);
     let r = e * e / v;
-    let gamma = 0.5 * (1.0 - r);
-    let sigma = 0.5 * e * (1.0 + r);
+    let gamma = half * (F::one() - r);
+    let sigma = half * e * (F::one() + r);
     let log_likelihood = compute_log_likelihood(peaks, gamma, sigma);
 
     (gamma, sigma, log_likelihood)
 }
 
 /// Grimshaw estimator for GPD parameters
-pub fn grimshaw_estimator(peaks: &Peaks) -> (f64, f64, f64) {
+pub fn grimshaw_estimator<F: Float, B: RingBuffer<F>>(peaks: &Peaks<F, B>) -> (F, F, F) {
+    grimshaw_estimator_with(peaks, GrimshawOptions::default())
+}
+
+/// Grimshaw estimator for GPD parameters with custom root finding options
+pub(crate) fn grimshaw_estimator_with<F: Float, B: RingBuffer<F>>(
+    peaks: &Peaks<F, B>,
+    options: GrimshawOptions,
+) -> (F, F, F) {
     let mini = peaks.min();
     let maxi = peaks.max();
     let mean = peaks.mean();
 
     if mini.is_nan() || maxi.is_nan() || mean.is_nan() {
-        return (f64::NAN, f64::NAN, f64::NAN);
+        return (F::nan(), F::nan(), F::nan());
     }
 
-    let epsilon = xmin(BRENT_DEFAULT_EPSILON, 0.5 / maxi);
+    // The brackets keep the default epsilon, the options only tune the convergence
+    let epsilon = xmin(brent_epsilon::<F>(), F::of(0.5) / maxi);
+    let tol = F::of(options.epsilon).max(F::epsilon());
+    let solve = |a, b| brent_with(a, b, |x| grimshaw_w(x, peaks), tol, tol, options.max_iter);
 
     let mut found = [true, false, false]; // true, false, false
-    let mut roots = [0.0, 0.0, 0.0]; // 0., ?, ?
+    let mut roots = [F::zero(); 3]; // 0., ?, ?
 
     // Left root
-    let a = -1.0 / maxi + epsilon;
+    let a = -F::one() / maxi + epsilon;
     let b = -epsilon;
-    if let Some(root) = brent(a, b, |x| grimshaw_w(x, peaks), BRENT_DEFAULT_EPSILON) {
+    if let Some(root) = solve(a, b) {
         roots[1] = root;
         found[1] = true;
     }
 
     // Right root
     let a = epsilon;
-    let b = 2.0 * (mean - mini) / (mini * mini);
-    if let Some(root) = brent(a, b, |x| grimshaw_w(x, peaks), BRENT_DEFAULT_EPSILON) {
+    let b = F::of(2.0) * (mean - mini) / (mini * mini);
+    if let Some(root) = solve(a, b) {
         roots[2] = root;
         found[2] = true;
     }
 
+    #[cfg(feature = "log")]
+    log::trace!(
+        "Grimshaw roots: left = {:?}, right = {:?}",
+        found[1].then_some(roots[1]),
+        found[2].then_some(roots[2])
+    );
+
     // Compare all roots (exact C implementation logic)
     let (mut best_gamma, mut best_sigma, mut max_llhood) =
         grimshaw_simplified_log_likelihood(roots[0], peaks);
@@ -77,31 +249,80 @@ pub fn grimshaw_estimator(peaks: &Peaks) -> (f64, f64, f64) {
             }
         }
     }
+
+    #[cfg(feature = "log")]
+    log::debug!(
+        "Grimshaw fit: gamma = {best_gamma}, sigma = {best_sigma}, log-likelihood = {max_llhood}"
+    );
     (best_gamma, best_sigma, max_llhood)
 }
 
+/// Probability-Weighted Moments estimator for GPD parameters
+///
+/// Uses the unbiased moments `b0 = mean` and `b1 = 1/n sum((i - 1) / (n - 1) * x_(i))`
+/// over the order statistics `x_(1) <= ... <= x_(n)` (Hosking & Wallis, 1987):
+/// `gamma = 2 - b0 / (2 b1 - b0)` and `sigma = 2 b0 (b0 - b1) / (2 b1 - b0)`.
+/// Unlike MoM it only requires gamma < 1 and stays well behaved on small samples.
+pub fn pwm_estimator<F: Float, B: RingBuffer<F>>(peaks: &Peaks<F, B>) -> (F, F, F) {
+    let size = peaks.size();
+    if size < 2 {
+        return (F::nan(), F::nan(), F::nan());
+    }
+
+    let mut sorted: Vec<F> = peaks.iter().collect();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    let two = F::of(2.0);
+    let n = F::from_usize(size);
+    let b0 = sorted.iter().sum::<F>() / n;
+    let b1 = sorted
+        .iter()
+        .enumerate()
+        .map(|(i, &x)| F::from_usize(i) / (n - F::one()) * x)
+        .sum::<F>()
+        / n;
+
+    let d = two * b1 - b0;
+    if b0.is_nan() || b1.is_nan() || d <= F::zero() {
+        return (F::nan(), F::nan(), F::nan());
+    }
+
+    let gamma = two - b0 / d;
+    let sigma = two * b0 * (b0 - b1) / d;
+    if sigma <= F::zero() {
+        return (F::nan(), F::nan(), F::nan());
+    }
+
+    let log_likelihood = compute_log_likelihood(peaks, gamma, sigma);
+    (gamma, sigma, log_likelihood)
+}
+
 /// Compute log-likelihood for GPD with given parameters
-pub fn compute_log_likelihood(peaks: &Peaks, gamma: f64, sigma: f64) -> f64 {
+pub fn compute_log_likelihood<F: Float, B: RingBuffer<F>>(
+    peaks: &Peaks<F, B>,
+    gamma: F,
+    sigma: F,
+) -> F {
     let nt_local = peaks.size();
-    let nt = nt_local as f64;
+    let nt = F::from_usize(nt_local);
 
-    if nt == 0.0 || sigma <= 0.0 {
-        return f64::NEG_INFINITY;
+    if nt == F::zero() || sigma <= F::zero() {
+        return F::neg_infinity();
     }
 
-    if gamma == 0.0 {
+    if gamma == F::zero() {
         return -nt * xlog(sigma) - peaks.sum() / sigma;
     }
 
     let mut r = -nt * xlog(sigma);
-    let c = 1.0 + 1.0 / gamma;
+    let c = F::one() + F::one() / gamma;
     let x = gamma / sigma;
 
     // Iterate through raw container data order (matches C implementation)
     for &value in peaks.container().raw_data().iter().take(nt_local) {
-        let term = 1.0 + x * value;
-        if term <= 0.0 {
-            return f64::NEG_INFINITY; // Invalid parameters
+        let term = F::one() + x * value;
+        if term <= F::zero() {
+            return F::neg_infinity(); // Invalid parameters
         }
         r += -c * xlog(term);
     }
@@ -110,48 +331,51 @@ pub fn compute_log_likelihood(peaks: &Peaks, gamma: f64, sigma: f64) -> f64 {
 }
 
 /// Grimshaw w function for root finding
-fn grimshaw_w(x: f64, peaks: &Peaks) -> f64 {
+fn grimshaw_w<F: Float, B: RingBuffer<F>>(x: F, peaks: &Peaks<F, B>) -> F {
     let nt_local = peaks.size();
-    let mut u: f64 = 0.0;
-    let mut v: f64 = 0.0;
+    let mut u = F::zero();
+    let mut v = F::zero();
 
     for &data_i in peaks.container().raw_data().iter().take(nt_local) {
-        let s: f64 = 1.0 + x * data_i;
-        if s <= 0.0 {
-            return f64::NAN; // Invalid
+        let s = F::one() + x * data_i;
+        if s <= F::zero() {
+            return F::nan(); // Invalid
         }
-        u += 1.0 / s;
+        u += F::one() / s;
         v += xlog(s);
     }
 
     if nt_local == 0 {
-        return f64::NAN;
+        return F::nan();
     }
 
-    let nt: f64 = nt_local as f64;
-    (u / nt) * (1.0 + v / nt) - 1.0
+    let nt = F::from_usize(nt_local);
+    (u / nt) * (F::one() + v / nt) - F::one()
 }
 
 /// Grimshaw v function
-fn grimshaw_v(x: f64, peaks: &Peaks) -> f64 {
-    let mut v = 0.0;
+fn grimshaw_v<F: Float, B: RingBuffer<F>>(x: F, peaks: &Peaks<F, B>) -> F {
+    let mut v = F::zero();
     let nt_local = peaks.size();
 
     for &data_i in peaks.container().raw_data().iter().take(nt_local) {
-        v += xlog(1.0 + x * data_i);
+        v += xlog(F::one() + x * data_i);
     }
 
-    let nt = nt_local as f64;
-    1.0 + v / nt
+    let nt = F::from_usize(nt_local);
+    F::one() + v / nt
 }
 
 /// Compute simplified log likelihood for Grimshaw method
-fn grimshaw_simplified_log_likelihood(x_star: f64, peaks: &Peaks) -> (f64, f64, f64) {
+fn grimshaw_simplified_log_likelihood<F: Float, B: RingBuffer<F>>(
+    x_star: F,
+    peaks: &Peaks<F, B>,
+) -> (F, F, F) {
     // Match C implementation exactly: use exact equality check only
-    let (gamma, sigma) = if x_star == 0.0 {
-        (0.0, peaks.mean())
+    let (gamma, sigma) = if x_star == F::zero() {
+        (F::zero(), peaks.mean())
     } else {
-        let gamma = grimshaw_v(x_star, peaks) - 1.0;
+        let gamma = grimshaw_v(x_star, peaks) - F::one();
         let sigma = gamma / x_star;
         (gamma, sigma)
     };
@@ -160,18 +384,165 @@ fn grimshaw_simplified_log_likelihood(x_star: f64, peaks: &Peaks) -> (f64, f64,
     (gamma, sigma, log_likelihood)
 }
 
+/// Weighted Method of Moments estimator for GPD parameters
+///
+/// Same as [`mom_estimator`] with the mean and (population) variance of
+/// `values` weighted by the matching entries of `weights`.
+pub(crate) fn weighted_mom_estimator<F: Float>(values: &[F], weights: &[F]) -> (F, F, F) {
+    let total = weights.iter().copied().sum::<F>();
+    if values.is_empty() || total <= F::zero() {
+        return (F::nan(), F::nan(), F::nan());
+    }
+
+    let e = weighted_mean(values, weights, total);
+    let v = values
+        .iter()
+        .zip(weights)
+        .map(|(&x, &w)| w * (x - e) * (x - e))
+        .sum::<F>()
+        / total;
+
+    if e.is_nan() || v.is_nan() || v <= F::zero() {
+        return (F::nan(), F::nan(), F::nan());
+    }
+
+    let half = F::of(0.5);
+    let r = e * e / v;
+    let gamma = half * (F::one() - r);
+    let sigma = half * e * (F::one() + r);
+    let log_likelihood = weighted_log_likelihood(values, weights, gamma, sigma);
+
+    (gamma, sigma, log_likelihood)
+}
+
+/// Weighted Grimshaw estimator for GPD parameters
+///
+/// Same as [`grimshaw_estimator_with`] where each excess contributes to the
+/// likelihood with the matching entry of `weights`. The root brackets use the
+/// weighted mean.
+pub(crate) fn weighted_grimshaw_estimator<F: Float>(
+    values: &[F],
+    weights: &[F],
+    options: GrimshawOptions,
+) -> (F, F, F) {
+    let total = weights.iter().copied().sum::<F>();
+    if values.is_empty() || total <= F::zero() {
+        return (F::nan(), F::nan(), F::nan());
+    }
+
+    let mini = values.iter().copied().fold(F::infinity(), F::min);
+    let maxi = values.iter().copied().fold(F::neg_infinity(), F::max);
+    let mean = weighted_mean(values, weights, total);
+
+    if mini.is_nan() || maxi.is_nan() || mean.is_nan() {
+        return (F::nan(), F::nan(), F::nan());
+    }
+
+    let epsilon = xmin(brent_epsilon::<F>(), F::of(0.5) / maxi);
+    let tol = F::of(options.epsilon).max(F::epsilon());
+    let w = |x: F| {
+        let (mut u, mut v) = (F::zero(), F::zero());
+        for (&data_i, &weight) in values.iter().zip(weights) {
+            let s = F::one() + x * data_i;
+            if s <= F::zero() {
+                return F::nan();
+            }
+            u += weight / s;
+            v += weight * xlog(s);
+        }
+        (u / total) * (F::one() + v / total) - F::one()
+    };
+    let solve = |a, b| brent_with(a, b, w, tol, tol, options.max_iter);
+
+    let mut candidates = [Some(F::zero()), None, None];
+    candidates[1] = solve(-F::one() / maxi + epsilon, -epsilon);
+    candidates[2] = solve(epsilon, F::of(2.0) * (mean - mini) / (mini * mini));
+
+    let mut best = (F::nan(), F::nan(), F::neg_infinity());
+    for x_star in candidates.into_iter().flatten() {
+        let (gamma, sigma) = if x_star == F::zero() {
+            (F::zero(), mean)
+        } else {
+            let v = values
+                .iter()
+                .zip(weights)
+                .map(|(&x, &weight)| weight * xlog(F::one() + x_star * x))
+                .sum::<F>();
+            let gamma = v / total;
+            (gamma, gamma / x_star)
+        };
+        let log_likelihood = weighted_log_likelihood(values, weights, gamma, sigma);
+        if best.0.is_nan() || log_likelihood > best.2 {
+            best = (gamma, sigma, log_likelihood);
+        }
+    }
+    best
+}
+
+/// Weighted log-likelihood of the GPD, each excess counting with its weight
+pub(crate) fn weighted_log_likelihood<F: Float>(
+    values: &[F],
+    weights: &[F],
+    gamma: F,
+    sigma: F,
+) -> F {
+    let total = weights.iter().copied().sum::<F>();
+    if values.is_empty() || total <= F::zero() || sigma <= F::zero() {
+        return F::neg_infinity();
+    }
+
+    let mut r = -total * xlog(sigma);
+    if gamma == F::zero() {
+        let sum = values.iter().zip(weights).map(|(&x, &w)| w * x).sum::<F>();
+        return r - sum / sigma;
+    }
+
+    let c = F::one() + F::one() / gamma;
+    let x = gamma / sigma;
+    for (&value, &weight) in values.iter().zip(weights) {
+        let term = F::one() + x * value;
+        if term <= F::zero() {
+            return F::neg_infinity();
+        }
+        r += -c * weight * xlog(term);
+    }
+
+    r
+}
+
+/// Weighted mean of `values` given the sum of the weights
+fn weighted_mean<F: Float>(values: &[F], weights: &[F], total: F) -> F {
+    values.iter().zip(weights).map(|(&x, &w)| w * x).sum::<F>() / total
+}
+
 /// Brent's method for root finding
 /// Returns Some(root) if found, None otherwise
 /// This implementation matches the C libspot brent.c exactly
-fn brent<F>(x1: f64, x2: f64, func: F, tol: f64) -> Option<f64>
+#[cfg(test)]
+fn brent<F, W>(x1: F, x2: F, func: W, tol: F) -> Option<F>
+where
+    F: Float,
+    W: Fn(F) -> F,
+{
+    brent_with(x1, x2, func, tol, brent_epsilon(), BRENT_ITMAX)
+}
+
+/// Brent's method with an explicit machine epsilon and iteration cap
+fn brent_with<F, W>(x1: F, x2: F, func: W, tol: F, eps: F, max_iter: usize) -> Option<F>
 where
-    F: Fn(f64) -> f64,
+    F: Float,
+    W: Fn(F) -> F,
 {
+    let zero = F::zero();
+    let one = F::one();
+    let two = F::of(2.0);
+    let half = F::of(0.5);
+
     let mut a = x1;
     let mut b = x2;
     let mut c = x2;
-    let mut d = 0.0;
-    let mut e = 0.0;
+    let mut d = zero;
+    let mut e = zero;
 
     let mut fa = func(a);
     let mut fb = func(b);
@@ -181,14 +552,14 @@ where
     }
 
     // Check that root is bracketed
-    if (fa > 0.0 && fb > 0.0) || (fa < 0.0 && fb < 0.0) {
+    if (fa > zero && fb > zero) || (fa < zero && fb < zero) {
         return None;
     }
 
     let mut fc = fb;
 
-    for _iter in 0..BRENT_ITMAX {
-        if (fb > 0.0 && fc > 0.0) || (fb < 0.0 && fc < 0.0) {
+    for _iter in 0..max_iter {
+        if (fb > zero && fc > zero) || (fb < zero && fc < zero) {
             c = a; // Rename a, b, c and adjust bounding interval
             fc = fa;
             d = b - a; // Match C exactly: e = d = b - a
@@ -202,35 +573,35 @@ where
             fb = fc;
             fc = fa;
         }
-        let tol1 = 2.0 * BRENT_DEFAULT_EPSILON * b.abs() + 0.5 * tol; // Convergence check.
-        let xm = 0.5 * (c - b);
-        if xm.abs() <= tol1 || fb == 0.0 {
+        let tol1 = two * eps * b.abs() + half * tol; // Convergence check.
+        let xm = half * (c - b);
+        if xm.abs() <= tol1 || fb == zero {
             return Some(b);
         }
         if e.abs() >= tol1 && fa.abs() > fb.abs() {
             let s = fb / fa; // Attempt inverse quadratic interpolation.
             let (p, q) = if a == c {
-                let p = 2.0 * xm * s;
-                let q = 1.0 - s;
+                let p = two * xm * s;
+                let q = one - s;
                 (p, q)
             } else {
                 let q = fa / fc;
                 let r = fb / fc;
-                let p = s * (2.0 * xm * q * (q - r) - (b - a) * (r - 1.0));
-                let q = (q - 1.0) * (r - 1.0) * (s - 1.0);
+                let p = s * (two * xm * q * (q - r) - (b - a) * (r - one));
+                let q = (q - one) * (r - one) * (s - one);
                 (p, q)
             };
 
-            let q = if p > 0.0 {
+            let q = if p > zero {
                 -q // Check whether in bounds.
             } else {
                 q
             };
             let p = p.abs();
 
-            let min1 = 3.0 * xm * q - (tol1 * q).abs();
+            let min1 = F::of(3.0) * xm * q - (tol1 * q).abs();
             let min2 = (e * q).abs();
-            if 2.0 * p < if min1 < min2 { min1 } else { min2 } {
+            if two * p < if min1 < min2 { min1 } else { min2 } {
                 e = d; // Accept interpolation.
                 d = p / q;
             } else {
@@ -248,7 +619,7 @@ where
             // Evaluate new trial root.
             b += d;
         } else {
-            b += if xm >= 0.0 { tol1.abs() } else { -tol1.abs() };
+            b += if xm >= zero { tol1.abs() } else { -tol1.abs() };
         }
         fb = func(b);
         if fb.is_nan() {
@@ -299,6 +670,78 @@ mod tests {
         assert!(sigma > 0.0); // Sigma should be positive
     }
 
+    /// Exact GPD quantiles at the midpoints of `n` equal probability bins
+    fn gpd_sample(n: usize, gamma: f64, sigma: f64) -> Peaks {
+        let mut peaks = Peaks::new(n).unwrap();
+        for i in 0..n {
+            let u = (i as f64 + 0.5) / n as f64;
+            let x = if gamma == 0.0 {
+                -sigma * (1.0 - u).ln()
+            } else {
+                sigma / gamma * ((1.0 - u).powf(-gamma) - 1.0)
+            };
+            peaks.push(x);
+        }
+        peaks
+    }
+
+    #[test]
+    fn test_pwm_estimator_degenerate() {
+        let peaks = Peaks::new(5).unwrap();
+        assert!(pwm_estimator(&peaks).0.is_nan());
+
+        let mut peaks = Peaks::new(5).unwrap();
+        peaks.push(1.0);
+        assert!(pwm_estimator(&peaks).0.is_nan());
+
+        // Constant data: 2 b1 - b0 = 0
+        peaks.push(1.0);
+        peaks.push(1.0);
+        let (gamma, sigma, llhood) = pwm_estimator(&peaks);
+        assert!(gamma.is_nan() && sigma.is_nan() && llhood.is_nan());
+    }
+
+    #[test]
+    fn test_pwm_estimator_recovers_known_gpd() {
+        for (gamma, sigma) in [(0.0, 1.0), (0.2, 2.0), (-0.3, 1.5)] {
+            let peaks = gpd_sample(1000, gamma, sigma);
+            let (g, s, llhood) = pwm_estimator(&peaks);
+            assert_relative_eq!(g, gamma, epsilon = 0.05);
+            assert_relative_eq!(s, sigma, epsilon = 0.05 * sigma);
+            assert!(llhood.is_finite());
+        }
+    }
+
+    #[test]
+    fn test_pwm_estimator_heavy_tail_beyond_mom() {
+        // MoM can never return gamma >= 0.5 (its variance is infinite there),
+        // while PWM stays valid for gamma < 1.
+        let peaks = gpd_sample(2000, 0.7, 1.0);
+        let (mom_gamma, mom_sigma, _) = mom_estimator(&peaks);
+        let (pwm_gamma, pwm_sigma, pwm_llhood) = pwm_estimator(&peaks);
+
+        assert!(mom_gamma < 0.5);
+        assert!(pwm_gamma > 0.6 && pwm_gamma < 0.8, "gamma={pwm_gamma}");
+        assert!((pwm_sigma - 1.0).abs() < (mom_sigma - 1.0).abs());
+        assert!(pwm_llhood > compute_log_likelihood(&peaks, mom_gamma, mom_sigma));
+    }
+
+    #[test]
+    fn test_pwm_estimator_negative_excesses() {
+        // MoM returns a negative sigma when the mean is negative, while PWM
+        // still yields finite parameters with a positive scale.
+        let mut peaks = Peaks::new(5).unwrap();
+        for value in [-3.0, -2.0, -1.5, -1.0] {
+            peaks.push(value);
+        }
+        let (_, mom_sigma, _) = mom_estimator(&peaks);
+        assert!(mom_sigma < 0.0);
+
+        let (gamma, sigma, _) = pwm_estimator(&peaks);
+        assert!(gamma.is_finite());
+        assert!(sigma.is_finite() && sigma > 0.0);
+    }
+
     #[test]
     fn test_log_likelihood_gamma_zero() {
         let mut peaks = Peaks::new(10).unwrap();
@@ -338,4 +781,106 @@ mod tests {
         let result = brent(-1.0, 1.0, |x| x * x + 1.0, 1e-10);
         assert!(result.is_none());
     }
+
+    #[test]
+    fn test_grimshaw_options_tighter_epsilon() {
+        // Quantiles of a GPD with gamma = 0.6 and sigma = 1
+        let mut peaks = Peaks::new(100).unwrap();
+        for i in 0..100 {
+            let u = (i as f64 + 0.5) / 100.0;
+            peaks.push(((1.0 - u).powf(-0.6) - 1.0) / 0.6);
+        }
+
+        let default = grimshaw_estimator(&peaks);
+        assert_eq!(
+            grimshaw_estimator_with(&peaks, GrimshawOptions::default()),
+            default
+        );
+
+        let tight = grimshaw_estimator_with(
+            &peaks,
+            GrimshawOptions {
+                epsilon: 1e-14,
+                max_iter: 1000,
+            },
+        );
+        assert!(tight.0.is_finite() && tight.1.is_finite() && tight.2.is_finite());
+        assert_ne!(tight.0, default.0);
+        assert!((tight.0 - default.0).abs() < BRENT_DEFAULT_EPSILON);
+        assert!((tight.1 - default.1).abs() < BRENT_DEFAULT_EPSILON);
+
+        // Without iterations no root is found and only the exponential candidate remains
+        let (gamma, _, _) = grimshaw_estimator_with(
+            &peaks,
+            GrimshawOptions {
+                max_iter: 0,
+                ..GrimshawOptions::default()
+            },
+        );
+        assert_eq!(gamma, 0.0);
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn test_grimshaw_fit_emits_debug_record() {
+        use alloc::string::String;
+        use core::cell::RefCell;
+
+        std::thread_local! {
+            static RECORDS: RefCell<Vec<(log::Level, String, String)>> =
+                const { RefCell::new(Vec::new()) };
+        }
+
+        /// Logger keeping the records of the current thread
+        struct CaptureLogger;
+
+        impl log::Log for CaptureLogger {
+            fn enabled(&self, _: &log::Metadata) -> bool {
+                true
+            }
+
+            fn log(&self, record: &log::Record) {
+                let entry = (
+                    record.level(),
+                    String::from(record.target()),
+                    std::format!("{}", record.args()),
+                );
+                RECORDS.with(|records| records.borrow_mut().push(entry));
+            }
+
+            fn flush(&self) {}
+        }
+
+        static LOGGER: CaptureLogger = CaptureLogger;
+        let _ = log::set_logger(&LOGGER);
+        log::set_max_level(log::LevelFilter::Trace);
+
+        let mut peaks = Peaks::new(100).unwrap();
+        for i in 1..=100 {
+            peaks.push(-(1.0 - i as f64 / 101.0).ln());
+        }
+        let (gamma, sigma, _) = grimshaw_estimator(&peaks);
+
+        let records = RECORDS.with(|records| records.take());
+        let debug: Vec<_> = records
+            .iter()
+            .filter(|(level, target, _)| {
+                *level == log::Level::Debug && target == "libspot_rs::estimator"
+            })
+            .collect();
+        assert_eq!(debug.len(), 1);
+        let message = &debug[0].2;
+        assert!(
+            message.contains(&std::format!("gamma = {gamma}")),
+            "{message}"
+        );
+        assert!(
+            message.contains(&std::format!("sigma = {sigma}")),
+            "{message}"
+        );
+        assert!(records
+            .iter()
+            .any(|(level, _, message)| *level == log::Level::Trace
+                && message.starts_with("Grimshaw roots")));
+    }
 }
@@ -0,0 +1,189 @@
+//! Floating-point abstraction for the detector precision
+//!
+//! Every model type is generic over a [`Float`], implemented for `f64` (the
+//! default, bit-for-bit compatible with the C implementation) and `f32` (half the
+//! memory for the excess buffers).
+
+use core::fmt;
+use core::iter::Sum;
+
+/// Floating-point type the SPOT model can be computed with
+///
+/// This trait is sealed: it is implemented for `f32` and `f64` only, since the
+/// continued-fraction math relies on the exact binary layout of the type.
+pub trait Float:
+    num_traits::Float
+    + num_traits::NumAssignOps
+    + Sum
+    + for<'a> Sum<&'a Self>
+    + fmt::Debug
+    + fmt::Display
+    + Default
+    + Send
+    + Sync
+    + SerdeFloat
+    + sealed::Sealed
+    + 'static
+{
+    /// Natural logarithm of 2
+    const LOG2: Self;
+
+    /// Largest argument for which `exp` is finite: ln(MAX)
+    const EXP_OVERFLOW: Self;
+
+    /// Convert an `f64` constant, rounding to the nearest value for `f32`
+    fn of(x: f64) -> Self;
+
+    /// Convert a count
+    fn from_usize(n: usize) -> Self;
+
+    /// Widen to `f64`
+    fn as_f64(self) -> f64;
+
+    /// Split into a mantissa in [0.5, 1) and a power of two, like C `frexp`
+    fn frexp(self) -> (Self, i32);
+}
+
+impl Float for f64 {
+    const LOG2: Self = f64::from_bits(0x3FE62E42FEFA39EF);
+    const EXP_OVERFLOW: Self = 709.782712893384;
+
+    #[inline]
+    fn of(x: f64) -> Self {
+        x
+    }
+
+    #[inline]
+    fn from_usize(n: usize) -> Self {
+        n as f64
+    }
+
+    #[inline]
+    fn as_f64(self) -> f64 {
+        self
+    }
+
+    fn frexp(self) -> (Self, i32) {
+        let x = self;
+        if x == 0.0 {
+            return (x, 0);
+        }
+
+        let bits = x.to_bits();
+        let sign = if bits & (1u64 << 63) != 0 { -1.0 } else { 1.0 };
+        let exp_bits = (bits >> 52) & 0x7ff;
+        let mantissa_bits = bits & 0xfffffffffffff;
+
+        if exp_bits == 0 {
+            // Subnormal number
+            let (norm_mantissa, norm_exp) = (x * (1u64 << 52) as f64).frexp();
+            return (norm_mantissa, norm_exp - 52);
+        } else if exp_bits == 0x7ff {
+            // Infinity or NaN
+            return (x, 0);
+        }
+
+        let exponent = exp_bits as i32 - 0x3fe;
+        let mantissa = sign * f64::from_bits(mantissa_bits | 0x3fe0000000000000);
+
+        (mantissa, exponent)
+    }
+}
+
+impl Float for f32 {
+    const LOG2: Self = f32::from_bits(0x3F317218);
+    const EXP_OVERFLOW: Self = 88.72283;
+
+    #[inline]
+    fn of(x: f64) -> Self {
+        x as f32
+    }
+
+    #[inline]
+    fn from_usize(n: usize) -> Self {
+        n as f32
+    }
+
+    #[inline]
+    fn as_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn frexp(self) -> (Self, i32) {
+        let x = self;
+        if x == 0.0 {
+            return (x, 0);
+        }
+
+        let bits = x.to_bits();
+        let sign = if bits & (1u32 << 31) != 0 { -1.0 } else { 1.0 };
+        let exp_bits = (bits >> 23) & 0xff;
+        let mantissa_bits = bits & 0x7fffff;
+
+        if exp_bits == 0 {
+            // Subnormal number
+            let (norm_mantissa, norm_exp) = (x * (1u32 << 23) as f32).frexp();
+            return (norm_mantissa, norm_exp - 23);
+        } else if exp_bits == 0xff {
+            // Infinity or NaN
+            return (x, 0);
+        }
+
+        let exponent = exp_bits as i32 - 0x7e;
+        let mantissa = sign * f32::from_bits(mantissa_bits | 0x3f000000);
+
+        (mantissa, exponent)
+    }
+}
+
+/// Serde bounds required from a [`Float`] when the `serde` feature is enabled
+#[cfg(feature = "serde")]
+pub trait SerdeFloat: serde::Serialize + serde::de::DeserializeOwned {}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize + serde::de::DeserializeOwned> SerdeFloat for T {}
+
+/// Serde bounds required from a [`Float`] when the `serde` feature is enabled
+#[cfg(not(feature = "serde"))]
+pub trait SerdeFloat {}
+
+#[cfg(not(feature = "serde"))]
+impl<T> SerdeFloat for T {}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for f32 {}
+    impl Sealed for f64 {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frexp_matches_definition() {
+        for x in [1.0f64, 0.5, 3.0, 1e-3, 123456.789, -7.25, 1e300, 5e-324] {
+            let (m, e) = Float::frexp(x);
+            assert!((0.5..1.0).contains(&m.abs()), "{x}: {m}");
+            // Split the power so that 2^e does not overflow for subnormal x
+            assert_eq!(m * 2f64.powi(e / 2) * 2f64.powi(e - e / 2), x);
+        }
+        for x in [1.0f32, 0.5, 3.0, 1e-3, 12345.678, -7.25, 1e30, 1e-45] {
+            let (m, e) = Float::frexp(x);
+            assert!((0.5..1.0).contains(&m.abs()), "{x}: {m}");
+            assert_eq!(m as f64 * 2f64.powi(e), x as f64);
+        }
+        assert_eq!(Float::frexp(0.0f32), (0.0, 0));
+        assert!(Float::frexp(f32::INFINITY).0.is_infinite());
+    }
+
+    #[test]
+    fn test_constants() {
+        assert_eq!(f32::LOG2, std::f32::consts::LN_2);
+        assert_eq!(f64::LOG2, std::f64::consts::LN_2);
+        assert!(f32::EXP_OVERFLOW.exp().is_finite());
+        assert!((f32::EXP_OVERFLOW * 1.0001).exp().is_infinite());
+        assert!(f64::EXP_OVERFLOW.exp().is_finite());
+    }
+}
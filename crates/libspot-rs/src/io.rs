@@ -0,0 +1,119 @@
+//! Reading and writing data files
+//!
+//! Enabled with the `io` feature. The data files of the experiments hold one
+//! observation per line, possibly as one column of a comma-separated record,
+//! with `#` comment lines and an optional header.
+
+use std::fs;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Read the values of the given column (0-based) of a comma-separated file
+///
+/// Blank lines and lines starting with `#` are skipped, as is a header: a
+/// first record without a number in any field. Fields are trimmed, so
+/// `1.5, 2.5` reads like `1.5,2.5`. Fails with [`io::ErrorKind::InvalidData`]
+/// on any other record that is too short or whose field is not a number.
+///
+/// ```no_run
+/// use libspot_rs::io::read_values_csv;
+///
+/// // timestamp,value
+/// let values = read_values_csv("data.csv", 1).unwrap();
+/// ```
+pub fn read_values_csv(path: impl AsRef<Path>, column: usize) -> io::Result<Vec<f64>> {
+    let reader = BufReader::new(fs::File::open(path)?);
+    let mut values = Vec::new();
+    let mut first_record = true;
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let record = line.trim();
+        if record.is_empty() || record.starts_with('#') {
+            continue;
+        }
+
+        let field = record.split(',').nth(column).map(str::trim);
+        match field.map(str::parse::<f64>) {
+            Some(Ok(value)) => values.push(value),
+            Some(Err(_)) | None if first_record && is_header(record) => {}
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "line {}: no number in column {column}: {record:?}",
+                        index + 1
+                    ),
+                ))
+            }
+        }
+        first_record = false;
+    }
+    Ok(values)
+}
+
+/// Whether `record` is a header, i.e. none of its fields is a number
+fn is_header(record: &str) -> bool {
+    record
+        .split(',')
+        .all(|field| field.trim().parse::<f64>().is_err())
+}
+
+/// Write `values` to a file, one per line
+///
+/// The values are written with the shortest representation that reads back
+/// exactly, so [`read_values_csv`] with column 0 returns them unchanged.
+pub fn write_values(path: impl AsRef<Path>, values: &[f64]) -> io::Result<()> {
+    let mut writer = BufWriter::new(fs::File::create(path)?);
+    for value in values {
+        writeln!(writer, "{value}")?;
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Path of a scratch file unique to the test
+    fn scratch_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("libspot-rs-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn test_read_values_csv_skips_comments_and_header() {
+        let path = scratch_path("columns.csv");
+        fs::write(
+            &path,
+            "# exported samples\ntimestamp,value\n1,0.5\n\n2, 1.25\n# gap\n3,-3e-2,extra\n",
+        )
+        .unwrap();
+
+        assert_eq!(read_values_csv(&path, 1).unwrap(), vec![0.5, 1.25, -0.03]);
+        assert_eq!(read_values_csv(&path, 0).unwrap(), vec![1.0, 2.0, 3.0]);
+        let error = read_values_csv(&path, 2).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().starts_with("line 3:"));
+
+        fs::write(&path, "1,0.5\n2,oops\n").unwrap();
+        let error = read_values_csv(&path, 1).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        // A malformed or too short first data record is not a header
+        for contents in ["1,oops\n2,0.5\n", "1\n2,0.5\n"] {
+            fs::write(&path, contents).unwrap();
+            let error = read_values_csv(&path, 1).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+            assert!(error.to_string().starts_with("line 1:"));
+        }
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_write_values_roundtrip() {
+        let path = scratch_path("roundtrip.csv");
+        let values = [0.1, -2.5, 1e-300, 123456789.0, f64::MIN_POSITIVE];
+        write_values(&path, &values).unwrap();
+        assert_eq!(read_values_csv(&path, 0).unwrap(), values);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,64 @@
+//! JSON persistence of SPOT models
+//!
+//! Thin wrappers around `serde_json` so that saving and loading a detector
+//! does not require depending on it directly.
+
+use alloc::string::String;
+
+use serde_json::error::Category;
+
+use crate::error::{DeserializationKind, SpotError, SpotResult};
+use crate::float::Float;
+use crate::spot::SpotDetector;
+
+impl SpotDetector {
+    /// Deserialize an `f64` detector from a JSON string
+    ///
+    /// Fails with [`SpotError::Deserialization`], carrying the kind of JSON
+    /// error, if `s` is not a valid serialized detector (including one written
+    /// with a newer [model schema version](crate::model_schema_version)).
+    pub fn from_json(s: &str) -> SpotResult<Self> {
+        Self::from_json_generic(s)
+    }
+}
+
+impl<F: Float> SpotDetector<F> {
+    /// Serialize the detector to a JSON string
+    ///
+    /// NaN and infinite values (e.g. the thresholds before `fit`) are written as
+    /// strings, so any detector can be serialized.
+    ///
+    /// ```
+    /// use libspot_rs::{SpotConfig, SpotDetector};
+    ///
+    /// let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+    /// let data: Vec<f64> = (0..1000).map(|i| (i as f64) / 100.0).collect();
+    /// spot.fit(&data).unwrap();
+    ///
+    /// let json = spot.to_json().unwrap();
+    /// let loaded = SpotDetector::from_json(&json).unwrap();
+    /// assert_eq!(loaded.anomaly_threshold(), spot.anomaly_threshold());
+    /// ```
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    /// Serialize the detector to an indented JSON string
+    pub fn to_json_pretty(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Deserialize a detector of any [`Float`] type from a JSON string
+    ///
+    /// See [`from_json`](SpotDetector::from_json).
+    pub fn from_json_generic(s: &str) -> SpotResult<Self> {
+        serde_json::from_str(s).map_err(|error| {
+            SpotError::Deserialization(match error.classify() {
+                Category::Syntax => DeserializationKind::Syntax,
+                Category::Data => DeserializationKind::Data,
+                Category::Eof => DeserializationKind::Eof,
+                Category::Io => DeserializationKind::Io,
+            })
+        })
+    }
+}
@@ -1,4 +1,5 @@
 #![doc = include_str!("../README.md")]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 //!
 //! # Feature Flags
 //!
@@ -12,14 +13,49 @@
 //!   To disable serialization support (e.g., for minimal dependencies), use:
 //!   ```toml
 //!   [dependencies]
-//!   libspot-rs = { version = "0.2", default-features = false }
+//!   libspot-rs = { version = "0.4", default-features = false, features = ["std"] }
 //!   ```
 //!
+//! - **`json`**: Adds `SpotDetector::to_json` and `SpotDetector::from_json`,
+//!   which save and load detectors through `serde_json` without depending on
+//!   it directly. Implies `serde`, which alone does not pull in `serde_json`.
+//!
+//! - **`std`** (enabled by default): Links the standard library. Without it the crate
+//!   is `no_std` and only requires `alloc`, and the floating-point functions go
+//!   through `libm`. The SPOT fit and thresholds use the in-crate continued
+//!   fractions for logarithms and exponentials, plus correctly rounded
+//!   operations (`sqrt`, `abs`), so they are the same with and without `std`.
+//!   The other uses of `ln`, `exp`, `powf`, `exp_m1` and `ln_1p` (e.g. the
+//!   decay weights of [`RollingSpot`], the confidence intervals, the tail
+//!   quantiles for gamma near 0 with `stable_small_gamma`) call `libm`, whose
+//!   results may differ from the standard library in the last bits.
+//!   The `arrow` feature, the [`SpotError::Io`] conversions, [`SharedSpot`]
+//!   and `SpotDetector::with_channel` require `std`.
+//!
+//! - **`io`**: Exposes the `io` module, reading a column of a CSV data file
+//!   (skipping `#` comments and a header) and writing values one per line.
+//!   Requires `std`.
+//!
+//! - **`log`**: Emits the GPD fits through the `log` facade: the Grimshaw
+//!   roots at the `trace` level and the selected estimates at the `debug`
+//!   level, under the `libspot_rs::estimator` and `libspot_rs::tail` targets.
+//!
+//! - **`rand`**: Enables `SpotDetector::sample_tail`, drawing synthetic values
+//!   from the fitted tail with any `rand_core` generator.
+//!
+//! - **`std-math`**: Computes logarithms, exponentials and powers with the
+//!   standard functions instead of the continued fractions of the C
+//!   implementation. This is faster, at the cost of results that no longer
+//!   match the C library bit for bit.
+//!
+//! - **`testing`**: Exposes the `testing` module with a seeded, platform
+//!   independent generator of exponential data for reproducible examples.
+//!
 //! ## Example with Serialization
 //!
 //! ```toml
 //! [dependencies]
-//! libspot-rs = { version = "0.2" }  # serde is enabled by default
+//! libspot-rs = { version = "0.4" }  # serde is enabled by default
 //! serde_json = "1.0"
 //! ```
 //!
@@ -45,32 +81,129 @@
 //! let status = loaded.step(50.0);
 //! ```
 
+//! # Numerical Determinism
+//!
+//! The estimators are written to reproduce the C implementation bit for bit, so the
+//! crate relies on strict IEEE 754 semantics: no fused multiply-add and no
+//! reassociation of floating-point expressions. Building with
+//! `-ffast-math`-equivalent flags (e.g. LLVM `-C llvm-args=-enable-unsafe-fp-math`)
+//! is unsupported and will make results diverge from the reference implementation.
+//!
+//! # Precision
+//!
+//! [`SpotDetector`], [`Tail`], [`Peaks`], [`Ubend`] and [`P2Estimator`] are generic
+//! over the [`Float`] trait, implemented for `f64` and `f32`. The type parameter
+//! defaults to [`SpotFloat`] (`f64`), which is the only precision matching the C
+//! implementation; the `new` constructors build `f64` values and `new_generic`
+//! builds any precision:
+//!
+//! ```
+//! use libspot_rs::{SpotConfig, SpotDetector};
+//!
+//! let mut spot = SpotDetector::<f32>::new_generic(SpotConfig::default()).unwrap();
+//! let data: Vec<f32> = (0..1000).map(|i| i as f32 / 100.0).collect();
+//! spot.fit(&data).unwrap();
+//! ```
+
+extern crate alloc;
+
+#[cfg(feature = "arrow")]
+mod arrow;
+#[cfg(feature = "serde")]
+mod compact;
+mod compat;
 mod config;
+mod detector;
+mod dspot;
+#[cfg(feature = "serde")]
+mod envelope;
 mod error;
 mod estimator;
+mod float;
+#[cfg(feature = "io")]
+pub mod io;
+#[cfg(feature = "json")]
+mod json;
 mod math;
+mod online;
 mod p2;
 mod peaks;
+mod report;
+mod rolling;
 #[cfg(feature = "serde")]
 mod ser;
 mod spot;
 mod status;
+mod stream;
+#[cfg(feature = "std")]
+mod sync;
 mod tail;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+mod timed;
+mod two_tailed;
 mod ubend;
+mod util;
 
 // Re-export public types
-pub use config::SpotConfig;
-pub use error::{SpotError, SpotResult};
+#[cfg(feature = "serde")]
+pub use compact::CompactSpot;
+pub use compat::CSpotLayout;
+pub use config::{AdaptiveTail, SpotConfig, SpotConfigBuilder};
+pub use detector::AnomalyDetector;
+pub use dspot::{DSpotConfig, DSpotDetector};
+#[cfg(feature = "serde")]
+pub use envelope::{ModelEnvelope, MODEL_ENVELOPE_VERSION};
+pub use error::{DeserializationKind, SpotError, SpotResult};
+pub use estimator::{
+    compute_log_likelihood, EstimatorKind, FitStrategy, GpdEstimator, GrimshawEstimator,
+    GrimshawOptions, MomEstimator, PwmEstimator,
+};
+pub use float::Float;
+pub use online::{Chain, OnlineDetector, Standardizer};
+pub use p2::{p2_quantile, P2Estimator};
 pub use peaks::Peaks;
-pub use spot::SpotDetector;
+pub use report::{
+    FitFailure, FitReport, QuantileBreakdown, RuntimeStats, SpotHealth, SpotSummary, StepReport,
+    ValidationReport,
+};
+pub use rolling::{RollingSpot, RollingSpotConfig};
+pub use spot::{spot_approx_eq, SpotDetector};
 pub use status::SpotStatus;
+pub use stream::SpotStream;
+#[cfg(feature = "std")]
+pub use sync::SharedSpot;
 pub use tail::Tail;
-pub use ubend::Ubend;
+pub use timed::{TimedSpot, TimedStatus};
+pub use two_tailed::{TwoTailedSpot, TwoTailedStatus};
+pub use ubend::{ArrayUbend, ArrayUbendIterator, RingBuffer, Ubend, UbendIterator};
+pub use util::empirical_quantile;
 
 // Re-export commonly used types to match libspot crate
 pub use f64 as SpotFloat;
 
 /// Get the version of the pure Rust libspot implementation
-pub fn version() -> String {
-    env!("CARGO_PKG_VERSION").to_string()
+pub fn version() -> alloc::string::String {
+    env!("CARGO_PKG_VERSION").into()
+}
+
+/// Version of the serialized model representation
+///
+/// - 1: first versioned layout
+/// - 2: adds the compensated [`Peaks`] statistics, the NaN skip counter, the
+///   window of recent verdicts, the configured capacity of a grown tail and
+///   the `reject_infinite`, `adaptive_tail`, `min_peaks` and
+///   `anomaly_rate_window` options. Version 1 models load
+///   with their defaults, which keep their former behavior.
+pub(crate) const MODEL_SCHEMA_VERSION: u32 = 2;
+
+/// Get the version of the serialized model representation
+///
+/// Bumped whenever the serialized form of [`SpotDetector`] (including its
+/// [`Tail`], [`Peaks`] and [`Ubend`]) changes, including when fields are
+/// added: an older crate would otherwise silently drop them. It is written along with every
+/// serialized detector, and deserializing a detector written with a newer
+/// version fails instead of misreading its fields.
+pub fn model_schema_version() -> u32 {
+    MODEL_SCHEMA_VERSION
 }
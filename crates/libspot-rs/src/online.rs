@@ -0,0 +1,273 @@
+//! Composable online detectors
+//!
+//! This module defines the [`OnlineDetector`] trait, a minimal interface shared by
+//! streaming models that are first fitted on a batch and then updated one value at
+//! a time. Implementors can be piped into each other with [`Chain`], which makes it
+//! possible to put preprocessing stages (e.g. a [`Standardizer`]) in front of the
+//! [`SpotDetector`].
+
+use alloc::vec::Vec;
+
+use crate::error::{SpotError, SpotResult};
+use crate::spot::SpotDetector;
+
+/// A streaming model that is fitted on a batch and then updated value by value
+pub trait OnlineDetector {
+    /// Fit the model using initial training data
+    fn fit(&mut self, data: &[f64]) -> SpotResult<()>;
+
+    /// Process a single value, updating the model, and return its score
+    fn update(&mut self, value: f64) -> SpotResult<f64>;
+
+    /// Score a single value against the current model without updating it
+    fn score(&self, value: f64) -> f64;
+
+    /// Pipe the output of this detector into `next`
+    fn then<B: OnlineDetector>(self, next: B) -> Chain<Self, B>
+    where
+        Self: Sized,
+    {
+        Chain::new(self, next)
+    }
+}
+
+/// Two online detectors composed so that the output of the first feeds the second
+///
+/// During [`fit`](OnlineDetector::fit), the first stage is fitted on the raw data
+/// and the second stage on the first stage's scores of that data.
+#[derive(Debug, Clone)]
+pub struct Chain<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A: OnlineDetector, B: OnlineDetector> Chain<A, B> {
+    /// Compose `first` and `second` into a single detector
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+
+    /// Get the first stage
+    pub fn first(&self) -> &A {
+        &self.first
+    }
+
+    /// Get the second stage
+    pub fn second(&self) -> &B {
+        &self.second
+    }
+
+    /// Split the chain back into its two stages
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<A: OnlineDetector, B: OnlineDetector> OnlineDetector for Chain<A, B> {
+    fn fit(&mut self, data: &[f64]) -> SpotResult<()> {
+        self.first.fit(data)?;
+        let mapped: Vec<f64> = data.iter().map(|&x| self.first.score(x)).collect();
+        self.second.fit(&mapped)
+    }
+
+    fn update(&mut self, value: f64) -> SpotResult<f64> {
+        let mapped = self.first.update(value)?;
+        self.second.update(mapped)
+    }
+
+    fn score(&self, value: f64) -> f64 {
+        self.second.score(self.first.score(value))
+    }
+}
+
+/// Preprocessing stage mapping values to z-scores
+///
+/// The mean and standard deviation are estimated once during
+/// [`fit`](OnlineDetector::fit) and kept frozen afterwards. The fit fails with
+/// [`SpotError::DataIsNaN`] on NaN values and with
+/// [`SpotError::DegenerateData`] on empty or constant data (or an infinite
+/// spread), which no z-score can be computed from, leaving the standardizer
+/// unchanged.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Standardizer {
+    /// Mean of the training data
+    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_float"))]
+    mean: f64,
+    /// Standard deviation of the training data
+    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_float"))]
+    std_dev: f64,
+}
+
+impl Standardizer {
+    /// Create an unfitted standardizer
+    pub fn new() -> Self {
+        Self {
+            mean: f64::NAN,
+            std_dev: f64::NAN,
+        }
+    }
+
+    /// Get the fitted mean
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// Get the fitted standard deviation
+    pub fn std_dev(&self) -> f64 {
+        self.std_dev
+    }
+}
+
+impl Default for Standardizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OnlineDetector for Standardizer {
+    fn fit(&mut self, data: &[f64]) -> SpotResult<()> {
+        if data.iter().any(|x| x.is_nan()) {
+            return Err(SpotError::DataIsNaN);
+        }
+
+        if data.is_empty() {
+            return Err(SpotError::DegenerateData);
+        }
+
+        let size = data.len() as f64;
+        let mean = data.iter().sum::<f64>() / size;
+        let variance = data.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / size;
+        let std_dev = num_traits::Float::sqrt(variance);
+        if !(std_dev > 0.0 && std_dev.is_finite()) {
+            return Err(SpotError::DegenerateData);
+        }
+        self.mean = mean;
+        self.std_dev = std_dev;
+        Ok(())
+    }
+
+    fn update(&mut self, value: f64) -> SpotResult<f64> {
+        if value.is_nan() {
+            return Err(SpotError::DataIsNaN);
+        }
+        Ok(self.score(value))
+    }
+
+    fn score(&self, value: f64) -> f64 {
+        (value - self.mean) / self.std_dev
+    }
+}
+
+impl OnlineDetector for SpotDetector {
+    fn fit(&mut self, data: &[f64]) -> SpotResult<()> {
+        SpotDetector::fit(self, data)
+    }
+
+    /// Step the detector and return the score the value had before the update
+    fn update(&mut self, value: f64) -> SpotResult<f64> {
+        let score = OnlineDetector::score(self, value);
+        self.step(value)?;
+        Ok(score)
+    }
+
+    /// Normalized severity, see [`SpotDetector::anomaly_score`]
+    fn score(&self, value: f64) -> f64 {
+        self.anomaly_score(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SpotConfig;
+    use crate::testing::exponential_data;
+    use approx::assert_relative_eq;
+
+    /// Raw-scale sensor readings: a large offset plus exponential noise
+    fn raw_data(n: usize) -> Vec<f64> {
+        exponential_data(n, 42)
+            .into_iter()
+            .map(|x| 1000.0 + 50.0 * x)
+            .collect()
+    }
+
+    #[test]
+    fn test_standardizer_fit() {
+        let mut standardizer = Standardizer::new();
+        standardizer.fit(&[1.0, 2.0, 3.0, 4.0, 5.0]).unwrap();
+
+        assert_relative_eq!(standardizer.mean(), 3.0);
+        assert_relative_eq!(standardizer.std_dev(), 2.0_f64.sqrt());
+        assert_relative_eq!(standardizer.score(3.0), 0.0);
+        assert_relative_eq!(standardizer.update(5.0).unwrap(), 2.0_f64.sqrt());
+        assert_eq!(standardizer.update(f64::NAN), Err(SpotError::DataIsNaN));
+    }
+
+    #[test]
+    fn test_standardizer_fit_rejects_degenerate_data() {
+        let mut standardizer = Standardizer::new();
+        assert_eq!(standardizer.fit(&[]), Err(SpotError::DegenerateData));
+        assert_eq!(standardizer.fit(&[2.5; 10]), Err(SpotError::DegenerateData));
+        assert_eq!(
+            standardizer.fit(&[1.0, f64::INFINITY]),
+            Err(SpotError::DegenerateData)
+        );
+        assert!(standardizer.mean().is_nan() && standardizer.std_dev().is_nan());
+
+        // A failed refit keeps the previous fit
+        standardizer.fit(&[1.0, 3.0]).unwrap();
+        assert_eq!(standardizer.fit(&[2.5; 10]), Err(SpotError::DegenerateData));
+        assert_eq!((standardizer.mean(), standardizer.std_dev()), (2.0, 1.0));
+
+        // A chain stops at the first stage
+        let mut chain = Standardizer::new().then(SpotDetector::new(SpotConfig::default()).unwrap());
+        assert_eq!(chain.fit(&[7.0; 100]), Err(SpotError::DegenerateData));
+    }
+
+    #[test]
+    fn test_chain_standardizer_spot_detects_raw_spikes() {
+        let config = SpotConfig {
+            level: 0.98,
+            q: 0.001,
+            ..SpotConfig::default()
+        };
+        let mut chain = Standardizer::new().then(SpotDetector::new(config).unwrap());
+
+        let data = raw_data(20_000);
+        chain.fit(&data).unwrap();
+
+        // The second stage works on the standardized scale.
+        let spot = chain.second();
+        assert!(spot.excess_threshold() < 10.0);
+        assert!(spot.anomaly_threshold().is_finite());
+
+        // Typical raw values score low, a raw-scale spike scores close to 1.
+        let normal_score = chain.update(1010.0).unwrap();
+        assert_relative_eq!(normal_score, 0.0);
+
+        let spike_score = chain.update(1000.0 + 50.0 * 40.0).unwrap();
+        assert!(spike_score > 1.0 - 0.001, "spike score {spike_score}");
+
+        let z = chain.first().score(1000.0 + 50.0 * 40.0);
+        assert!(z > chain.second().anomaly_threshold());
+    }
+
+    #[test]
+    fn test_spot_online_update_matches_step() {
+        let data = raw_data(5_000);
+        let mut online = SpotDetector::new(SpotConfig::default()).unwrap();
+        let mut plain = SpotDetector::new(SpotConfig::default()).unwrap();
+        OnlineDetector::fit(&mut online, &data).unwrap();
+        plain.fit(&data).unwrap();
+
+        for &x in &data[..500] {
+            online.update(x).unwrap();
+            plain.step(x).unwrap();
+        }
+
+        assert_eq!(online.n(), plain.n());
+        assert_eq!(online.nt(), plain.nt());
+        assert_relative_eq!(online.anomaly_threshold(), plain.anomaly_threshold());
+    }
+}
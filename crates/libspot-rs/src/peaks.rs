@@ -1,45 +1,181 @@
 //! Peaks structure for computing statistics over peak data
 //!
 //! This module implements the Peaks structure that computes statistics
-//! about peaks data using an underlying Ubend circular buffer.
+//! about peaks data using an underlying circular buffer (a [`Ubend`] by default).
+
+use alloc::{collections::VecDeque, vec, vec::Vec};
+use core::cmp::Ordering;
 
 use crate::error::SpotResult;
+use crate::float::Float;
 
-use crate::ubend::Ubend;
+use crate::ubend::{RingBuffer, Ubend};
+use crate::util::sorted_quantile;
 
 /// Structure that computes stats about the peaks
 ///
 /// # Serialization
 ///
-/// When the `serde` feature is enabled, this struct can be serialized and deserialized.
-/// This allows saving and restoring the peak statistics state.
+/// When the `serde` feature is enabled, this struct can be serialized and deserialized
+/// (if its buffer can). This allows saving and restoring the peak statistics state.
+///
+/// # Buffer
+///
+/// The peaks are kept in a [`Ubend`] by default. Any other [`RingBuffer`],
+/// e.g. an [`ArrayUbend`](crate::ArrayUbend) that needs no allocation, can
+/// be wrapped with [`from_buffer`](Self::from_buffer).
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Peaks {
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "B: serde::Serialize",
+        deserialize = "B: serde::Deserialize<'de>"
+    ))
+)]
+pub struct Peaks<F: Float = f64, B: RingBuffer<F> = Ubend<F>> {
     /// Sum of the elements
-    e: f64,
+    e: F,
     /// Sum of the square of the elements
-    e2: f64,
+    e2: F,
     /// Minimum of the elements
-    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_f64"))]
-    min: f64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_float"))]
+    min: F,
     /// Maximum of the elements
-    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_f64"))]
-    max: f64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_float"))]
+    max: F,
     /// Underlying data container
-    container: Ubend,
+    container: B,
+    /// Running mean and sum of squared deviations, if compensated
+    #[cfg_attr(feature = "serde", serde(default))]
+    welford: Option<Welford<F>>,
+}
+
+/// Welford accumulators of the mean and the sum of squared deviations (M2)
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = ""))]
+struct Welford<F: Float> {
+    mean: F,
+    m2: F,
+}
+
+impl<F: Float> Welford<F> {
+    fn new() -> Self {
+        Self {
+            mean: F::zero(),
+            m2: F::zero(),
+        }
+    }
+
+    /// Add `x`, `size` being the number of values including it
+    fn add(&mut self, x: F, size: usize) {
+        let delta = x - self.mean;
+        self.mean += delta / F::from_usize(size);
+        self.m2 += delta * (x - self.mean);
+    }
+
+    /// Remove `x`, `size` being the number of values left without it
+    fn remove(&mut self, x: F, size: usize) {
+        if size == 0 {
+            *self = Self::new();
+            return;
+        }
+        let delta = x - self.mean;
+        self.mean -= delta / F::from_usize(size);
+        // Rounding can bring M2 slightly below zero when the values are equal
+        self.m2 = (self.m2 - delta * (x - self.mean)).max(F::zero());
+    }
 }
 
 impl Peaks {
-    /// Initialize a new Peaks structure with the given size
+    /// Initialize a new `f64` Peaks structure with the given size
     pub fn new(size: usize) -> SpotResult<Self> {
-        Ok(Self {
-            e: 0.0,
-            e2: 0.0,
-            min: f64::NAN,
-            max: f64::NAN,
-            container: Ubend::new(size)?,
-        })
+        Self::new_generic(size)
+    }
+
+    /// Initialize a new `f64` Peaks structure with compensated mean and variance
+    ///
+    /// See [`new_compensated_generic`](Peaks::new_compensated_generic).
+    pub fn new_compensated(size: usize) -> SpotResult<Self> {
+        Self::new_compensated_generic(size)
+    }
+}
+
+impl<F: Float> Peaks<F> {
+    /// Initialize a new Peaks structure of any [`Float`] type with the given size
+    pub fn new_generic(size: usize) -> SpotResult<Self> {
+        Ok(Self::from_buffer(Ubend::new_generic(size)?))
+    }
+
+    /// Initialize a new Peaks structure of any [`Float`] type whose
+    /// [`mean`](Self::mean) and [`variance`](Self::variance) are tracked with
+    /// Welford's algorithm
+    ///
+    /// The default peaks derive the variance from the sums of the elements and
+    /// of their squares, like the C implementation. When the elements share a
+    /// large offset, `e2 / n - mean^2` cancels catastrophically and the
+    /// incremental updates drift over millions of pushes. The compensated peaks
+    /// additionally maintain the running mean and sum of squared deviations,
+    /// which keep their precision. The other stats are unchanged.
+    pub fn new_compensated_generic(size: usize) -> SpotResult<Self> {
+        Ok(Self::new_generic(size)?.compensated())
+    }
+
+    /// Wrap an existing buffer, computing the stats of its elements in one scan
+    ///
+    /// Same as [`from_buffer`](Self::from_buffer).
+    pub fn from_ubend(u: Ubend<F>) -> Self {
+        Self::from_buffer(u)
+    }
+}
+
+impl<F: Float, B: RingBuffer<F>> Peaks<F, B> {
+    /// Wrap an existing buffer, computing the stats of its elements in one scan
+    ///
+    /// The peaks take over the buffer as is: its capacity and wrap-around state
+    /// are kept, so the next [`push`](Self::push) erases the same element as a
+    /// push into `buffer` would.
+    ///
+    /// ```
+    /// use libspot_rs::{ArrayUbend, Peaks};
+    ///
+    /// let mut peaks = Peaks::from_buffer(ArrayUbend::<3>::new());
+    /// for x in [1.0, 2.0, 3.0, 4.0] {
+    ///     peaks.push(x);
+    /// }
+    /// assert_eq!(peaks.mean(), 3.0);
+    /// ```
+    pub fn from_buffer(buffer: B) -> Self {
+        let mut peaks = Self::from_raw_parts(buffer, F::zero(), F::zero(), F::nan(), F::nan());
+        peaks.update_stats();
+        peaks
+    }
+
+    /// Track the mean and variance with Welford's algorithm from now on (see
+    /// [`new_compensated_generic`](Peaks::new_compensated_generic))
+    pub fn compensated(mut self) -> Self {
+        self.welford = Some(Welford::new());
+        self.update_stats();
+        self
+    }
+
+    /// Whether the mean and variance are compensated (see
+    /// [`new_compensated_generic`](Peaks::new_compensated_generic))
+    pub fn is_compensated(&self) -> bool {
+        self.welford.is_some()
+    }
+
+    /// Rebuild the peaks from their raw state, keeping the stats as given
+    pub(crate) fn from_raw_parts(container: B, e: F, e2: F, min: F, max: F) -> Self {
+        Self {
+            e,
+            e2,
+            min,
+            max,
+            container,
+            welford: None,
+        }
     }
 
     /// Get the current size of the peaks container
@@ -47,20 +183,203 @@ impl Peaks {
         self.container.size()
     }
 
+    /// Get the maximum number of peaks kept
+    pub fn capacity(&self) -> usize {
+        self.container.capacity()
+    }
+
+    /// Check if the peaks are at capacity, so that the next push erases the
+    /// oldest one
+    pub fn is_full(&self) -> bool {
+        self.container.is_filled()
+    }
+
+    /// Increase the capacity of the peaks, keeping the stored excesses
+    ///
+    /// See [`Ubend::grow`]; a buffer of fixed size keeps its capacity. The
+    /// stats are unchanged since the elements are.
+    pub fn grow(&mut self, new_capacity: usize) {
+        self.container.grow(new_capacity);
+    }
+
     /// Reset the peaks to their empty state, keeping the allocated buffer.
     pub(crate) fn reset(&mut self) {
-        self.e = 0.0;
-        self.e2 = 0.0;
-        self.min = f64::NAN;
-        self.max = f64::NAN;
-        self.container.reset();
+        self.e = F::zero();
+        self.e2 = F::zero();
+        self.min = F::nan();
+        self.max = F::nan();
+        self.container.clear();
+        if let Some(welford) = self.welford.as_mut() {
+            *welford = Welford::new();
+        }
+    }
+
+    /// Keep only the values for which `f` returns `true`, in insertion order
+    ///
+    /// The container is rebuilt with the kept values and the stats recomputed
+    /// from them. Returns the number of removed values.
+    pub fn retain(&mut self, mut f: impl FnMut(F) -> bool) -> usize {
+        let kept: Vec<F> = self.container.iter().filter(|&x| f(x)).collect();
+        let removed = self.size() - kept.len();
+        self.reset();
+        self.push_iter(kept);
+        removed
+    }
+
+    /// Append the values of `other` after those of `self`
+    ///
+    /// When the values do not all fit in the capacity of `self`, only the
+    /// largest ones are kept (the tail of the excess distribution), in their
+    /// order of the concatenation; among equal values the first ones win. The
+    /// stats are then recomputed in a single scan, like in
+    /// [`from_buffer`](Self::from_buffer).
+    pub fn merge(&mut self, other: &Peaks<F, B>) {
+        let capacity = self.capacity();
+        let mut values: Vec<F> = self.iter().collect();
+        values.extend(other.iter());
+
+        if values.len() > capacity {
+            let mut order: Vec<usize> = (0..values.len()).collect();
+            order.sort_by(|&a, &b| values[b].partial_cmp(&values[a]).unwrap_or(Ordering::Equal));
+            order.truncate(capacity);
+            order.sort_unstable();
+            values = order.into_iter().map(|index| values[index]).collect();
+        }
+
+        // The capacity is that of an existing buffer, so this cannot fail
+        if let Ok(mut container) = B::with_capacity(capacity) {
+            for &x in &values {
+                container.push(x);
+            }
+            self.container = container;
+            self.update_stats();
+        }
     }
 
     /// Add a new data point into the peaks
-    pub fn push(&mut self, x: f64) {
+    ///
+    /// NaN values are ignored (and trip a debug assertion), since they would
+    /// poison the sums and the min/max; see [`push_checked`](Self::push_checked).
+    pub fn push(&mut self, x: F) {
+        debug_assert!(!x.is_nan(), "NaN pushed into Peaks");
+        self.push_checked(x);
+    }
+
+    /// Add a new data point into the peaks, returning whether it was accepted
+    ///
+    /// NaN values are rejected and leave the peaks untouched.
+    pub fn push_checked(&mut self, x: F) -> bool {
+        if x.is_nan() {
+            return false;
+        }
+        if self.push_value(x) {
+            self.update_stats();
+        }
+        true
+    }
+
+    /// Add several data points into the peaks
+    ///
+    /// Gives exactly the same result as calling [`push`](Self::push) for each
+    /// value, but rescans the container at most once: `push` rescans it each
+    /// time the erased value was the min or the max, and every rescan overwrites
+    /// the stats accumulated before it. The last push needing a rescan is found
+    /// by tracking the sliding min and max, the values before it are only
+    /// stored, and the following ones are pushed as usual. NaN values are
+    /// skipped.
+    pub fn push_iter(&mut self, it: impl IntoIterator<Item = F>) {
+        let values: Vec<F> = it
+            .into_iter()
+            .inspect(|x| debug_assert!(!x.is_nan(), "NaN pushed into Peaks"))
+            .filter(|x| !x.is_nan())
+            .collect();
+
+        let Some(last) = self.last_rescan(&values) else {
+            for &x in &values {
+                self.push(x);
+            }
+            return;
+        };
+        for &x in &values[..last] {
+            self.container.push(x);
+        }
+        self.push_value(values[last]);
+        self.update_stats();
+        for &x in &values[last + 1..] {
+            self.push(x);
+        }
+    }
+
+    /// Index of the last of `values` whose [`push`](Self::push) would rescan
+    /// the container, if any
+    fn last_rescan(&self, values: &[F]) -> Option<usize> {
+        let capacity = self.capacity();
+        let stored: Vec<F> = self.container.iter().collect();
+        if stored.iter().any(|x| x.is_nan()) {
+            // A stored NaN is not counted as erased, replay every push instead
+            return None;
+        }
+
+        // Monotonic deques of the indices of the sliding min and max over the
+        // stored values followed by the new ones
+        let sequence = |index: usize| {
+            if index < stored.len() {
+                stored[index]
+            } else {
+                values[index - stored.len()]
+            }
+        };
+        let mut mins: VecDeque<usize> = VecDeque::new();
+        let mut maxs: VecDeque<usize> = VecDeque::new();
+        let mut last = None;
+        for index in 0..stored.len() + values.len() {
+            let x = sequence(index);
+            if index >= capacity {
+                // The window before the push ends at `index` and starts with
+                // the value erased by the push
+                let start = index - capacity;
+                let erased = sequence(start);
+                let min = sequence(mins[0]);
+                let max = sequence(maxs[0]);
+                if index >= stored.len()
+                    && ((erased <= min && erased <= x) || (erased >= max && erased >= x))
+                {
+                    last = Some(index - stored.len());
+                }
+                if mins[0] == start {
+                    mins.pop_front();
+                }
+                if maxs[0] == start {
+                    maxs.pop_front();
+                }
+            }
+            while mins.back().is_some_and(|&i| sequence(i) >= x) {
+                mins.pop_back();
+            }
+            mins.push_back(index);
+            while maxs.back().is_some_and(|&i| sequence(i) <= x) {
+                maxs.pop_back();
+            }
+            maxs.push_back(index);
+        }
+        last
+    }
+
+    /// Add a new data point, returning whether the stats must be recomputed
+    /// with [`update_stats`](Self::update_stats)
+    fn push_value(&mut self, x: F) -> bool {
         let erased = self.container.push(x);
         let size = self.size();
 
+        if let Some(welford) = self.welford.as_mut() {
+            if erased.is_nan() {
+                welford.add(x, size);
+            } else {
+                welford.remove(erased, size - 1);
+                welford.add(x, size);
+            }
+        }
+
         // Increment the stats
         self.e += x;
         self.e2 += x * x;
@@ -78,71 +397,136 @@ impl Peaks {
         if !erased.is_nan() {
             self.e -= erased;
             self.e2 -= erased * erased;
-            if (erased <= self.min) || (erased >= self.max) {
-                // Here we have to loop in the container to ensure having
-                // the right stats (in particular min and max). However, we
-                // also update e and e2 (the in/decrements may create precision errors)
-                self.update_stats();
-            }
+            // Here we have to loop in the container to ensure having
+            // the right stats (in particular min and max). However, we
+            // also update e and e2 (the in/decrements may create precision errors)
+            return (erased <= self.min) || (erased >= self.max);
         }
+        false
     }
 
     /// Compute the mean of the elements
-    pub fn mean(&self) -> f64 {
+    pub fn mean(&self) -> F {
         let size = self.size();
         if size == 0 {
-            f64::NAN
+            F::nan()
+        } else if let Some(welford) = self.welford {
+            welford.mean
         } else {
-            self.e / (size as f64)
+            self.e / F::from_usize(size)
         }
     }
 
     /// Compute the variance of the elements
-    pub fn variance(&self) -> f64 {
+    ///
+    /// The variance is `e2 / size - mean^2` like in the C implementation, which
+    /// cancels catastrophically when the elements are nearly equal. It is
+    /// therefore never negative: a value below zero within the rounding error
+    /// of `e2 / size` is clamped to 0, and a larger negative one is recomputed
+    /// with two passes over the elements.
+    pub fn variance(&self) -> F {
         let size = self.size();
         if size == 0 {
-            f64::NAN
+            F::nan()
         } else {
-            let size_f = size as f64;
+            let size_f = F::from_usize(size);
+            if let Some(welford) = self.welford {
+                return welford.m2 / size_f;
+            }
             let mean = self.e / size_f;
-            (self.e2 / size_f) - (mean * mean)
+            let mean_square = self.e2 / size_f;
+            let variance = mean_square - (mean * mean);
+            if variance < -(F::epsilon() * mean_square) {
+                self.two_pass_variance()
+            } else {
+                variance.max(F::zero())
+            }
         }
     }
 
+    /// Variance of the elements computed from their mean, in two passes
+    fn two_pass_variance(&self) -> F {
+        let size_f = F::from_usize(self.size());
+        let mean = self.container.iter().fold(F::zero(), |sum, x| sum + x) / size_f;
+        self.container.iter().fold(F::zero(), |sum, x| {
+            let d = x - mean;
+            sum + d * d
+        }) / size_f
+    }
+
+    /// Compute the empirical `p`-quantile of the stored peaks
+    ///
+    /// Sorts a copy of the data and interpolates linearly between the two
+    /// closest order statistics, so `p = 0` gives the minimum and `p = 1` the
+    /// maximum. Returns NaN if the peaks are empty or `p` is outside `[0, 1]`.
+    pub fn quantile(&self, p: F) -> F {
+        sorted_quantile(self.iter().collect(), p)
+    }
+
+    /// Count the stored peaks in `bins` bins of equal width over `[min, max]`
+    ///
+    /// Returns the left edge and the count of each bin, the maximum falling in
+    /// the last one. All the peaks fall in a single bin at `min` if they are
+    /// all equal, and the histogram is empty if the peaks are or `bins` is 0.
+    pub fn histogram(&self, bins: usize) -> Vec<(F, usize)> {
+        let size = self.size();
+        if size == 0 || bins == 0 {
+            return Vec::new();
+        }
+        if self.max <= self.min {
+            return vec![(self.min, size)];
+        }
+
+        let width = (self.max - self.min) / F::from_usize(bins);
+        let mut histogram: Vec<(F, usize)> = (0..bins)
+            .map(|i| (self.min + F::from_usize(i) * width, 0))
+            .collect();
+        for x in self.container.iter() {
+            let i = ((x - self.min) / width).to_usize().unwrap_or(0);
+            histogram[i.min(bins - 1)].1 += 1;
+        }
+        histogram
+    }
+
     /// Get the minimum value
-    pub fn min(&self) -> f64 {
+    pub fn min(&self) -> F {
         self.min
     }
 
     /// Get the maximum value
-    pub fn max(&self) -> f64 {
+    pub fn max(&self) -> F {
         self.max
     }
 
     /// Get the sum of elements
-    pub fn sum(&self) -> f64 {
+    pub fn sum(&self) -> F {
         self.e
     }
 
     /// Get the sum of squares
-    pub fn sum_squares(&self) -> f64 {
+    pub fn sum_squares(&self) -> F {
         self.e2
     }
 
     /// Get access to the underlying container
-    pub fn container(&self) -> &Ubend {
+    pub fn container(&self) -> &B {
         &self.container
     }
 
+    /// Get an iterator over the stored values in insertion order
+    pub fn iter(&self) -> B::Iter<'_> {
+        self.container.iter()
+    }
+
     /// Update all statistics by iterating through the container
     /// This is called when we need to recompute min/max after an erasure
     fn update_stats(&mut self) {
         // Reset min and max
-        self.min = f64::NAN;
-        self.max = f64::NAN;
+        self.min = F::nan();
+        self.max = F::nan();
         // Reset accumulators
-        self.e = 0.0;
-        self.e2 = 0.0;
+        self.e = F::zero();
+        self.e2 = F::zero();
 
         let max_iteration = self.container.size();
 
@@ -159,6 +543,26 @@ impl Peaks {
                 self.max = value;
             }
         }
+
+        // Restart the compensated accumulators as well, dropping any drift
+        if let Some(welford) = self.welford.as_mut() {
+            *welford = Welford::new();
+            for (i, &value) in self.container.raw_data()[..max_iteration]
+                .iter()
+                .enumerate()
+            {
+                welford.add(value, i + 1);
+            }
+        }
+    }
+}
+
+impl<'a, F: Float, B: RingBuffer<F>> IntoIterator for &'a Peaks<F, B> {
+    type Item = F;
+    type IntoIter = B::Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
     }
 }
 
@@ -166,8 +570,193 @@ impl Peaks {
 mod tests {
     use super::*;
     use crate::error::SpotError;
+    use crate::testing::{exponential_data, SeededExp};
     use approx::assert_relative_eq;
 
+    #[test]
+    fn test_peaks_capacity_and_is_full() {
+        let mut p = Peaks::new(3).unwrap();
+        assert_eq!(p.capacity(), 3);
+        assert!(!p.is_full());
+
+        p.push(1.0);
+        p.push(2.0);
+        assert!(!p.is_full());
+        p.push(3.0);
+        assert!(p.is_full());
+        p.push(4.0);
+        assert!(p.is_full());
+        assert_eq!(p.capacity(), 3);
+
+        p.reset();
+        assert!(!p.is_full());
+        assert_eq!(p.capacity(), 3);
+    }
+
+    #[test]
+    fn test_peaks_into_iterator_matches_container() {
+        let mut p = Peaks::new(3).unwrap();
+        p.push_iter([1.0, 2.0, 3.0, 4.0]);
+
+        let mut values = Vec::new();
+        for x in &p {
+            values.push(x);
+        }
+        assert_eq!(values, vec![2.0, 3.0, 4.0]);
+        assert_eq!(p.iter().collect::<Vec<_>>(), p.container().data());
+        assert_eq!((&p).into_iter().sum::<f64>(), p.sum());
+    }
+
+    #[test]
+    fn test_peaks_merge_keeps_largest_values() {
+        let mut a = Peaks::new(100).unwrap();
+        let mut b = Peaks::new(100).unwrap();
+        a.push_iter((0..80).map(|i| i as f64));
+        b.push_iter((0..80).map(|i| 0.5 + i as f64));
+
+        a.merge(&b);
+        assert_eq!(a.size(), 100);
+        assert_eq!(a.capacity(), 100);
+
+        // The 100 largest of the 160 values: everything from 30 upwards
+        let mut expected: Vec<f64> = (30..80).map(|i| i as f64).collect();
+        expected.extend((30..80).map(|i| 0.5 + i as f64));
+        assert_eq!(a.container().data(), expected);
+        assert_eq!(a.min(), 30.0);
+        assert_eq!(a.max(), 79.5);
+        assert_relative_eq!(a.sum(), expected.iter().sum::<f64>());
+        assert_relative_eq!(a.sum_squares(), expected.iter().map(|x| x * x).sum::<f64>());
+        let mean = expected.iter().sum::<f64>() / 100.0;
+        assert_relative_eq!(a.mean(), mean);
+        assert_relative_eq!(
+            a.variance(),
+            expected
+                .iter()
+                .map(|x| (x - mean) * (x - mean))
+                .sum::<f64>()
+                / 100.0,
+            max_relative = 1e-12
+        );
+
+        // Without overflow the values are appended
+        let mut c = Peaks::new(10).unwrap();
+        c.push_iter([3.0, 1.0]);
+        let mut d = Peaks::new(5).unwrap();
+        d.push_iter([2.0, 4.0]);
+        c.merge(&d);
+        assert_eq!(c.container().data(), vec![3.0, 1.0, 2.0, 4.0]);
+        assert_eq!((c.min(), c.max(), c.sum()), (1.0, 4.0, 10.0));
+        c.push(5.0);
+        assert_eq!(c.size(), 5);
+    }
+
+    #[test]
+    fn test_peaks_compensated_variance_with_large_offset() {
+        let offset = 1e9;
+        let size = 1000;
+        let mut naive = Peaks::new(size).unwrap();
+        let mut compensated = Peaks::new_compensated(size).unwrap();
+        assert!(compensated.is_compensated() && !naive.is_compensated());
+
+        let pushes = 10_000_000;
+        let mut rng = SeededExp::new(8);
+        let mut last = Vec::with_capacity(size);
+        for i in 0..pushes {
+            let u = (-rng.next()).exp();
+            if i >= pushes - size {
+                last.push(u);
+            }
+            naive.push(offset + u);
+            compensated.push(offset + u);
+        }
+
+        // Exact stats of the last `size` values, computed without the offset
+        let mean = last.iter().sum::<f64>() / size as f64;
+        let variance = last.iter().map(|u| (u - mean).powi(2)).sum::<f64>() / size as f64;
+
+        let compensated_error = (compensated.variance() - variance).abs();
+        // The running sums drift so far that `e2 / n - mean^2` is useless (the
+        // naive variance then falls back on two passes over the elements)
+        let naive_mean = naive.sum() / size as f64;
+        let naive_error =
+            (naive.sum_squares() / size as f64 - naive_mean * naive_mean - variance).abs();
+        assert!(compensated_error < 1e-6 * variance, "{compensated_error}");
+        assert!(naive_error > 1e3 * compensated_error, "{naive_error}");
+        assert_relative_eq!(naive.variance(), variance, max_relative = 1e-6);
+        assert_relative_eq!(compensated.mean(), offset + mean, max_relative = 1e-15);
+        // The other stats are shared with the default path
+        assert_eq!(compensated.sum(), naive.sum());
+        assert_eq!(compensated.min(), naive.min());
+        assert_eq!(compensated.max(), naive.max());
+    }
+
+    #[test]
+    fn test_peaks_variance_is_never_negative() {
+        let mut peaks = Peaks::new(200).unwrap();
+        // 5.0 - 1e-12 then 5.0 + 1e-12, whose variance is 1e-24
+        let values: Vec<f64> = (0..200)
+            .map(|i| if i < 100 { 5.0 - 1e-12 } else { 5.0 + 1e-12 })
+            .collect();
+        for &x in &values {
+            peaks.push(x);
+        }
+        let mean = peaks.sum() / 200.0;
+        assert!(peaks.sum_squares() / 200.0 - mean * mean < 0.0);
+
+        let variance = peaks.variance();
+        assert!(variance >= 0.0);
+        assert_relative_eq!(variance, 1e-24, max_relative = 1e-2);
+    }
+
+    #[test]
+    fn test_peaks_from_ubend() {
+        let mut ubend = Ubend::new(5).unwrap();
+        for x in [4.0, 1.5, 3.0, 0.5, 2.0, 6.0, 2.5] {
+            ubend.push(x);
+        }
+        let values = ubend.data();
+        assert_eq!(values, vec![3.0, 0.5, 2.0, 6.0, 2.5]);
+
+        let mut peaks = Peaks::from_ubend(ubend);
+        assert_eq!(peaks.size(), 5);
+        assert_eq!(peaks.capacity(), 5);
+        assert_relative_eq!(peaks.sum(), values.iter().sum::<f64>());
+        assert_relative_eq!(
+            peaks.sum_squares(),
+            values.iter().map(|x| x * x).sum::<f64>()
+        );
+        assert_eq!((peaks.min(), peaks.max()), (0.5, 6.0));
+        assert_relative_eq!(peaks.mean(), 2.8);
+        assert_relative_eq!(peaks.variance(), 3.26, epsilon = 1e-12);
+
+        // Further pushes keep evicting in insertion order
+        peaks.push(1.0);
+        assert_eq!(peaks.container().data(), vec![0.5, 2.0, 6.0, 2.5, 1.0]);
+        assert_relative_eq!(peaks.sum(), 12.0);
+
+        let empty = Peaks::from_ubend(Ubend::new(3).unwrap());
+        assert_eq!(empty.size(), 0);
+        assert!(empty.min().is_nan() && empty.mean().is_nan());
+    }
+
+    #[test]
+    fn test_peaks_compensated_matches_default_on_small_values() {
+        let mut naive = Peaks::new(4).unwrap();
+        let mut compensated = Peaks::new_compensated(4).unwrap();
+        assert!(compensated.mean().is_nan() && compensated.variance().is_nan());
+        for x in [1.0, 2.0, 3.0, 4.0, 5.0, 6.0] {
+            naive.push(x);
+            compensated.push(x);
+            assert_relative_eq!(compensated.mean(), naive.mean(), epsilon = 1e-12);
+            assert_relative_eq!(compensated.variance(), naive.variance(), epsilon = 1e-12);
+        }
+
+        compensated.reset();
+        assert!(compensated.is_compensated());
+        compensated.push(2.0);
+        assert_eq!((compensated.mean(), compensated.variance()), (2.0, 0.0));
+    }
+
     #[test]
     fn test_peaks_reset_clears_stats() {
         let mut p = Peaks::new(4).unwrap();
@@ -322,4 +911,172 @@ mod tests {
         assert_relative_eq!(peaks.min(), 1.5);
         assert_relative_eq!(peaks.max(), 2.0);
     }
+
+    #[test]
+    fn test_peaks_push_iter_matches_push() {
+        let data: Vec<f64> = exponential_data(10_000, 5)
+            .into_iter()
+            .map(|x| (-x).exp())
+            .collect();
+
+        // Decreasing values erase the max on every push once full
+        let decreasing: Vec<f64> = (0..500).map(|i| 1e3 - i as f64 * 0.7).collect();
+
+        for compensated in [false, true] {
+            let new = || {
+                if compensated {
+                    Peaks::new_compensated(200).unwrap()
+                } else {
+                    Peaks::new(200).unwrap()
+                }
+            };
+            for (prefix, batch) in [
+                (&data[..0], &data[..]),
+                (&data[..50], &data[50..]),
+                (&data[..300], &data[300..310]),
+                (&data[..300], &decreasing[..]),
+                (&decreasing[..], &data[..5]),
+            ] {
+                let mut single = new();
+                let mut bulk = new();
+                for &x in prefix {
+                    single.push(x);
+                    bulk.push(x);
+                }
+                for &x in batch {
+                    single.push(x);
+                }
+                bulk.push_iter(batch.iter().copied());
+
+                assert_eq!(bulk.size(), single.size());
+                assert_eq!(bulk.container().data(), single.container().data());
+                assert_eq!(bulk.min(), single.min());
+                assert_eq!(bulk.max(), single.max());
+                assert_eq!(bulk.mean(), single.mean());
+                assert_eq!(bulk.variance(), single.variance());
+            }
+        }
+    }
+
+    #[test]
+    fn test_peaks_quantile() {
+        let mut peaks = Peaks::new(10).unwrap();
+        assert!(peaks.quantile(0.5).is_nan());
+
+        for v in [5.0, 1.0, 4.0, 2.0, 3.0] {
+            peaks.push(v);
+        }
+        assert_relative_eq!(peaks.quantile(0.5), 3.0);
+        assert_relative_eq!(peaks.quantile(0.0), 1.0);
+        assert_relative_eq!(peaks.quantile(1.0), 5.0);
+        assert_relative_eq!(peaks.quantile(0.25), 2.0);
+        assert_relative_eq!(peaks.quantile(0.9), 4.6);
+
+        peaks.push(6.0);
+        assert_relative_eq!(peaks.quantile(0.5), 3.5);
+
+        for p in [-0.1, 1.1, f64::NAN] {
+            assert!(peaks.quantile(p).is_nan());
+        }
+    }
+
+    #[test]
+    fn test_peaks_quantile_after_wrap_and_single_value() {
+        let mut peaks = Peaks::new(3).unwrap();
+        for v in [100.0, 1.0, 2.0, 3.0] {
+            peaks.push(v);
+        }
+        // 100.0 was erased
+        assert_relative_eq!(peaks.quantile(1.0), 3.0);
+        assert_relative_eq!(peaks.quantile(0.5), 2.0);
+
+        let mut single = Peaks::new(3).unwrap();
+        single.push(7.0);
+        assert_relative_eq!(single.quantile(0.0), 7.0);
+        assert_relative_eq!(single.quantile(0.5), 7.0);
+        assert_relative_eq!(single.quantile(1.0), 7.0);
+    }
+
+    #[test]
+    fn test_peaks_histogram() {
+        let mut peaks = Peaks::new(10).unwrap();
+        assert!(peaks.histogram(4).is_empty());
+
+        peaks.push_iter([0.5, 1.0, 1.5, 2.0, 2.5, 3.0, 4.5, 5.0]);
+        let histogram = peaks.histogram(3);
+        assert_eq!(histogram.len(), 3);
+        assert_eq!(
+            histogram.iter().map(|&(_, count)| count).sum::<usize>(),
+            peaks.size()
+        );
+        assert_eq!(histogram[0], (0.5, 3));
+        assert_eq!(histogram[1], (2.0, 3));
+        assert_eq!(histogram[2], (3.5, 2));
+        // The bins span [min, max]
+        let width = histogram[1].0 - histogram[0].0;
+        assert_relative_eq!(histogram[2].0 + width, peaks.max());
+        assert!(peaks.histogram(0).is_empty());
+
+        let mut constant = Peaks::new(5).unwrap();
+        constant.push_iter([2.0; 4]);
+        assert_eq!(constant.histogram(10), vec![(2.0, 4)]);
+    }
+
+    #[test]
+    fn test_peaks_push_checked_rejects_nan() {
+        let mut peaks = Peaks::new(3).unwrap();
+        assert!(!peaks.push_checked(f64::NAN));
+        assert_eq!(peaks.size(), 0);
+        assert!(peaks.min().is_nan() && peaks.max().is_nan());
+        assert_eq!(peaks.sum(), 0.0);
+
+        assert!(peaks.push_checked(1.0));
+        assert!(peaks.push_checked(2.0));
+        assert!(!peaks.push_checked(f64::NAN));
+        assert_eq!(peaks.size(), 2);
+        assert_relative_eq!(peaks.min(), 1.0);
+        assert_relative_eq!(peaks.max(), 2.0);
+        assert_relative_eq!(peaks.mean(), 1.5);
+        assert!(peaks.variance().is_finite());
+    }
+
+    #[test]
+    #[should_panic(expected = "NaN pushed into Peaks")]
+    #[cfg(debug_assertions)]
+    fn test_peaks_push_nan_debug_asserts() {
+        let mut peaks = Peaks::new(3).unwrap();
+        peaks.push(f64::NAN);
+    }
+
+    #[test]
+    fn test_peaks_over_array_ubend_match_ubend() {
+        use crate::ubend::ArrayUbend;
+
+        let mut peaks = Peaks::new(50).unwrap().compensated();
+        let mut array = Peaks::from_buffer(ArrayUbend::<50>::new()).compensated();
+        let mut rng = SeededExp::new(3);
+        for _ in 0..120 {
+            let x = rng.next();
+            peaks.push(x);
+            array.push(x);
+        }
+
+        assert_eq!(array.size(), peaks.size());
+        assert_eq!(array.mean(), peaks.mean());
+        assert_eq!(array.variance(), peaks.variance());
+        assert_eq!((array.min(), array.max()), (peaks.min(), peaks.max()));
+        assert_eq!(array.quantile(0.9), peaks.quantile(0.9));
+        assert!(array.iter().eq(peaks.iter()));
+
+        let other: Vec<f64> = (0..30).map(|_| rng.next()).collect();
+        let mut other_peaks = Peaks::new(50).unwrap();
+        let mut other_array = Peaks::from_buffer(ArrayUbend::<50>::new());
+        other_peaks.push_iter(other.iter().copied());
+        other_array.push_iter(other.iter().copied());
+        peaks.merge(&other_peaks);
+        array.merge(&other_array);
+        assert!(array.iter().eq(peaks.iter()));
+        assert_eq!(array.mean(), peaks.mean());
+        assert_eq!(array.variance(), peaks.variance());
+    }
 }
@@ -0,0 +1,239 @@
+//! Diagnostic report types returned by the SPOT detector
+
+use core::fmt;
+
+use crate::error::SpotError;
+use crate::estimator::EstimatorKind;
+use crate::float::Float;
+use crate::status::SpotStatus;
+
+/// Out-of-sample statistics collected by
+/// [`SpotDetector::fit_with_validation`](crate::SpotDetector::fit_with_validation)
+///
+/// The detector is fitted on the leading part of the data and then stepped
+/// through the held-out part. A realized excess rate far from the expected
+/// `1 - level` signals a mis-specified model.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValidationReport {
+    /// Number of points used to fit the model
+    pub training_size: usize,
+    /// Number of held-out points stepped through the fitted model
+    pub validation_size: usize,
+    /// Number of held-out points classified as [`SpotStatus::Excess`](crate::SpotStatus::Excess)
+    pub excesses: usize,
+    /// Number of held-out points classified as [`SpotStatus::Anomaly`](crate::SpotStatus::Anomaly)
+    pub anomalies: usize,
+    /// Fraction of held-out points that landed in the tail (excesses and anomalies)
+    pub excess_rate: f64,
+    /// Fraction of held-out points flagged as anomalies
+    pub anomaly_rate: f64,
+    /// Excess rate expected from the configuration (`1 - level`)
+    pub expected_excess_rate: f64,
+}
+
+/// Classification of a single value with the model state it left behind,
+/// returned by [`SpotDetector::step_detailed`](crate::SpotDetector::step_detailed)
+///
+/// All fields are read right after the step, so they are consistent with each
+/// other even if the detector is stepped again later.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = ""))]
+pub struct StepReport<F: Float = f64> {
+    /// Classification of the value
+    pub status: SpotStatus,
+    /// The classified value
+    pub value: F,
+    /// Anomaly threshold after the step
+    pub anomaly_threshold: F,
+    /// Excess threshold after the step
+    pub excess_threshold: F,
+    /// Probability of observing a value beyond `value` under the updated model
+    pub probability: F,
+}
+
+/// Outcome of a GPD fit, returned by [`Tail::fit_report`](crate::Tail::fit_report)
+///
+/// # Serialization
+///
+/// When the `serde` feature is enabled, this struct can be serialized and deserialized.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = ""))]
+pub struct FitReport<F: Float = f64> {
+    /// Fitted GPD gamma parameter
+    pub gamma: F,
+    /// Fitted GPD sigma parameter
+    pub sigma: F,
+    /// Log-likelihood of the peaks under the fitted GPD
+    pub log_likelihood: F,
+    /// Estimator whose candidate had the highest log-likelihood
+    pub estimator: EstimatorKind,
+}
+
+/// Snapshot of a detector's counters, thresholds and tail parameters, returned
+/// by [`SpotDetector::summary`](crate::SpotDetector::summary)
+///
+/// # Serialization
+///
+/// When the `serde` feature is enabled, this struct can be serialized and deserialized.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = ""))]
+pub struct SpotSummary<F: Float = f64> {
+    /// Total number of data points seen
+    pub n: usize,
+    /// Number of excesses
+    pub nt: usize,
+    /// Excess threshold (`T`)
+    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_float"))]
+    pub excess_threshold: F,
+    /// Anomaly threshold (`Z`)
+    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_float"))]
+    pub anomaly_threshold: F,
+    /// GPD gamma parameter
+    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_float"))]
+    pub gamma: F,
+    /// GPD sigma parameter
+    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_float"))]
+    pub sigma: F,
+}
+
+/// Numerical self-check of a detector, returned by
+/// [`SpotDetector::health`](crate::SpotDetector::health)
+///
+/// A detector is healthy when every check passes, see
+/// [`is_healthy`](Self::is_healthy). A detector that has not been fitted yet
+/// fails every check except possibly `tail_full`.
+///
+/// With an [`AdaptiveTail`](crate::AdaptiveTail) policy, the buffer grows
+/// once full, so `tail_full` is reported but not required.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpotHealth {
+    /// Whether a fit has set the excess threshold
+    pub fitted: bool,
+    /// Whether both the excess and the anomaly thresholds are finite
+    pub thresholds_finite: bool,
+    /// Whether gamma is finite and not below the configured
+    /// [`min_gamma`](crate::SpotConfig::min_gamma)
+    pub gamma_in_range: bool,
+    /// Whether sigma is finite and strictly positive
+    pub sigma_positive: bool,
+    /// Whether the tail buffer holds `max_excess` excesses
+    pub tail_full: bool,
+    /// Whether the tail buffer grows with an [`AdaptiveTail`](crate::AdaptiveTail)
+    /// policy, which exempts it from `tail_full`
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub adaptive_tail: bool,
+}
+
+impl SpotHealth {
+    /// Whether every check passes (`tail_full` only without an adaptive tail)
+    pub fn is_healthy(&self) -> bool {
+        self.fitted
+            && self.thresholds_finite
+            && self.gamma_in_range
+            && self.sigma_positive
+            && (self.tail_full || self.adaptive_tail)
+    }
+}
+
+/// Work counters of a detector, returned by
+/// [`SpotDetector::stats`](crate::SpotDetector::stats)
+///
+/// They make the cost of a run observable without a profiler: each refit of
+/// the tail runs the MoM estimator and the Grimshaw root search, the latter
+/// being the dominant cost of [`step`](crate::SpotDetector::step). The
+/// counters start at zero on creation, [`reset`](crate::SpotDetector::reset)
+/// and deserialization.
+///
+/// # Serialization
+///
+/// When the `serde` feature is enabled, this struct can be serialized and deserialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuntimeStats {
+    /// Number of calls to `step`
+    pub steps: u64,
+    /// Number of steps classified as excesses
+    pub excesses: u64,
+    /// Number of tail fits triggered by an excess
+    pub refits: u64,
+    /// Number of Grimshaw estimates, training fits included
+    pub grimshaw_calls: u64,
+    /// Number of MoM estimates, training fits included
+    pub mom_calls: u64,
+}
+
+/// Intermediate values of the quantile computation, returned by
+/// [`SpotDetector::quantile_components`](crate::SpotDetector::quantile_components)
+///
+/// The quantile is `z = excess_threshold ± tail_quantile` (minus for the lower
+/// tail), where `tail_quantile` is the GPD quantile of probability `r = q / s`.
+///
+/// # Serialization
+///
+/// When the `serde` feature is enabled, this struct can be serialized and deserialized.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = ""))]
+pub struct QuantileBreakdown<F: Float = f64> {
+    /// Tail mass `s = nt / n`
+    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_float"))]
+    pub s: F,
+    /// Probability within the tail `r = q / s`
+    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_float"))]
+    pub r: F,
+    /// Distance of the quantile beyond the excess threshold
+    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_float"))]
+    pub tail_quantile: F,
+    /// Excess threshold (`T`)
+    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_float"))]
+    pub excess_threshold: F,
+    /// Quantile (`z`)
+    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_float"))]
+    pub z: F,
+}
+
+/// Failed fit with the state reached before the failure, returned by
+/// [`SpotDetector::try_fit`](crate::SpotDetector::try_fit)
+///
+/// # Serialization
+///
+/// When the `serde` feature is enabled, this struct can be serialized and deserialized.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = ""))]
+pub struct FitFailure<F: Float = f64> {
+    /// Excess threshold computed from the training data (NaN if it could not be)
+    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_float"))]
+    pub excess_threshold: F,
+    /// Number of training values strictly beyond the excess threshold
+    pub nt: usize,
+    /// Error returned by [`SpotDetector::fit`](crate::SpotDetector::fit)
+    pub error: SpotError,
+}
+
+impl<F: Float> fmt::Display for FitFailure<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (excess threshold {}, {} excesses)",
+            self.error, self.excess_threshold, self.nt
+        )
+    }
+}
+
+impl<F: Float> core::error::Error for FitFailure<F> {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl<F: Float> From<FitFailure<F>> for SpotError {
+    fn from(failure: FitFailure<F>) -> Self {
+        failure.error
+    }
+}
@@ -0,0 +1,374 @@
+//! Rolling SPOT detector
+//!
+//! The SPOT detector gives the same weight to every excess in its tail, so after a
+//! change in the excess distribution the anomaly threshold only catches up once
+//! the old excesses have been evicted from the buffer. [`RollingSpot`] instead
+//! weights each excess (and each observation) by `decay^age`, where the age is
+//! counted in steps, so that recent excesses dominate the GPD fit.
+
+use alloc::vec::Vec;
+
+use crate::config::SpotConfig;
+use crate::error::{SpotError, SpotResult};
+use crate::p2::P2Estimator;
+use crate::spot::finite_training_data;
+use crate::status::SpotStatus;
+use crate::tail::Tail;
+use crate::ubend::Ubend;
+
+/// Configuration parameters for the rolling SPOT detector
+///
+/// # Serialization
+///
+/// When the `serde` feature is enabled, this struct can be serialized and deserialized.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RollingSpotConfig {
+    /// Decay factor applied to the weight of past values at each step, in (0, 1]
+    ///
+    /// `None` (or `Some(1.0)`) weights all values equally, like [`SpotDetector`](crate::SpotDetector).
+    pub decay: Option<f64>,
+    /// Configuration of the underlying SPOT algorithm
+    ///
+    /// `track_excess_threshold`, `warmup_steps` and `adaptive_tail` are ignored.
+    pub spot: SpotConfig,
+}
+
+/// SPOT detector whose tail fit favors the most recent excesses
+///
+/// The counters `n` and `nt` are decayed sums: each step multiplies them by the
+/// decay factor before counting the new value, and the tail is fitted with the
+/// weighted MoM and Grimshaw estimators. Without decay, the detector reproduces
+/// [`SpotDetector`](crate::SpotDetector) step for step.
+///
+/// # Serialization
+///
+/// When the `serde` feature is enabled, this struct can be serialized and deserialized.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RollingSpot {
+    /// Probability of an anomaly
+    q: f64,
+    /// Location of the tail (high quantile)
+    level: f64,
+    /// Flag anomalies (true = flag, false = don't flag)
+    discard_anomalies: bool,
+    /// Upper/Lower tail choice (true = lower tail, false = upper tail)
+    low: bool,
+    /// Internal constant (+/- 1.0)
+    up_down: f64,
+    /// Decay factor of the weights (`None` keeps them equal)
+    decay: Option<f64>,
+    /// Minimum number of excesses required to refit the tail
+    min_peaks: usize,
+    /// Reject infinite values instead of keeping them out of the model
+    reject_infinite: bool,
+    /// Normal/abnormal threshold
+    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_float"))]
+    anomaly_threshold: f64,
+    /// Tail threshold
+    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_float"))]
+    excess_threshold: f64,
+    /// Decayed number of observations
+    n: f64,
+    /// Decayed number of excesses
+    nt: f64,
+    /// Number of steps counted so far (training values included)
+    clock: f64,
+    /// GPD tail
+    tail: Tail,
+    /// Clock value at which each excess of the tail was observed, aligned with
+    /// the raw order of the peaks container
+    stamps: Ubend,
+}
+
+impl RollingSpot {
+    /// Create a new rolling SPOT detector with the given configuration
+    pub fn new(config: RollingSpotConfig) -> SpotResult<Self> {
+        config.spot.validate()?;
+        if let Some(decay) = config.decay {
+            if !(decay > 0.0 && decay <= 1.0) {
+                return Err(SpotError::DecayOutOfBounds);
+            }
+        }
+
+        let spot = config.spot;
+        Ok(Self {
+            q: spot.q,
+            level: spot.level,
+            discard_anomalies: spot.discard_anomalies,
+            low: spot.low_tail,
+            up_down: if spot.low_tail { -1.0 } else { 1.0 },
+            decay: config.decay,
+            min_peaks: spot.min_peaks,
+            reject_infinite: spot.reject_infinite,
+            anomaly_threshold: f64::NAN,
+            excess_threshold: f64::NAN,
+            n: 0.0,
+            nt: 0.0,
+            clock: 0.0,
+            tail: Tail::new(spot.max_excess)?
+                .with_pwm(spot.pwm_estimator)
+                .with_grimshaw_options(spot.grimshaw_options)
+                .with_fit_strategy(spot.fit_strategy)
+                .with_min_gamma(spot.min_gamma),
+            stamps: Ubend::new(spot.max_excess)?,
+        })
+    }
+
+    /// Fit the model using initial training data
+    ///
+    /// The training values are counted in order, so the last ones weigh the most.
+    pub fn fit(&mut self, data: &[f64]) -> SpotResult<()> {
+        let data = &*finite_training_data(data, self.reject_infinite)?;
+        let p = if self.low {
+            1.0 - self.level
+        } else {
+            self.level
+        };
+        let mut estimator = P2Estimator::new_reference(p);
+        for &value in data {
+            estimator.push(value);
+        }
+        let et = estimator.quantile_or_zero();
+        if et.is_nan() {
+            return Err(SpotError::ExcessThresholdIsNaN);
+        }
+
+        self.excess_threshold = et;
+        self.n = 0.0;
+        self.nt = 0.0;
+        self.clock = 0.0;
+        self.tail.reset();
+        self.stamps.clear();
+        for &value in data {
+            let excess = self.up_down * (value - et);
+            self.count(excess > 0.0);
+            if excess > 0.0 {
+                self.record(excess);
+            }
+        }
+
+        self.refit();
+        self.anomaly_threshold = self.quantile(self.q);
+        if self.anomaly_threshold.is_nan() {
+            return Err(if self.tail.size() >= self.min_peaks {
+                SpotError::AnomalyThresholdIsNaN
+            } else {
+                SpotError::TooFewExcesses {
+                    have: self.tail.size(),
+                    need: self.min_peaks,
+                }
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Process a single data point and return its classification
+    pub fn step(&mut self, value: f64) -> SpotResult<SpotStatus> {
+        if value.is_nan() {
+            return Err(SpotError::DataIsNaN);
+        }
+        if value.is_infinite() {
+            if self.reject_infinite {
+                return Err(SpotError::DataIsInfinite);
+            }
+            // Beyond any threshold, but kept out of the model
+            return Ok(if self.up_down * value > 0.0 {
+                SpotStatus::Anomaly
+            } else {
+                SpotStatus::Normal
+            });
+        }
+
+        if self.discard_anomalies && (self.up_down * (value - self.anomaly_threshold) > 0.0) {
+            return Ok(SpotStatus::Anomaly);
+        }
+
+        let ex = self.up_down * (value - self.excess_threshold);
+        self.count(ex >= 0.0);
+        if ex >= 0.0 {
+            self.record(ex);
+            if self.tail.size() >= self.min_peaks {
+                self.refit();
+                self.anomaly_threshold = self.quantile(self.q);
+            }
+            return Ok(SpotStatus::Excess);
+        }
+
+        Ok(SpotStatus::Normal)
+    }
+
+    /// Get the quantile for a given probability
+    ///
+    /// Returns NaN if `q` is NaN or the detector has not been fitted.
+    pub fn quantile(&self, q: f64) -> f64 {
+        if self.n == 0.0 || q.is_nan() {
+            return f64::NAN;
+        }
+
+        let s = self.nt / self.n;
+        self.excess_threshold + self.up_down * self.tail.quantile(s, q)
+    }
+
+    /// Get the current anomaly threshold
+    pub fn anomaly_threshold(&self) -> f64 {
+        self.anomaly_threshold
+    }
+
+    /// Get the current excess threshold
+    pub fn excess_threshold(&self) -> f64 {
+        self.excess_threshold
+    }
+
+    /// Get the decay factor of the weights
+    pub fn decay(&self) -> Option<f64> {
+        self.decay
+    }
+
+    /// Get the decayed number of observations
+    pub fn n(&self) -> f64 {
+        self.n
+    }
+
+    /// Get the decayed number of excesses
+    pub fn nt(&self) -> f64 {
+        self.nt
+    }
+
+    /// Get the current tail parameters
+    pub fn tail_parameters(&self) -> (f64, f64) {
+        (self.tail.gamma(), self.tail.sigma())
+    }
+
+    /// Advance the clock and the decayed counters by one observation
+    fn count(&mut self, is_excess: bool) {
+        let decay = self.decay.unwrap_or(1.0);
+        self.clock += 1.0;
+        self.n = decay * self.n + 1.0;
+        self.nt = decay * self.nt + if is_excess { 1.0 } else { 0.0 };
+    }
+
+    /// Push an excess observed at the current clock
+    fn record(&mut self, excess: f64) {
+        self.tail.push(excess);
+        self.stamps.push(self.clock);
+    }
+
+    /// Fit the tail, weighting each excess by `decay^age` when decaying
+    fn refit(&mut self) {
+        let Some(decay) = self.decay else {
+            self.tail.fit();
+            return;
+        };
+
+        let clock = self.clock;
+        let weights: Vec<f64> = self
+            .stamps
+            .raw_data()
+            .iter()
+            .map(|&stamp| num_traits::Float::powf(decay, clock - stamp))
+            .collect();
+        self.tail.fit_weighted(&weights);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spot::SpotDetector;
+
+    /// Deterministic exponential samples with the given scale
+    fn exponential_data(n: usize, scale: f64, seed: u64) -> Vec<f64> {
+        let mut state = seed;
+        (0..n)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                let u = ((state >> 11) as f64 + 0.5) / (1u64 << 53) as f64;
+                -scale * u.ln()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rolling_spot_invalid_decay() {
+        for decay in [0.0, -0.5, 1.5, f64::NAN] {
+            let config = RollingSpotConfig {
+                decay: Some(decay),
+                ..RollingSpotConfig::default()
+            };
+            assert_eq!(
+                RollingSpot::new(config).unwrap_err(),
+                SpotError::DecayOutOfBounds
+            );
+        }
+    }
+
+    #[test]
+    fn test_rolling_spot_without_decay_matches_spot() {
+        let config = SpotConfig {
+            level: 0.99,
+            ..SpotConfig::default()
+        };
+        let data = exponential_data(8_000, 1.0, 7);
+        let (train, stream) = data.split_at(3_000);
+
+        let mut spot = SpotDetector::new(config.clone()).unwrap();
+        let mut rolling = RollingSpot::new(RollingSpotConfig {
+            decay: None,
+            spot: config,
+        })
+        .unwrap();
+        spot.fit(train).unwrap();
+        rolling.fit(train).unwrap();
+        assert_eq!(rolling.anomaly_threshold(), spot.anomaly_threshold());
+
+        for &x in stream {
+            assert_eq!(rolling.step(x).unwrap(), spot.step(x).unwrap());
+            assert_eq!(rolling.anomaly_threshold(), spot.anomaly_threshold());
+            assert_eq!(rolling.excess_threshold(), spot.excess_threshold());
+        }
+    }
+
+    #[test]
+    fn test_rolling_spot_adapts_to_tail_change() {
+        let config = SpotConfig {
+            level: 0.95,
+            q: 1e-3,
+            discard_anomalies: false,
+            max_excess: 500,
+            ..SpotConfig::default()
+        };
+        let mut spot = SpotDetector::new(config.clone()).unwrap();
+        let mut rolling = RollingSpot::new(RollingSpotConfig {
+            decay: Some(0.998),
+            spot: config,
+        })
+        .unwrap();
+
+        let train = exponential_data(5_000, 1.0, 11);
+        spot.fit(&train).unwrap();
+        rolling.fit(&train).unwrap();
+
+        // The excesses over the threshold become three times larger
+        for x in exponential_data(2_000, 3.0, 13) {
+            spot.step(x).unwrap();
+            rolling.step(x).unwrap();
+        }
+
+        // True 1e-3 quantile of the new distribution
+        let target = -3.0 * 1e-3f64.ln();
+        let spot_gap = (spot.anomaly_threshold() - target).abs();
+        let rolling_gap = (rolling.anomaly_threshold() - target).abs();
+        assert!(
+            rolling_gap < spot_gap / 2.0,
+            "rolling {} vs spot {} (target {})",
+            rolling.anomaly_threshold(),
+            spot.anomaly_threshold(),
+            target
+        );
+    }
+}
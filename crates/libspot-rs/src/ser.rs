@@ -1,19 +1,27 @@
-//! Serde helper module for handling special float values (NaN, Infinity)
+//! Serde helper modules for special float values (NaN, Infinity) and the
+//! model schema version
 //!
 //! JSON doesn't natively support NaN or Infinity values, so we need custom
-//! serialization/deserialization logic to handle these cases.
+//! serialization/deserialization logic to handle these cases. Binary formats
+//! (e.g. bincode) store IEEE 754 values as is and only get the raw float.
 
-/// Custom serde module for f64 values that may be NaN or Infinity
+/// Custom serde module for float values that may be NaN or Infinity
 #[cfg(feature = "serde")]
-pub mod nan_safe_f64 {
+pub mod nan_safe_float {
+    use crate::float::Float;
+    use alloc::{format, string::String};
     use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
-    /// Serialize an f64, converting NaN and Infinity to special string representations
-    pub fn serialize<S>(value: &f64, serializer: S) -> Result<S::Ok, S::Error>
+    /// Serialize a float, converting NaN and Infinity to special string representations
+    /// in human-readable formats
+    pub fn serialize<F, S>(value: &F, serializer: S) -> Result<S::Ok, S::Error>
     where
+        F: Float,
         S: Serializer,
     {
-        if value.is_nan() {
+        if !serializer.is_human_readable() {
+            value.serialize(serializer)
+        } else if value.is_nan() {
             "NaN".serialize(serializer)
         } else if value.is_infinite() {
             if value.is_sign_positive() {
@@ -26,41 +34,103 @@ pub mod nan_safe_f64 {
         }
     }
 
-    /// Deserialize an f64, handling special string representations for NaN and Infinity
-    pub fn deserialize<'de, D>(deserializer: D) -> Result<f64, D::Error>
+    /// Deserialize a float, handling special string representations for NaN and Infinity
+    /// in human-readable formats
+    pub fn deserialize<'de, F, D>(deserializer: D) -> Result<F, D::Error>
     where
+        F: Float,
         D: Deserializer<'de>,
     {
+        // The untagged enum below needs `deserialize_any`, which binary formats
+        // do not support
+        if !deserializer.is_human_readable() {
+            return F::deserialize(deserializer);
+        }
+
         #[derive(Deserialize)]
         #[serde(untagged)]
-        enum FloatOrString {
-            Float(f64),
+        enum FloatOrString<F> {
+            Float(F),
             String(String),
         }
 
-        match FloatOrString::deserialize(deserializer)? {
+        match FloatOrString::<F>::deserialize(deserializer)? {
             FloatOrString::Float(f) => Ok(f),
             FloatOrString::String(s) => match s.as_str() {
-                "NaN" | "nan" => Ok(f64::NAN),
-                "Infinity" | "inf" | "+Infinity" | "+inf" => Ok(f64::INFINITY),
-                "-Infinity" | "-inf" => Ok(f64::NEG_INFINITY),
+                "NaN" | "nan" => Ok(F::nan()),
+                "Infinity" | "inf" | "+Infinity" | "+inf" => Ok(F::infinity()),
+                "-Infinity" | "-inf" => Ok(F::neg_infinity()),
                 _ => Err(de::Error::custom(format!("Invalid float string: {}", s))),
             },
         }
     }
 }
 
+/// Custom serde module for the model schema version of a serialized detector
+///
+/// Always writes the current [`model_schema_version`](crate::model_schema_version)
+/// and rejects newer versions with the message of [`SpotError::VersionMismatch`](crate::SpotError::VersionMismatch).
+#[cfg(feature = "serde")]
+pub mod schema_version {
+    use crate::error::SpotError;
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+    /// Serialize the current schema version, whatever the loaded one
+    pub fn serialize<S>(_version: &u32, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        crate::model_schema_version().serialize(serializer)
+    }
+
+    /// Deserialize a schema version, failing if it is newer than the current one
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<u32, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let found = u32::deserialize(deserializer)?;
+        let expected = crate::model_schema_version();
+        if found > expected {
+            return Err(de::Error::custom(SpotError::VersionMismatch {
+                found,
+                expected,
+            }));
+        }
+        Ok(found)
+    }
+}
+
 #[cfg(all(test, feature = "serde"))]
 mod tests {
-    use super::nan_safe_f64;
+    use super::nan_safe_float;
     use serde::{Deserialize, Serialize};
 
     #[derive(Debug, Serialize, Deserialize)]
     struct TestStruct {
-        #[serde(with = "nan_safe_f64")]
+        #[serde(with = "nan_safe_float")]
         value: f64,
     }
 
+    #[derive(Debug, Serialize, Deserialize)]
+    struct TestStruct32 {
+        #[serde(with = "nan_safe_float")]
+        value: f32,
+    }
+
+    #[test]
+    fn test_f32_serialization() {
+        let test = TestStruct32 { value: f32::NAN };
+        let json = serde_json::to_string(&test).unwrap();
+        assert_eq!(json, r#"{"value":"NaN"}"#);
+        let loaded: TestStruct32 = serde_json::from_str(&json).unwrap();
+        assert!(loaded.value.is_nan());
+
+        let test = TestStruct32 { value: 0.1 };
+        let json = serde_json::to_string(&test).unwrap();
+        let loaded: TestStruct32 = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.value, 0.1);
+    }
+
     #[test]
     fn test_nan_serialization() {
         let test = TestStruct { value: f64::NAN };
@@ -33,12 +33,27 @@
 //! let status = loaded.step(50.0);
 //! ```
 
-use crate::config::SpotConfig;
+use alloc::{borrow::Cow, boxed::Box, vec, vec::Vec};
+use core::cmp::Ordering;
+use core::fmt;
+
+use crate::config::{AdaptiveTail, SpotConfig};
 
 use crate::error::{SpotError, SpotResult};
-use crate::p2::p2_quantile;
+use crate::estimator::FitStrategy;
+use crate::float::Float;
+use crate::math::normal_quantile;
+use crate::p2::P2Estimator;
+use crate::peaks::Peaks;
+use crate::report::{
+    FitFailure, QuantileBreakdown, RuntimeStats, SpotHealth, SpotSummary, StepReport,
+    ValidationReport,
+};
 use crate::status::SpotStatus;
+use crate::stream::SpotStream;
 use crate::tail::Tail;
+use crate::ubend::{RingBuffer, Ubend};
+use crate::util::empirical_quantile;
 
 /// Main SPOT detector for streaming anomaly detection
 ///
@@ -50,6 +65,18 @@ use crate::tail::Tail;
 /// When the `serde` feature is enabled, the detector can be serialized and deserialized,
 /// allowing you to save trained models and restore them later without re-training.
 ///
+/// # Precision
+///
+/// The detector is generic over its [`Float`] type and defaults to `f64`.
+/// An `f32` detector is built with [`new_generic`](Self::new_generic) and halves
+/// the memory used by the tail buffer.
+///
+/// # Buffer
+///
+/// The excesses are kept in a [`Ubend`] by default. Any other [`RingBuffer`]
+/// can be used with [`new_with_buffer`](Self::new_with_buffer), e.g. an
+/// [`ArrayUbend`](crate::ArrayUbend) whose size `max_excess` must match.
+///
 /// # Example
 ///
 /// ```
@@ -69,9 +96,25 @@ use crate::tail::Tail;
 ///     SpotStatus::Anomaly => println!("Anomaly detected!"),
 /// }
 /// ```
-#[derive(Debug)]
+///
+/// # Cloning
+///
+/// A clone is a fully independent copy of the model, e.g. to checkpoint it
+/// without serialization. The callback registered with
+/// [`on_threshold_update`](Self::on_threshold_update) is not cloned.
+#[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct SpotDetector {
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "B: serde::Serialize",
+        deserialize = "B: serde::Deserialize<'de>"
+    ))
+)]
+pub struct SpotDetector<F: Float = f64, B: RingBuffer<F> = Ubend<F>> {
+    /// Model schema version the detector was created with (or deserialized from)
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::ser::schema_version"))]
+    schema_version: u32,
     /// Probability of an anomaly
     q: f64,
     /// Location of the tail (high quantile)
@@ -81,408 +124,4436 @@ pub struct SpotDetector {
     /// Upper/Lower tail choice (true = lower tail, false = upper tail)
     low: bool,
     /// Internal constant (+/- 1.0)
-    up_down: f64,
+    up_down: F,
     /// Normal/abnormal threshold
-    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_f64"))]
-    anomaly_threshold: f64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_float"))]
+    anomaly_threshold: F,
     /// Tail threshold
-    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_f64"))]
-    excess_threshold: f64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_float"))]
+    excess_threshold: F,
     /// Total number of excesses
     nt: usize,
     /// Total number of seen data
     n: usize,
     /// GPD Tail
-    tail: Tail,
+    tail: Tail<F, B>,
+    /// Re-estimate the excess threshold from recent data
+    #[cfg_attr(feature = "serde", serde(default))]
+    track_excess_threshold: bool,
+    /// P2 estimator of the excess threshold over the current tracking window
+    #[cfg_attr(feature = "serde", serde(default))]
+    excess_tracker: Option<P2Estimator<F>>,
+    /// Number of values per tracking window (the size of the training set)
+    #[cfg_attr(feature = "serde", serde(default))]
+    tracking_window: usize,
+    /// P2 estimator of the excess threshold over the training data, kept for
+    /// [`fit_update`](Self::fit_update)
+    #[cfg_attr(feature = "serde", serde(default))]
+    training_estimator: Option<P2Estimator<F>>,
+    /// Minimum number of peaks before `step` refits the GPD (0, the former
+    /// behavior, for models serialized before the option existed)
+    #[cfg_attr(feature = "serde", serde(default))]
+    min_peaks: usize,
+    /// Number of `step` calls after `fit` without anomaly flags
+    #[cfg_attr(feature = "serde", serde(default))]
+    warmup_steps: usize,
+    /// Number of `step` calls left in the current warmup
+    #[cfg_attr(feature = "serde", serde(default))]
+    warmup_remaining: usize,
+    /// Reject infinite values instead of keeping them out of the model
+    #[cfg_attr(
+        feature = "serde",
+        serde(default = "crate::config::default_reject_infinite")
+    )]
+    reject_infinite: bool,
+    /// Growth policy of the excess buffer
+    #[cfg_attr(feature = "serde", serde(default))]
+    adaptive_tail: Option<AdaptiveTail>,
+    /// Configured capacity of the excess buffer, which the [`AdaptiveTail`]
+    /// policy may have grown since (0 for models serialized before the field
+    /// existed, which report the current capacity)
+    #[cfg_attr(feature = "serde", serde(default))]
+    max_excess: usize,
+    /// Last `step` verdicts, to monitor the observed anomaly rate
+    #[cfg_attr(feature = "serde", serde(default))]
+    verdicts: Option<VerdictWindow>,
+    /// Number of NaN values skipped by `step_or_skip`
+    #[cfg_attr(feature = "serde", serde(default))]
+    skipped: usize,
+    /// Reusable copy of the finite training values (not serialized)
+    #[cfg_attr(feature = "serde", serde(skip))]
+    scratch: Vec<F>,
+    /// Step, excess and refit counters (the estimator calls are counted by
+    /// the tail, not serialized)
+    #[cfg_attr(feature = "serde", serde(skip))]
+    stats: RuntimeStats,
+    /// Callback fired when `step` moves the anomaly threshold (not serialized)
+    #[cfg_attr(feature = "serde", serde(skip))]
+    threshold_hook: ThresholdHook<F>,
+    /// Channel receiving a report of every step (not serialized)
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    step_channel: Option<std::sync::mpsc::Sender<StepReport<F>>>,
+}
+
+/// Callback receiving the previous and the new anomaly threshold
+type ThresholdCallback<F> = Box<dyn FnMut(F, F) + Send + Sync>;
+
+/// Optional [`ThresholdCallback`], with a `Debug` implementation
+struct ThresholdHook<F>(Option<ThresholdCallback<F>>);
+
+impl<F> Default for ThresholdHook<F> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+/// A callback cannot be cloned, so the clone has none
+impl<F> Clone for ThresholdHook<F> {
+    fn clone(&self) -> Self {
+        Self(None)
+    }
+}
+
+impl<F> fmt::Debug for ThresholdHook<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.0.is_some() { "Some(..)" } else { "None" })
+    }
 }
 
 impl SpotDetector {
-    /// Create a new SPOT detector with the given configuration
+    /// Create a new `f64` SPOT detector with the given configuration
     pub fn new(config: SpotConfig) -> SpotResult<Self> {
+        Self::new_generic(config)
+    }
+}
+
+impl<F: Float> SpotDetector<F> {
+    /// Create a new SPOT detector of any [`Float`] type with the given configuration
+    pub fn new_generic(config: SpotConfig) -> SpotResult<Self> {
+        Self::new_with_buffer(config)
+    }
+
+    /// Create a detector with the given configuration and fit it on `data`
+    ///
+    /// Fails with the errors of [`new_generic`](Self::new_generic) and
+    /// [`fit`](Self::fit).
+    ///
+    /// ```
+    /// use libspot_rs::{SpotConfig, SpotDetector};
+    ///
+    /// let data: Vec<f64> = (0..1000).map(|i| ((i * 37) % 1000) as f64 / 100.0).collect();
+    /// let spot = SpotDetector::fitted(SpotConfig::default(), &data).unwrap();
+    /// assert!(spot.anomaly_threshold() > spot.excess_threshold());
+    /// ```
+    pub fn fitted(config: SpotConfig, data: &[F]) -> SpotResult<Self> {
+        let mut spot = Self::new_generic(config)?;
+        spot.fit(data)?;
+        Ok(spot)
+    }
+}
+
+impl<F: Float, B: RingBuffer<F>> SpotDetector<F, B> {
+    /// Create a new SPOT detector storing its excesses in a buffer of type `B`
+    ///
+    /// Fails like [`new_generic`](SpotDetector::new_generic), or if `B` cannot
+    /// hold `max_excess` values. A buffer of fixed size is never grown by an
+    /// [`AdaptiveTail`] policy.
+    ///
+    /// ```
+    /// use libspot_rs::{ArrayUbend, SpotConfig, SpotDetector};
+    ///
+    /// let config = SpotConfig {
+    ///     max_excess: 64,
+    ///     ..SpotConfig::default()
+    /// };
+    /// let spot = SpotDetector::<f64, ArrayUbend<64>>::new_with_buffer(config).unwrap();
+    /// assert_eq!(spot.tail().peaks().capacity(), 64);
+    /// ```
+    pub fn new_with_buffer(config: SpotConfig) -> SpotResult<Self> {
         // Validate parameters
-        if config.level < 0.0 || config.level >= 1.0 {
-            return Err(SpotError::LevelOutOfBounds);
-        }
-        if config.q >= (1.0 - config.level) || config.q <= 0.0 {
-            return Err(SpotError::QOutOfBounds);
-        }
+        config.validate()?;
 
-        let up_down = if config.low_tail { -1.0 } else { 1.0 };
+        let up_down = if config.low_tail { -F::one() } else { F::one() };
+        let verdicts = match config.anomaly_rate_window {
+            0 => None,
+            window => Some(VerdictWindow::new(window)),
+        };
 
         Ok(Self {
+            schema_version: crate::model_schema_version(),
             q: config.q,
             level: config.level,
             discard_anomalies: config.discard_anomalies,
             low: config.low_tail,
             up_down,
-            anomaly_threshold: f64::NAN,
-            excess_threshold: f64::NAN,
+            anomaly_threshold: F::nan(),
+            excess_threshold: F::nan(),
             nt: 0,
             n: 0,
-            tail: Tail::new(config.max_excess)?,
+            tail: Tail::from_peaks(Peaks::from_buffer(B::with_capacity(config.max_excess)?))
+                .with_pwm(config.pwm_estimator)
+                .with_grimshaw_options(config.grimshaw_options)
+                .with_fit_strategy(config.fit_strategy)
+                .with_min_gamma(config.min_gamma)
+                .with_stable_small_gamma(config.stable_small_gamma),
+            track_excess_threshold: config.track_excess_threshold,
+            excess_tracker: None,
+            tracking_window: 0,
+            training_estimator: None,
+            min_peaks: config.min_peaks,
+            warmup_steps: config.warmup_steps,
+            warmup_remaining: 0,
+            reject_infinite: config.reject_infinite,
+            adaptive_tail: config.adaptive_tail,
+            max_excess: config.max_excess,
+            verdicts,
+            skipped: 0,
+            scratch: Vec::new(),
+            stats: RuntimeStats::default(),
+            threshold_hook: ThresholdHook::default(),
+            #[cfg(feature = "std")]
+            step_channel: None,
         })
     }
 
     /// Fit the model using initial training data
-    pub fn fit(&mut self, data: &[f64]) -> SpotResult<()> {
-        // Reset counters
-        self.nt = 0;
+    ///
+    /// A successful fit starts the warmup (see [`SpotConfig::warmup_steps`]).
+    ///
+    /// When no training value lies strictly beyond the estimated excess
+    /// threshold, e.g. because it falls on a value repeated in most of the
+    /// data, the threshold is moved to the next distinct value on the other
+    /// side, so that the repeated value forms the tail. Only data without any
+    /// such value (e.g. constant data) fails with an empty tail.
+    pub fn fit(&mut self, data: &[F]) -> SpotResult<()> {
+        self.fit_with_progress(data, |_, _| {})
+    }
+
+    /// Fit the model like [`fit`](Self::fit), reporting the progress to
+    /// `progress` for very large training sets
+    ///
+    /// The fit makes two passes over the (finite) training values: the P2
+    /// estimation of the excess threshold, then the collection of the excesses.
+    /// `progress(processed, total)` is called about 50 times per pass, with
+    /// `total` twice the number of values, the last call reporting
+    /// `(total, total)`.
+    ///
+    /// ```
+    /// use libspot_rs::{SpotConfig, SpotDetector};
+    ///
+    /// let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+    /// let data: Vec<f64> = (0..100_000).map(|i| ((i * 37) % 1000) as f64 / 100.0).collect();
+    /// let mut last = (0, 0);
+    /// spot.fit_with_progress(&data, |processed, total| last = (processed, total)).unwrap();
+    /// assert_eq!(last, (200_000, 200_000));
+    /// ```
+    pub fn fit_with_progress(
+        &mut self,
+        data: &[F],
+        mut progress: impl FnMut(usize, usize),
+    ) -> SpotResult<()> {
+        self.with_finite_data(data, |spot, data| {
+            let total = 2 * data.len();
+            let mut processed = 0;
+            let mut report = |count| {
+                processed += count;
+                progress(processed, total);
+            };
+
+            // Reset counters
+            spot.nt = 0;
+            spot.n = data.len();
+
+            let estimator = spot.training_threshold_estimator(data, &mut report)?;
+            spot.excess_threshold = spot.threshold_with_excesses(
+                data.iter().copied(),
+                excess_threshold_estimate(&estimator, spot.level, spot.low),
+            );
+            spot.training_estimator = Some(estimator);
+            spot.fit_tail(data, &mut report)
+        })
+    }
+
+    /// Reserve room for `training_hint` training values in the scratch
+    /// buffer of the fits
+    ///
+    /// [`fit`](Self::fit), [`fit_from_quantile`](Self::fit_from_quantile) and
+    /// [`fit_update`](Self::fit_update) only copy the training data when it
+    /// holds infinite values to skip, into a buffer kept by the detector and
+    /// reused by the next fits. With a hint at least as large as the training
+    /// sets, that copy never reallocates. The results are the same with or
+    /// without the hint.
+    ///
+    /// ```
+    /// use libspot_rs::{SpotConfig, SpotDetector};
+    ///
+    /// let mut data: Vec<f64> = (0..1000).map(|i| ((i * 37) % 1000) as f64 / 100.0).collect();
+    /// data[10] = f64::INFINITY;
+    /// let config = SpotConfig { reject_infinite: false, ..SpotConfig::default() };
+    /// let mut spot = SpotDetector::new(config)
+    ///     .unwrap()
+    ///     .with_capacity_hint(data.len());
+    /// spot.fit(&data).unwrap();
+    /// assert_eq!(spot.n(), 999);
+    /// ```
+    pub fn with_capacity_hint(mut self, training_hint: usize) -> Self {
+        self.scratch.reserve(training_hint);
+        self
+    }
+
+    /// Run `fit` on the finite values of `data`
+    ///
+    /// The infinite values are skipped (or rejected, with
+    /// [`SpotError::DataIsInfinite`]) like with [`finite_training_data`], the
+    /// others being copied into the reusable scratch buffer.
+    fn with_finite_data<R>(
+        &mut self,
+        data: &[F],
+        fit: impl FnOnce(&mut Self, &[F]) -> SpotResult<R>,
+    ) -> SpotResult<R> {
+        if !data.iter().any(|value| value.is_infinite()) {
+            return fit(self, data);
+        }
+        if self.reject_infinite {
+            return Err(SpotError::DataIsInfinite);
+        }
+
+        let mut scratch = core::mem::take(&mut self.scratch);
+        scratch.clear();
+        scratch.extend(data.iter().copied().filter(|value| !value.is_infinite()));
+        let result = fit(self, &scratch);
+        scratch.clear();
+        self.scratch = scratch;
+        result
+    }
+
+    /// Fit the model like [`fit`](Self::fit), with each training value weighted
+    /// by the matching entry of `weights`
+    ///
+    /// This suits aggregated or importance-sampled data, where a value of
+    /// weight 2 stands for two observations. The weights are normalized to sum
+    /// to the number of values, which is still `n`, and `nt` is the normalized
+    /// total weight of the excesses, rounded. The GPD is fitted with the
+    /// weighted MoM and Grimshaw estimators (PWM has no weighted variant). The
+    /// excess threshold is the P2 estimate over the values alone (moved like in
+    /// `fit` when no value exceeds it), and later refits in [`step`](Self::step) weigh all the excesses equally. Unlike
+    /// `fit`, the tail is emptied first since the weights of the excesses it
+    /// already holds are unknown.
+    ///
+    /// Fails with [`SpotError::InvalidWeights`] unless `weights` has the length
+    /// of `data` and holds finite non-negative values that are not all zero,
+    /// and otherwise with the errors of `fit`. Infinite values are skipped with
+    /// their weight when [`SpotConfig::reject_infinite`] is disabled.
+    ///
+    /// ```
+    /// use libspot_rs::{SpotConfig, SpotDetector};
+    ///
+    /// let data: Vec<f64> = (0..1000).map(|i| ((i * 37) % 1000) as f64 / 100.0).collect();
+    /// let weights = vec![1.0; data.len()];
+    /// let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+    /// spot.fit_weighted(&data, &weights).unwrap();
+    /// assert!(spot.anomaly_threshold() > spot.excess_threshold());
+    /// ```
+    pub fn fit_weighted(&mut self, data: &[F], weights: &[F]) -> SpotResult<()> {
+        if weights.len() != data.len()
+            || weights.iter().any(|&w| !(w.is_finite() && w >= F::zero()))
+        {
+            return Err(SpotError::InvalidWeights);
+        }
+        if self.reject_infinite && data.iter().any(|x| x.is_infinite()) {
+            return Err(SpotError::DataIsInfinite);
+        }
+        let (data, weights): (Vec<F>, Vec<F>) = data
+            .iter()
+            .zip(weights)
+            .filter(|(x, _)| !x.is_infinite())
+            .map(|(&x, &w)| (x, w))
+            .unzip();
+        let total = weights.iter().copied().sum::<F>();
+        if total <= F::zero() {
+            return Err(SpotError::InvalidWeights);
+        }
+
+        let estimator = self.training_threshold_estimator(&data, &mut |_| {})?;
+        let et = self.threshold_with_excesses(
+            data.iter().copied(),
+            excess_threshold_estimate(&estimator, self.level, self.low),
+        );
         self.n = data.len();
+        self.excess_threshold = et;
+        self.training_estimator = Some(estimator);
 
-        // Compute excess threshold using P2 quantile estimator
-        let et = if self.low {
-            // Take the low quantile (1 - level)
-            p2_quantile(1.0 - self.level, data)
-        } else {
-            p2_quantile(self.level, data)
-        };
+        // Weights of the excesses, aligned with the raw order of the tail buffer
+        self.tail.reset();
+        let mut excess_weights = Ubend::new_generic(self.tail.peaks().capacity())?;
+        let mut excess_total = F::zero();
+        for (&value, &weight) in data.iter().zip(&weights) {
+            let excess = self.up_down * (value - et);
+            if excess > F::zero() {
+                self.tail.push(excess);
+                excess_weights.push(weight);
+                excess_total += weight;
+            }
+        }
+        let nt = (excess_total * F::from_usize(self.n) / total).round();
+        self.nt = (nt.as_f64() as usize).min(self.n);
 
-        if et.is_nan() {
+        self.tail.fit_weighted(excess_weights.raw_data());
+        self.finish_fit()
+    }
+
+    /// Run the P2 estimator of the excess threshold over the training data, like
+    /// the C library, calling `progress` with the size of each processed chunk
+    fn training_threshold_estimator(
+        &self,
+        data: &[F],
+        progress: &mut impl FnMut(usize),
+    ) -> SpotResult<P2Estimator<F>> {
+        let mut estimator = P2Estimator::new_reference(self.tail_probability());
+        for chunk in data.chunks(progress_chunk_size(data.len())) {
+            for &value in chunk {
+                estimator.push(value);
+            }
+            progress(chunk.len());
+        }
+        if estimator.quantile_or_zero().is_nan() {
+            return Err(SpotError::ExcessThresholdIsNaN);
+        }
+        Ok(estimator)
+    }
+
+    /// `excess_threshold` if some value of `data` lies strictly beyond it,
+    /// otherwise the closest value of `data` on the other side (if any)
+    fn threshold_with_excesses(&self, data: impl IntoIterator<Item = F>, excess_threshold: F) -> F {
+        let mut nearest: Option<F> = None;
+        for value in data {
+            let excess = self.up_down * (value - excess_threshold);
+            if excess > F::zero() {
+                return excess_threshold;
+            }
+            if excess < F::zero()
+                && nearest.is_none_or(|nearest| self.up_down * (value - nearest) > F::zero())
+            {
+                nearest = Some(value);
+            }
+        }
+        nearest.unwrap_or(excess_threshold)
+    }
+
+    /// Fit the model using initial training data and a known excess threshold
+    ///
+    /// Same as [`fit`](Self::fit), except that `excess_threshold` replaces the
+    /// P2 estimate of the `level` quantile of `data`, e.g. when it is known from
+    /// domain knowledge or an exact quantile. Like the estimate, it is moved to
+    /// the next distinct value when no value of `data` exceeds it. Since no P2 state
+    /// is kept, a later [`fit_update`](Self::fit_update) starts from scratch.
+    ///
+    /// Fails with [`SpotError::ExcessThresholdIsNaN`] if `excess_threshold` is
+    /// not finite, leaving the detector unchanged.
+    pub fn fit_from_quantile(&mut self, data: &[F], excess_threshold: F) -> SpotResult<()> {
+        if !excess_threshold.is_finite() {
+            return Err(SpotError::ExcessThresholdIsNaN);
+        }
+        self.with_finite_data(data, |spot, data| {
+            spot.nt = 0;
+            spot.n = data.len();
+            spot.excess_threshold =
+                spot.threshold_with_excesses(data.iter().copied(), excess_threshold);
+            spot.training_estimator = None;
+            spot.fit_tail(data, &mut |_| {})
+        })
+    }
+
+    /// Fit the model like [`fit`](Self::fit) in a single pass over `iter`,
+    /// without collecting the training data
+    ///
+    /// The P2 estimator of the excess threshold is fed one value at a time,
+    /// while only the `2 * hint * (1 - level) + min_peaks` most extreme values
+    /// are kept as candidate excesses, `hint` being the expected number of
+    /// values. The result is identical to `fit` on the same values (NaN values
+    /// included, which count in `n` like in `fit`) as long as the final excess
+    /// threshold is exceeded by fewer values than that, which holds unless
+    /// `hint` underestimates the training size by about half or more. Beyond
+    /// that, the smallest excesses are missed and `nt` underestimates the tail
+    /// mass, which lowers the anomaly threshold. The fallback of `fit` to the
+    /// next distinct value gives identical results whatever the hint, since all
+    /// the excesses are then copies of the most extreme value.
+    pub fn fit_streaming<I: Iterator<Item = F>>(&mut self, iter: I, hint: usize) -> SpotResult<()> {
+        let expected = num_traits::Float::ceil(hint as f64 * (1.0 - self.level)) as usize;
+        let keep = 2 * expected + self.min_peaks.max(1);
+
+        let mut estimator = P2Estimator::new_reference(self.tail_probability());
+        let mut candidates: Vec<(usize, F)> = Vec::new();
+        // Two most extreme distinct values, with the copies of the first one
+        let mut extremes = ExtremeValues::default();
+        let mut n = 0;
+        for value in iter {
+            if value.is_infinite() {
+                if self.reject_infinite {
+                    return Err(SpotError::DataIsInfinite);
+                }
+                continue;
+            }
+            estimator.push(value);
+            if !value.is_nan() {
+                candidates.push((n, value));
+                extremes.push(value, self.up_down);
+            }
+            n += 1;
+            if candidates.len() >= 2 * keep {
+                retain_most_extreme(&mut candidates, keep, self.up_down);
+            }
+        }
+        let estimate = excess_threshold_estimate(&estimator, self.level, self.low);
+
+        self.nt = 0;
+        self.n = n;
+        if estimate.is_nan() {
             return Err(SpotError::ExcessThresholdIsNaN);
         }
 
+        // The fallback of `fit` moves the threshold to the closest value below
+        // it, which only the two most extreme distinct values can be
+        let et = self
+            .threshold_with_excesses(extremes.top.into_iter().chain(extremes.runner_up), estimate);
+        if et != estimate {
+            // Only the copies of the most extreme value can exceed the moved
+            // threshold, possibly more of them than the kept candidates
+            let excess = extremes
+                .top
+                .map_or(F::zero(), |top| self.up_down * (top - et));
+            self.excess_threshold = et;
+            self.training_estimator = Some(estimator);
+            if excess > F::zero() {
+                for _ in 0..extremes.top_count {
+                    self.nt += 1;
+                    self.tail.push(excess);
+                }
+            }
+            self.tail.fit();
+            return self.finish_fit();
+        }
+
+        // Push the excesses in their stream order, like `fit`
+        candidates.sort_unstable_by_key(|&(index, _)| index);
+        let values: Vec<F> = candidates.into_iter().map(|(_, value)| value).collect();
+
         self.excess_threshold = et;
+        self.training_estimator = Some(estimator);
+        self.fit_tail(&values, &mut |_| {})
+    }
+
+    /// Fill the tail with the excesses of `data` over the excess threshold, fit
+    /// it and compute the anomaly threshold, calling `progress` with the size
+    /// of each processed chunk
+    fn fit_tail(&mut self, data: &[F], progress: &mut impl FnMut(usize)) -> SpotResult<()> {
+        let et = self.excess_threshold;
 
         // Fill the tail with excesses
+        for chunk in data.chunks(progress_chunk_size(data.len())) {
+            for &value in chunk {
+                // Positive excess
+                let excess = self.up_down * (value - et);
+                if excess > F::zero() {
+                    // It's a real excess
+                    self.nt += 1;
+                    self.tail.push(excess);
+                }
+            }
+            progress(chunk.len());
+        }
+
+        // Fit the tail with the pushed data
+        self.tail.fit();
+        self.finish_fit()
+    }
+
+    /// Compute the anomaly threshold of a freshly fitted tail and start the
+    /// excess threshold tracking and the warmup
+    fn finish_fit(&mut self) -> SpotResult<()> {
+        // Compute first anomaly threshold
+        self.anomaly_threshold = self.quantile(F::of(self.q));
+        if self.anomaly_threshold.is_nan() {
+            return Err(self.degenerate_fit_error());
+        }
+
+        if self.track_excess_threshold {
+            self.tracking_window = self.n;
+            self.excess_tracker = Some(P2Estimator::new_generic(self.tail_probability()));
+        }
+        self.warmup_remaining = self.warmup_steps;
+        self.clear_verdicts();
+
+        Ok(())
+    }
+
+    /// Fit the model like [`fit`](Self::fit), reporting on failure how far the
+    /// fit went
+    ///
+    /// The [`FitFailure`] carries the excess threshold computed from `data` and
+    /// the number of excesses found beyond it, e.g. to tell that no value was
+    /// strictly above the threshold because the data is constant.
+    ///
+    /// ```
+    /// use libspot_rs::{SpotConfig, SpotDetector};
+    ///
+    /// let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+    /// let failure = spot.try_fit(&[1.0; 1000]).unwrap_err();
+    /// assert_eq!((failure.excess_threshold, failure.nt), (1.0, 0));
+    /// ```
+    pub fn try_fit(&mut self, data: &[F]) -> Result<(), FitFailure<F>> {
+        self.fit(data).map_err(|error| FitFailure {
+            // `fit` stops before storing a NaN excess threshold
+            excess_threshold: if error == SpotError::ExcessThresholdIsNaN {
+                F::nan()
+            } else {
+                self.excess_threshold
+            },
+            nt: self.nt,
+            error,
+        })
+    }
+
+    /// Extend the fitted model with more training data
+    ///
+    /// The new values are treated as more of the same training batch: the P2
+    /// estimator kept from [`fit`](Self::fit) re-estimates the excess threshold,
+    /// the stored peaks are shifted to it (dropping those that fall below), the
+    /// excesses of `data` are added and the GPD is refitted. When neither `data`
+    /// nor the stored peaks exceed the new threshold, it is moved like in `fit`
+    /// to the closest of these values on the other side. The result is close
+    /// to `fit` on the concatenated data but not identical, since the earlier
+    /// excesses no longer held in the tail cannot be requalified.
+    ///
+    /// Equivalent to [`fit`](Self::fit) if the detector has not been fitted
+    /// (or was deserialized without the training estimator).
+    pub fn fit_update(&mut self, data: &[F]) -> SpotResult<()> {
+        self.with_finite_data(data, Self::update_with_finite_data)
+    }
+
+    /// Body of [`fit_update`](Self::fit_update), on finite values
+    fn update_with_finite_data(&mut self, data: &[F]) -> SpotResult<()> {
+        let Some(estimator) = self.training_estimator.as_mut() else {
+            return self.fit(data);
+        };
+
+        for &value in data {
+            estimator.push(value);
+        }
+        let et = excess_threshold_estimate(estimator, self.level, self.low);
+        let training_size = estimator.count();
+
+        if et.is_nan() {
+            return Err(SpotError::ExcessThresholdIsNaN);
+        }
+        let stored = self
+            .tail
+            .peaks()
+            .into_iter()
+            .map(|excess| self.excess_threshold + self.up_down * excess);
+        let et = self.threshold_with_excesses(data.iter().copied().chain(stored), et);
+
+        self.n += data.len();
+        self.shift_excesses(et);
         for &value in data {
-            // Positive excess
             let excess = self.up_down * (value - et);
-            if excess > 0.0 {
-                // It's a real excess
+            if excess > F::zero() {
                 self.nt += 1;
                 self.tail.push(excess);
             }
         }
 
-        // Fit the tail with the pushed data
         self.tail.fit();
+        self.anomaly_threshold = self.quantile(F::of(self.q));
+        if self.anomaly_threshold.is_nan() {
+            return Err(self.degenerate_fit_error());
+        }
 
-        // Compute first anomaly threshold
-        self.anomaly_threshold = self.quantile(self.q);
+        if self.track_excess_threshold {
+            self.tracking_window = training_size;
+        }
+
+        Ok(())
+    }
+
+    /// Combine a detector fitted on another shard of the data into this one
+    ///
+    /// The counts of observed values are summed and the excess threshold is the
+    /// one furthest in the tail of the two (the conservative choice). The peaks
+    /// of both detectors are requalified against it (shifted by the difference
+    /// of thresholds, dropping those that fall below), keeping the `max_excess`
+    /// largest ones, and the GPD and the anomaly threshold are refitted.
+    ///
+    /// The P2 state used by [`fit_update`](Self::fit_update) cannot be merged,
+    /// so a later `fit_update` restarts from scratch. The options that may
+    /// differ between [compatible](SpotConfig::compatible_with) configurations
+    /// are those of `self`. Fails with [`SpotError::ConfigMismatch`] if the
+    /// configurations are not compatible and with
+    /// [`SpotError::ExcessThresholdIsNaN`] if either detector is not fitted.
+    pub fn merge(&mut self, other: &SpotDetector<F, B>) -> SpotResult<()> {
+        if !other
+            .config()
+            .is_some_and(|config| self.is_compatible_with(&config))
+        {
+            return Err(SpotError::ConfigMismatch);
+        }
+        if self.excess_threshold.is_nan() || other.excess_threshold.is_nan() {
+            return Err(SpotError::ExcessThresholdIsNaN);
+        }
+
+        let et = if self.up_down * (other.excess_threshold - self.excess_threshold) > F::zero() {
+            other.excess_threshold
+        } else {
+            self.excess_threshold
+        };
+
+        let mut peaks = Vec::with_capacity(self.tail.size() + other.tail.size());
+        let mut nt = 0;
+        for spot in [&*self, other] {
+            let shift = self.up_down * (et - spot.excess_threshold);
+            let before = peaks.len();
+            peaks.extend(
+                spot.tail
+                    .peaks()
+                    .container()
+                    .iter()
+                    .map(|excess| excess - shift)
+                    .filter(|&excess| excess > F::zero()),
+            );
+            nt += spot
+                .nt
+                .saturating_sub(spot.tail.size() - (peaks.len() - before));
+        }
+        peaks.sort_by(|a, b| b.partial_cmp(a).unwrap_or(core::cmp::Ordering::Equal));
+        peaks.truncate(self.tail.peaks().capacity());
+
+        self.n += other.n;
+        self.nt = nt;
+        self.excess_threshold = et;
+        self.tail.reset();
+        for excess in peaks {
+            self.tail.push(excess);
+        }
+        self.training_estimator = None;
+        self.tracking_window += other.tracking_window;
+
+        self.tail.fit();
+        self.anomaly_threshold = self.quantile(F::of(self.q));
         if self.anomaly_threshold.is_nan() {
-            return Err(SpotError::AnomalyThresholdIsNaN);
+            return Err(self.degenerate_fit_error());
         }
 
         Ok(())
     }
 
+    /// Drop the excesses smaller than `v` from the tail and refit it
+    ///
+    /// `v` is an excess (a distance beyond the excess threshold), not a raw
+    /// value. This prunes stale small excesses after a regime change without a
+    /// full [`reset`](Self::reset); the excess threshold and the counters are
+    /// kept. As in [`step`](Self::step), the GPD is only refitted and the
+    /// anomaly threshold updated if the tail is still
+    /// [ready](Self::is_tail_ready). Returns the number of removed excesses.
+    pub fn prune_excesses_below(&mut self, v: F) -> usize {
+        let removed = self.tail.retain(|excess| excess >= v);
+        if removed > 0 && self.is_tail_ready() {
+            self.tail.fit();
+            self.anomaly_threshold = self.quantile(F::of(self.q));
+        }
+        removed
+    }
+
+    /// Error for a NaN anomaly threshold after fitting, blaming a small tail if any
+    fn degenerate_fit_error(&self) -> SpotError {
+        if self.is_tail_ready() {
+            SpotError::AnomalyThresholdIsNaN
+        } else {
+            SpotError::TooFewExcesses {
+                have: self.tail.size(),
+                need: self.min_peaks,
+            }
+        }
+    }
+
+    /// Probability of the quantile delimiting the observed tail
+    /// (`level` for the upper tail, `1 - level` for the lower tail)
+    fn tail_probability(&self) -> F {
+        if self.low {
+            // Take the low quantile (1 - level)
+            F::of(1.0 - self.level)
+        } else {
+            F::of(self.level)
+        }
+    }
+
+    /// Feed the tracking estimator and move the excess threshold when a window completes
+    fn track_excess_threshold(&mut self, value: F) {
+        let Some(tracker) = self.excess_tracker.as_mut() else {
+            return;
+        };
+        tracker.push(value);
+        if tracker.count() < self.tracking_window {
+            return;
+        }
+
+        // The P2 estimate is NaN below 5 values, keep the threshold then
+        let threshold = if self.level == 0.0 {
+            normal_extreme(tracker, self.low)
+        } else {
+            tracker.quantile()
+        };
+        *tracker = P2Estimator::new_generic(tracker.probability());
+        if threshold.is_finite() {
+            self.relocate_excess_threshold(threshold);
+        }
+    }
+
+    /// Move the excess threshold to follow a drift in the location of the data
+    ///
+    /// The buffered excesses are kept as they are: under a location drift, an
+    /// excess over the old threshold is an excess of the same size over the
+    /// new one. The GPD and the counters `n` and `nt` are thus unchanged, and
+    /// the anomaly threshold moves along with the excess threshold.
+    fn relocate_excess_threshold(&mut self, threshold: F) {
+        self.excess_threshold = threshold;
+        self.anomaly_threshold = self.quantile(F::of(self.q));
+    }
+
+    /// Move the excess threshold and shift the buffered peaks, without refitting
+    fn shift_excesses(&mut self, threshold: F) {
+        let shift = self.up_down * (threshold - self.excess_threshold);
+        let kept: Vec<F> = self
+            .tail
+            .peaks()
+            .container()
+            .iter()
+            .map(|excess| excess - shift)
+            .filter(|&excess| excess > F::zero())
+            .collect();
+
+        self.nt = self.nt.saturating_sub(self.tail.size() - kept.len());
+        self.excess_threshold = threshold;
+        self.tail.reset();
+        for excess in kept {
+            self.tail.push(excess);
+        }
+    }
+
+    /// Fit the model on the leading part of `data` and validate it on the rest
+    ///
+    /// The first `(1 - val_fraction)` of the data is passed to [`fit`](Self::fit),
+    /// then the held-out tail is processed with [`step`](Self::step) while the
+    /// realized excess and anomaly rates are recorded. Note that the held-out
+    /// points do update the model, exactly as they would in production.
+    pub fn fit_with_validation(
+        &mut self,
+        data: &[F],
+        val_fraction: f64,
+    ) -> SpotResult<ValidationReport> {
+        if !(val_fraction > 0.0 && val_fraction < 1.0) {
+            return Err(SpotError::ValidationFractionOutOfBounds);
+        }
+
+        let split = ((data.len() as f64) * (1.0 - val_fraction)) as usize;
+        let (training, validation) = data.split_at(split);
+        self.fit(training)?;
+
+        let mut excesses = 0;
+        let mut anomalies = 0;
+        for &value in validation {
+            match self.step(value)? {
+                SpotStatus::Normal => {}
+                SpotStatus::Excess => excesses += 1,
+                SpotStatus::Anomaly => anomalies += 1,
+            }
+        }
+
+        let validation_size = validation.len();
+        let (excess_rate, anomaly_rate) = if validation_size == 0 {
+            (f64::NAN, f64::NAN)
+        } else {
+            let size = validation_size as f64;
+            (
+                (excesses + anomalies) as f64 / size,
+                anomalies as f64 / size,
+            )
+        };
+
+        Ok(ValidationReport {
+            training_size: training.len(),
+            validation_size,
+            excesses,
+            anomalies,
+            excess_rate,
+            anomaly_rate,
+            expected_excess_rate: 1.0 - self.level,
+        })
+    }
+
     /// Process a single data point and return its classification
-    pub fn step(&mut self, value: f64) -> SpotResult<SpotStatus> {
+    pub fn step(&mut self, value: F) -> SpotResult<SpotStatus> {
+        self.stats.steps += 1;
+        let status = self.classify_and_update(value)?;
+        self.record_verdict(status);
+        #[cfg(feature = "std")]
+        self.send_step_report(value, status);
+        Ok(status)
+    }
+
+    /// Body of [`step`](Self::step), without the anomaly rate monitoring
+    fn classify_and_update(&mut self, value: F) -> SpotResult<SpotStatus> {
         if value.is_nan() {
             return Err(SpotError::DataIsNaN);
         }
+        if value.is_infinite() {
+            return self.classify_infinite(value);
+        }
+        Ok(self.classify_finite(value))
+    }
 
-        if self.discard_anomalies && (self.up_down * (value - self.anomaly_threshold) > 0.0) {
-            return Ok(SpotStatus::Anomaly);
+    /// Process a finite data point and return its classification
+    ///
+    /// Same as [`step`](Self::step) without the `Result`, for hot loops whose
+    /// input is validated upstream.
+    ///
+    /// # Contract
+    ///
+    /// `value` must be finite. This is checked by a debug assertion only: in
+    /// release builds a NaN or infinite value is fed to the model as is and
+    /// corrupts its counters or its tail. This is not undefined behavior, but
+    /// the detector must then be refitted.
+    ///
+    /// ```
+    /// use libspot_rs::{SpotConfig, SpotDetector, SpotStatus};
+    ///
+    /// let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+    /// let data: Vec<f64> = (0..1000).map(|i| ((i * 37) % 1000) as f64 / 100.0).collect();
+    /// spot.fit(&data).unwrap();
+    /// assert_eq!(spot.step_unchecked(50.0), SpotStatus::Anomaly);
+    /// ```
+    pub fn step_unchecked(&mut self, value: F) -> SpotStatus {
+        debug_assert!(value.is_finite(), "step_unchecked on a non-finite value");
+        self.stats.steps += 1;
+        let status = self.classify_finite(value);
+        self.record_verdict(status);
+        #[cfg(feature = "std")]
+        self.send_step_report(value, status);
+        status
+    }
+
+    /// Classify a finite value and update the model
+    fn classify_finite(&mut self, value: F) -> SpotStatus {
+        self.track_excess_threshold(value);
+
+        // During the warmup, values beyond the anomaly threshold are excesses
+        let warming_up = self.in_warmup();
+        if warming_up {
+            self.warmup_remaining -= 1;
+        }
+        if !warming_up
+            && self.discard_anomalies
+            && (self.up_down * (value - self.anomaly_threshold) > F::zero())
+        {
+            return SpotStatus::Anomaly;
         }
 
         // Increment number of data (without the anomalies)
         self.n += 1;
 
         let ex = self.up_down * (value - self.excess_threshold);
-        if ex >= 0.0 {
+        if ex >= F::zero() {
             // Increment number of excesses
             self.nt += 1;
+            self.stats.excesses += 1;
+            self.grow_tail();
             self.tail.push(ex);
-            self.tail.fit();
-            // Update threshold
-            self.anomaly_threshold = self.quantile(self.q);
-            return Ok(SpotStatus::Excess);
+            // Keep the previous threshold until the tail can be fitted reliably
+            if self.is_tail_ready() {
+                self.tail.fit();
+                self.stats.refits += 1;
+                // Update threshold
+                let previous = self.anomaly_threshold;
+                self.anomaly_threshold = self.quantile(F::of(self.q));
+                self.notify_threshold_update(previous);
+            }
+            return SpotStatus::Excess;
         }
 
-        Ok(SpotStatus::Normal)
+        SpotStatus::Normal
     }
 
-    /// Get the quantile for a given probability
-    pub fn quantile(&self, q: f64) -> f64 {
-        if self.n == 0 {
-            return f64::NAN;
+    /// Keep the verdict of a step for [`observed_anomaly_rate`](Self::observed_anomaly_rate)
+    fn record_verdict(&mut self, status: SpotStatus) {
+        if let Some(verdicts) = self.verdicts.as_mut() {
+            verdicts.push(status == SpotStatus::Anomaly);
         }
+    }
 
-        let s = (self.nt as f64) / (self.n as f64);
-        self.excess_threshold + self.up_down * self.tail.quantile(s, q)
+    /// Forget the recorded verdicts
+    fn clear_verdicts(&mut self) {
+        if let Some(verdicts) = self.verdicts.as_mut() {
+            verdicts.clear();
+        }
     }
 
-    /// Get the probability for a given value
-    pub fn probability(&self, z: f64) -> f64 {
-        if self.n == 0 {
-            return f64::NAN;
+    /// Get the fraction of anomalies among the last
+    /// [`anomaly_rate_window`](SpotConfig::anomaly_rate_window) verdicts of
+    /// [`step`](Self::step) since the last fit
+    ///
+    /// With refits disabled (e.g. a `min_peaks` the tail never reaches), this
+    /// stays close to `q` on data following the training distribution. With
+    /// the default `discard_anomalies`, the refits only see the excesses below
+    /// the anomaly threshold, which shrinks it: the rate then slowly rises above
+    /// `q`, even without drift. Returns NaN if the window is disabled or no value
+    /// was stepped yet.
+    pub fn observed_anomaly_rate(&self) -> f64 {
+        self.verdicts.as_ref().map_or(f64::NAN, VerdictWindow::rate)
+    }
+
+    /// Get the gap between the [observed anomaly rate](Self::observed_anomaly_rate)
+    /// and the configured `q`
+    ///
+    /// Positive when the detector flags more anomalies than expected (e.g. after
+    /// a drift), negative when it flags fewer. NaN when the observed rate is.
+    pub fn calibration_error(&self) -> f64 {
+        self.observed_anomaly_rate() - self.q
+    }
+
+    /// Process a data point like [`step`](Self::step), skipping NaN values
+    ///
+    /// Returns `None` for NaN (e.g. a sensor dropout), leaving the model
+    /// untouched apart from [`skipped_count`](Self::skipped_count), and
+    /// `Some(step(value))` otherwise.
+    ///
+    /// ```
+    /// use libspot_rs::{SpotConfig, SpotDetector};
+    ///
+    /// let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+    /// let data: Vec<f64> = (0..1000).map(|i| ((i * 37) % 1000) as f64 / 100.0).collect();
+    /// spot.fit(&data).unwrap();
+    ///
+    /// assert_eq!(spot.step_or_skip(f64::NAN), None);
+    /// assert!(spot.step_or_skip(1.0).is_some());
+    /// assert_eq!((spot.n(), spot.skipped_count()), (1001, 1));
+    /// ```
+    pub fn step_or_skip(&mut self, value: F) -> Option<SpotResult<SpotStatus>> {
+        if value.is_nan() {
+            self.skipped += 1;
+            return None;
         }
+        Some(self.step(value))
+    }
+
+    /// Get the number of NaN values skipped by [`step_or_skip`](Self::step_or_skip)
+    /// since the creation or the last [`reset`](Self::reset)
+    pub fn skipped_count(&self) -> usize {
+        self.skipped
+    }
+
+    /// Classify a value against the current thresholds without updating the model
+    ///
+    /// Returns what [`step`](Self::step) would return for `value` right now
+    /// (including during the warmup, where no anomaly is flagged), with the same
+    /// errors, but leaves the counters, the tail and the thresholds untouched.
+    /// With [`track_excess_threshold`](SpotConfig::track_excess_threshold), the
+    /// excess threshold `step` would move before classifying is not anticipated.
+    ///
+    /// ```
+    /// use libspot_rs::{SpotConfig, SpotDetector, SpotStatus};
+    ///
+    /// let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+    /// let data: Vec<f64> = (0..1000).map(|i| ((i * 37) % 1000) as f64 / 100.0).collect();
+    /// spot.fit(&data).unwrap();
+    ///
+    /// assert_eq!(spot.predict(50.0), Ok(SpotStatus::Anomaly));
+    /// assert_eq!(spot.n(), 1000);
+    /// ```
+    pub fn predict(&self, value: F) -> SpotResult<SpotStatus> {
+        if value.is_nan() {
+            return Err(SpotError::DataIsNaN);
+        }
+        if value.is_infinite() {
+            return self.classify_infinite(value);
+        }
+
+        if !self.in_warmup()
+            && self.discard_anomalies
+            && (self.up_down * (value - self.anomaly_threshold) > F::zero())
+        {
+            return Ok(SpotStatus::Anomaly);
+        }
+        if self.up_down * (value - self.excess_threshold) >= F::zero() {
+            return Ok(SpotStatus::Excess);
+        }
+        Ok(SpotStatus::Normal)
+    }
+
+    /// Classify an infinite value, which never updates the model
+    fn classify_infinite(&self, value: F) -> SpotResult<SpotStatus> {
+        if self.reject_infinite {
+            return Err(SpotError::DataIsInfinite);
+        }
+        // Beyond any threshold, but kept out of the model
+        Ok(if self.up_down * value > F::zero() {
+            SpotStatus::Anomaly
+        } else {
+            SpotStatus::Normal
+        })
+    }
+
+    /// Register a callback fired with `(old_z, new_z)` each time
+    /// [`step`](Self::step), [`set_q`](Self::set_q) or
+    /// [`set_excess_threshold`](Self::set_excess_threshold) changes the anomaly
+    /// threshold
+    ///
+    /// For `step`, this happens when an excess refits the tail. Other updates
+    /// of the threshold (fits, merges, ...) do not fire it. The callback
+    /// replaces any previous one and is not serialized, so a deserialized
+    /// detector has none.
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use libspot_rs::{SpotConfig, SpotDetector};
+    ///
+    /// let config = SpotConfig { level: 0.98, ..SpotConfig::default() };
+    /// let mut spot = SpotDetector::new(config).unwrap();
+    /// let data: Vec<f64> = (0..1000).map(|i| ((i * 37) % 1000) as f64 / 100.0).collect();
+    /// spot.fit(&data).unwrap();
+    ///
+    /// let updates = Arc::new(Mutex::new(Vec::new()));
+    /// let sink = Arc::clone(&updates);
+    /// spot.on_threshold_update(Box::new(move |old, new| sink.lock().unwrap().push((old, new))));
+    /// spot.step(9.995).unwrap();
+    /// assert_eq!(updates.lock().unwrap().len(), 1);
+    /// ```
+    pub fn on_threshold_update(&mut self, cb: Box<dyn FnMut(F, F) + Send + Sync>) {
+        self.threshold_hook = ThresholdHook(Some(cb));
+    }
+
+    /// Send a [`StepReport`] of every successful [`step`](Self::step) to `tx`
+    ///
+    /// The reports can then be processed by another thread (logging,
+    /// alerting) off the hot path. Sending is best effort: once the receiver is
+    /// dropped the reports are discarded. Only one channel is kept, replacing
+    /// any previous one. A clone of the detector sends to the same channel.
+    /// Requires the `std` feature.
+    ///
+    /// ```
+    /// use std::sync::mpsc;
+    /// use libspot_rs::{SpotConfig, SpotDetector, SpotStatus};
+    ///
+    /// let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+    /// let data: Vec<f64> = (0..1000).map(|i| ((i * 37) % 1000) as f64 / 100.0).collect();
+    /// spot.fit(&data).unwrap();
+    ///
+    /// let (tx, rx) = mpsc::channel();
+    /// spot.with_channel(tx);
+    /// spot.step(50.0).unwrap();
+    /// assert_eq!(rx.recv().unwrap().status, SpotStatus::Anomaly);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn with_channel(&mut self, tx: std::sync::mpsc::Sender<StepReport<F>>) {
+        self.step_channel = Some(tx);
+    }
+
+    /// Send the report of a step to the channel, if any
+    #[cfg(feature = "std")]
+    fn send_step_report(&self, value: F, status: SpotStatus) {
+        let Some(tx) = &self.step_channel else {
+            return;
+        };
+        // A disconnected receiver is not an error of the step
+        let _ = tx.send(StepReport {
+            status,
+            value,
+            anomaly_threshold: self.anomaly_threshold,
+            excess_threshold: self.excess_threshold,
+            probability: self.probability(value),
+        });
+    }
+
+    /// Fire the threshold callback if the anomaly threshold moved from `previous`
+    fn notify_threshold_update(&mut self, previous: F) {
+        let current = self.anomaly_threshold;
+        let unchanged = current == previous || (current.is_nan() && previous.is_nan());
+        if let (Some(cb), false) = (self.threshold_hook.0.as_mut(), unchanged) {
+            cb(previous, current);
+        }
+    }
+
+    /// Process a single data point and report its classification together with
+    /// the thresholds and the probability of the value right after the step
+    ///
+    /// Same as [`step`](Self::step) followed by [`anomaly_threshold`](Self::anomaly_threshold),
+    /// [`excess_threshold`](Self::excess_threshold) and [`probability`](Self::probability).
+    pub fn step_detailed(&mut self, value: F) -> SpotResult<StepReport<F>> {
+        let status = self.step(value)?;
+        Ok(StepReport {
+            status,
+            value,
+            anomaly_threshold: self.anomaly_threshold,
+            excess_threshold: self.excess_threshold,
+            probability: self.probability(value),
+        })
+    }
+
+    /// Process a slice of data points in order and return their classifications
+    ///
+    /// Stops at the first error (e.g. [`SpotError::DataIsNaN`]), in which case the
+    /// values before the failing one have already updated the model. Use
+    /// [`step_batch_into`](Self::step_batch_into) to recover the failing index.
+    pub fn step_batch(&mut self, xs: &[F]) -> SpotResult<Vec<SpotStatus>> {
+        let mut out = Vec::with_capacity(xs.len());
+        self.step_batch_into(xs, &mut out)?;
+        Ok(out)
+    }
+
+    /// Like [`step_batch`](Self::step_batch), writing the classifications into a
+    /// reusable buffer
+    ///
+    /// `out` is cleared first. On error it holds the classifications of the values
+    /// processed so far, so `out.len()` is the index of the failing value in `xs`.
+    pub fn step_batch_into(&mut self, xs: &[F], out: &mut Vec<SpotStatus>) -> SpotResult<()> {
+        out.clear();
+        out.reserve(xs.len());
+        for &x in xs {
+            out.push(self.step(x)?);
+        }
+        Ok(())
+    }
+
+    /// Lazily step the detector over `values`, yielding each value with its
+    /// classification
+    ///
+    /// The detector stays mutably borrowed while the returned [`SpotStream`] is
+    /// alive. Collect into a `SpotResult<Vec<_>>` to stop at the first error:
+    ///
+    /// ```
+    /// use libspot_rs::{SpotConfig, SpotDetector, SpotResult};
+    ///
+    /// let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+    /// let data: Vec<f64> = (0..1000).map(|i| (i as f64) / 100.0).collect();
+    /// spot.fit(&data).unwrap();
+    ///
+    /// let results = spot
+    ///     .stream(vec![1.0, 2.0, 50.0].into_iter())
+    ///     .collect::<SpotResult<Vec<_>>>()
+    ///     .unwrap();
+    /// assert_eq!(results.len(), 3);
+    /// ```
+    pub fn stream<I: Iterator<Item = F>>(&mut self, values: I) -> SpotStream<'_, I, F, B> {
+        SpotStream::new(self, values)
+    }
+
+    /// Get the quantile for a given probability
+    ///
+    /// Returns NaN if `q` is NaN or the detector has not been fitted. For
+    /// `q == 0.0` the result is the upper endpoint of the fitted tail, which is
+    /// infinite unless gamma < 0.
+    pub fn quantile(&self, q: F) -> F {
+        if self.n == 0 || q.is_nan() {
+            return F::nan();
+        }
+
+        let s = F::from_usize(self.nt) / F::from_usize(self.n);
+        self.excess_threshold + self.up_down * self.tail.quantile(s, q)
+    }
+
+    /// Get the intermediate values of [`quantile`](Self::quantile) for `q`
+    ///
+    /// `z` is exactly `quantile(q)`, and is NaN (like the other values
+    /// depending on the fit) if the detector has not been fitted.
+    pub fn quantile_components(&self, q: F) -> QuantileBreakdown<F> {
+        let s = self.tail_ratio();
+        let tail_quantile = if self.n == 0 || q.is_nan() {
+            F::nan()
+        } else {
+            self.tail.quantile(s, q)
+        };
+        QuantileBreakdown {
+            s,
+            r: q / s,
+            tail_quantile,
+            excess_threshold: self.excess_threshold,
+            z: self.quantile(q),
+        }
+    }
+
+    /// Get the quantiles for several probabilities from the current tail fit
+    ///
+    /// Each entry is [`quantile`](Self::quantile) of the matching entry of `qs`,
+    /// which is handy to report severity tiers (e.g. `q = 1e-3, 1e-4, 1e-5`)
+    /// without running one detector per level.
+    pub fn quantiles(&self, qs: &[F]) -> Vec<F> {
+        qs.iter().map(|&q| self.quantile(q)).collect()
+    }
+
+    /// Get the severity tier of `x` given tier probabilities `qs`
+    ///
+    /// `qs` is expected from the least to the most severe tier (decreasing `q`).
+    /// Returns 0 when `x` is within the first threshold, otherwise `i + 1` where
+    /// `i` is the index of the highest tier exceeded.
+    pub fn classify_tiers(&self, x: F, qs: &[F]) -> usize {
+        qs.iter()
+            .take_while(|&&q| self.up_down * (x - self.quantile(q)) > F::zero())
+            .count()
+    }
+
+    /// Get a confidence interval `(lower, point, upper)` on the quantile for `q`
+    ///
+    /// The point estimate is [`quantile`](Self::quantile) and the bounds are
+    /// `point ∓ z * se`, where `z` is the normal quantile for the two-sided
+    /// `confidence` (e.g. 1.96 for 0.95) and `se` the delta-method standard
+    /// error of [`Tail::quantile_std_error`]. The bounds are NaN when it is not
+    /// available (gamma <= -0.5 or unfitted detector) or if `confidence` is not
+    /// in `(0, 1)`.
+    pub fn threshold_interval(&self, q: F, confidence: f64) -> (F, F, F) {
+        let point = self.quantile(q);
+        if self.n == 0 || !(confidence > 0.0 && confidence < 1.0) {
+            return (F::nan(), point, F::nan());
+        }
+
+        let s = F::from_usize(self.nt) / F::from_usize(self.n);
+        let z = normal_quantile(F::of(0.5 + confidence / 2.0));
+        let half_width = z * self.tail.quantile_std_error(s, q);
+        (point - half_width, point, point + half_width)
+    }
+
+    /// Get the probability for a given value
+    ///
+    /// Returns NaN if `z` is NaN or the detector has not been fitted. Values
+    /// infinitely far in the tail have probability `0.0`.
+    pub fn probability(&self, z: F) -> F {
+        if self.n == 0 || z.is_nan() {
+            return F::nan();
+        }
+
+        let s = F::from_usize(self.nt) / F::from_usize(self.n);
+        self.tail
+            .probability(s, self.up_down * (z - self.excess_threshold))
+    }
+
+    /// Get a normalized severity score of `x` in `[0, 1]`
+    ///
+    /// The score is `1 - P(X beyond x)` in the tail and `0.0` before the
+    /// excess threshold, so it increases with the distance into the monitored
+    /// tail (mirrored for the lower tail) and approaches `1.0` far in it. A
+    /// value at the anomaly threshold scores `1 - q`. Returns NaN if `x` is NaN
+    /// or the detector has not been fitted.
+    pub fn anomaly_score(&self, x: F) -> F {
+        if self.n == 0 || x.is_nan() {
+            return F::nan();
+        }
+        // The GPD is only defined beyond the excess threshold
+        if self.up_down * (x - self.excess_threshold) < F::zero() {
+            return F::zero();
+        }
+        let probability = self.probability(x);
+        if probability.is_nan() {
+            return F::nan();
+        }
+        (F::one() - probability).max(F::zero()).min(F::one())
+    }
+
+    /// Get the probabilities for several values from the current tail fit
+    ///
+    /// Equivalent to calling [`probability`](Self::probability) on each value of
+    /// `zs`: NaN values give NaN, and an unfitted detector gives all NaN.
+    pub fn probabilities(&self, zs: &[F]) -> Vec<F> {
+        if self.n == 0 {
+            return vec![F::nan(); zs.len()];
+        }
+
+        let s = F::from_usize(self.nt) / F::from_usize(self.n);
+        zs.iter()
+            .map(|&z| {
+                if z.is_nan() {
+                    F::nan()
+                } else {
+                    self.tail
+                        .probability(s, self.up_down * (z - self.excess_threshold))
+                }
+            })
+            .collect()
+    }
+
+    /// Draw `count` values from the fitted tail model
+    ///
+    /// Each value is the excess threshold moved by an excess drawn from the
+    /// fitted GPD by inverse transform sampling, i.e. a draw of the data
+    /// conditioned on being in the tail (like the values flagged as excesses and
+    /// anomalies). Returns an empty vector if the detector is not fitted.
+    ///
+    /// ```
+    /// use libspot_rs::{SpotConfig, SpotDetector};
+    /// use rand_xorshift::XorShiftRng;
+    /// use rand_core::SeedableRng;
+    ///
+    /// let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+    /// let data: Vec<f64> = (0..1000).map(|i| ((i * 37) % 1000) as f64 / 100.0).collect();
+    /// spot.fit(&data).unwrap();
+    ///
+    /// let mut rng = XorShiftRng::seed_from_u64(7);
+    /// let samples = spot.sample_tail(&mut rng, 100);
+    /// assert!(samples.iter().all(|&x| x >= spot.excess_threshold()));
+    /// ```
+    #[cfg(feature = "rand")]
+    pub fn sample_tail(&self, rng: &mut impl rand_core::Rng, count: usize) -> Vec<F> {
+        let (gamma, sigma) = self.tail_parameters();
+        if self.excess_threshold.is_nan() || gamma.is_nan() || sigma.is_nan() {
+            return Vec::new();
+        }
+
+        (0..count)
+            .map(|_| {
+                // Uniform in (0, 1) from the 53 high bits, used as P(excess > y)
+                let u = ((rng.next_u64() >> 11) as f64 + 0.5) / (1u64 << 53) as f64;
+                self.excess_threshold + self.up_down * self.tail.quantile(F::one(), F::of(u))
+            })
+            .collect()
+    }
+
+    /// Get the current anomaly threshold
+    pub fn anomaly_threshold(&self) -> F {
+        self.anomaly_threshold
+    }
+
+    /// Get the current excess threshold
+    pub fn excess_threshold(&self) -> F {
+        self.excess_threshold
+    }
+
+    /// Get the absolute difference between the excess threshold and the exact
+    /// empirical quantile of `data` at the excess level
+    ///
+    /// The excess threshold of [`fit`](Self::fit) is a P2 estimate; on the
+    /// training data this measures its approximation error (see
+    /// [`empirical_quantile`](crate::empirical_quantile)). The quantile is
+    /// `level` for an upper tail and `1 - level` for a lower tail. Returns NaN
+    /// if the detector is not fitted or `data` holds no value other than NaN.
+    pub fn excess_threshold_error(&self, data: &[F]) -> F {
+        (self.excess_threshold - empirical_quantile(data, self.tail_probability())).abs()
+    }
+
+    /// Get a stable 64-bit fingerprint of the model, e.g. as a cache key
+    ///
+    /// The fingerprint covers the whole [configuration](Self::config) (with the
+    /// configured `max_excess`, not the capacity an [`AdaptiveTail`] policy
+    /// grew the buffer to), the counters `n` and `nt`, the thresholds and the
+    /// GPD parameters. It is computed with FNV-1a over the bits of these
+    /// values, so it is the same across runs and platforms; every NaN hashes
+    /// alike. Two detectors with the same fingerprint may still differ in
+    /// their stored excesses.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hash = Fnv1a::new();
+        if let Some(config) = self.config() {
+            hash.write_config(&config);
+        }
+        hash.write(self.n as u64);
+        hash.write(self.nt as u64);
+        for value in [
+            self.excess_threshold,
+            self.anomaly_threshold,
+            self.tail.gamma(),
+            self.tail.sigma(),
+        ] {
+            hash.write_f64(value.as_f64());
+        }
+        hash.finish()
+    }
+
+    /// Get the current configuration (reconstructed)
+    ///
+    /// `max_excess` is the configured capacity of the excess buffer, even after
+    /// the [`AdaptiveTail`] policy grew it.
+    pub fn config(&self) -> Option<SpotConfig> {
+        Some(SpotConfig {
+            q: self.q,
+            low_tail: self.low,
+            discard_anomalies: self.discard_anomalies,
+            level: self.level,
+            max_excess: match self.max_excess {
+                0 => self.tail.peaks().capacity(),
+                max_excess => max_excess,
+            },
+            track_excess_threshold: self.track_excess_threshold,
+            pwm_estimator: self.tail.uses_pwm(),
+            min_peaks: self.min_peaks,
+            grimshaw_options: self.tail.grimshaw_options(),
+            warmup_steps: self.warmup_steps,
+            reject_infinite: self.reject_infinite,
+            adaptive_tail: self.adaptive_tail,
+            fit_strategy: self.tail.fit_strategy(),
+            min_gamma: self.tail.min_gamma(),
+            anomaly_rate_window: self.verdicts.as_ref().map_or(0, |v| v.capacity),
+            stable_small_gamma: self.tail.uses_stable_small_gamma(),
+        })
+    }
+
+    /// Whether the configuration of the detector is
+    /// [compatible](SpotConfig::compatible_with) with `config`
+    pub(crate) fn is_compatible_with(&self, config: &SpotConfig) -> bool {
+        self.config().is_some_and(|own| own.compatible_with(config))
+    }
+
+    /// Get the [model schema version](crate::model_schema_version) of the
+    /// detector
+    ///
+    /// This is the current version for a new detector and the version it was
+    /// written with for a deserialized one (0 if written before versioning).
+    /// Serializing always writes the current version.
+    pub fn schema_version(&self) -> u32 {
+        self.schema_version
+    }
+
+    /// Get the total number of data points seen
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// Get the total number of excesses
+    pub fn nt(&self) -> usize {
+        self.nt
+    }
+
+    /// Get the observed excess rate `nt / n`, i.e. the tail mass `s` scaling
+    /// every quantile and probability
+    ///
+    /// Returns NaN if the detector has not been fitted.
+    pub fn excess_rate(&self) -> F {
+        if self.n == 0 {
+            return F::nan();
+        }
+        F::from_usize(self.nt) / F::from_usize(self.n)
+    }
+
+    /// Get the tail mass `s = nt / n` used by the quantiles and probabilities
+    ///
+    /// Same as [`excess_rate`](Self::excess_rate), named after the `s` of the
+    /// quantile formula (see [`quantile_components`](Self::quantile_components)).
+    pub fn tail_ratio(&self) -> F {
+        self.excess_rate()
+    }
+
+    /// Get the excess rate expected from the configuration, `1 - level`
+    pub fn expected_excess_rate(&self) -> F {
+        F::of(1.0 - self.level)
+    }
+
+    /// Whether the observed [`excess_rate`](Self::excess_rate) is more than
+    /// `tolerance` away from the [`expected_excess_rate`](Self::expected_excess_rate)
+    ///
+    /// A large gap means the stream no longer follows the distribution the
+    /// excess threshold was learned on. Always false before the fit.
+    pub fn is_drifting(&self, tolerance: f64) -> bool {
+        (self.excess_rate() - self.expected_excess_rate()).abs() > F::of(tolerance)
+    }
+
+    /// Set whether anomalies are discarded from the model updates
+    ///
+    /// Takes effect from the next [`step`](Self::step).
+    pub fn set_discard_anomalies(&mut self, discard_anomalies: bool) {
+        self.discard_anomalies = discard_anomalies;
+    }
+
+    /// Set the anomaly probability, recomputing the anomaly threshold from the
+    /// fitted tail if any
+    ///
+    /// Fires the [`on_threshold_update`](Self::on_threshold_update) callback if
+    /// the threshold changes. Fails with [`SpotError::QOutOfBounds`] unless
+    /// `0 < q < 1 - level` (e.g. for NaN), leaving the detector unchanged.
+    pub fn set_q(&mut self, q: f64) -> SpotResult<()> {
+        if !(q > 0.0 && q < 1.0 - self.level) {
+            return Err(SpotError::QOutOfBounds);
+        }
+
+        self.q = q;
+        if self.n > 0 {
+            let previous = self.anomaly_threshold;
+            self.anomaly_threshold = self.quantile(F::of(q));
+            self.notify_threshold_update(previous);
+        }
+        Ok(())
+    }
+
+    /// Pin the excess threshold and recompute the anomaly threshold from the
+    /// current tail, without refitting
+    ///
+    /// Meant as a manual override, e.g. during an incident. The tail keeps the
+    /// excesses (and the GPD fitted on them) measured from the previous
+    /// threshold, and `nt / n` no longer estimates the probability of exceeding
+    /// the new one, so the anomaly threshold loses its statistical guarantees
+    /// until the next [`fit`](Self::fit). With
+    /// [`track_excess_threshold`](SpotConfig::track_excess_threshold), `step`
+    /// moves the threshold again at the end of the tracking window.
+    ///
+    /// Fires the [`on_threshold_update`](Self::on_threshold_update) callback if
+    /// the anomaly threshold changes.
+    ///
+    /// Fails with [`SpotError::NotFitted`] before the first fit (there is no
+    /// tail to measure from), and with [`SpotError::DataIsNaN`] or
+    /// [`SpotError::DataIsInfinite`] unless `t` is finite.
+    pub fn set_excess_threshold(&mut self, t: F) -> SpotResult<()> {
+        if self.n == 0 {
+            return Err(SpotError::NotFitted);
+        }
+        if t.is_nan() {
+            return Err(SpotError::DataIsNaN);
+        }
+        if t.is_infinite() {
+            return Err(SpotError::DataIsInfinite);
+        }
+
+        self.excess_threshold = t;
+        let previous = self.anomaly_threshold;
+        self.anomaly_threshold = self.quantile(F::of(self.q));
+        self.notify_threshold_update(previous);
+        Ok(())
+    }
+
+    /// Set the excess level of a detector that has not been fitted yet
+    ///
+    /// The excess threshold and the tail depend on the level, so this fails
+    /// with [`SpotError::AlreadyFitted`] once [`fit`](Self::fit) was called
+    /// (until [`reset`](Self::reset)), and with the errors of
+    /// [`SpotConfig::validate`] for the new level.
+    pub fn set_level(&mut self, level: f64) -> SpotResult<()> {
+        if self.is_fitted() {
+            return Err(SpotError::AlreadyFitted);
+        }
+        if !(0.0..1.0).contains(&level) {
+            return Err(SpotError::LevelOutOfBounds);
+        }
+        if self.q >= (1.0 - level) {
+            return Err(SpotError::QOutOfBounds);
+        }
+
+        self.level = level;
+        Ok(())
+    }
+
+    /// Refit the detector on `data` with another excess level
+    ///
+    /// Unlike [`set_level`](Self::set_level), this works on a fitted detector:
+    /// the stored level is replaced, the tail is emptied and the model is
+    /// fitted again like [`fit`](Self::fit), so the excess threshold, the GPD
+    /// and the anomaly threshold all follow the new level.
+    ///
+    /// Fails with [`SpotError::LevelOutOfBounds`] or [`SpotError::QOutOfBounds`]
+    /// if `new_level` is invalid, and with the errors of `fit` otherwise. The
+    /// refit runs on a copy of the model, so the detector is left untouched
+    /// (level and tail included) when it fails.
+    pub fn recompute_excess_threshold(&mut self, data: &[F], new_level: f64) -> SpotResult<()> {
+        if !(0.0..1.0).contains(&new_level) {
+            return Err(SpotError::LevelOutOfBounds);
+        }
+        if self.q >= (1.0 - new_level) {
+            return Err(SpotError::QOutOfBounds);
+        }
+
+        let mut refitted = self.clone();
+        refitted.level = new_level;
+        refitted.tail.reset();
+        refitted.fit(data)?;
+        // The clone has no callback, and fits do not fire it anyway
+        refitted.threshold_hook = core::mem::take(&mut self.threshold_hook);
+        *self = refitted;
+        Ok(())
+    }
+
+    /// Set the maximum number of excesses of a detector that has not been
+    /// fitted yet, reallocating the tail buffer
+    ///
+    /// Fails with [`SpotError::AlreadyFitted`] once [`fit`](Self::fit) was
+    /// called (until [`reset`](Self::reset)), and with
+    /// [`SpotError::MemoryAllocationFailed`] if `max_excess` is 0.
+    pub fn set_max_excess(&mut self, max_excess: usize) -> SpotResult<()> {
+        if self.is_fitted() {
+            return Err(SpotError::AlreadyFitted);
+        }
+
+        self.tail = Tail::from_peaks(Peaks::from_buffer(B::with_capacity(max_excess)?))
+            .with_pwm(self.tail.uses_pwm())
+            .with_grimshaw_options(self.tail.grimshaw_options())
+            .with_fit_strategy(self.tail.fit_strategy())
+            .with_min_gamma(self.tail.min_gamma())
+            .with_stable_small_gamma(self.tail.uses_stable_small_gamma());
+        self.max_excess = max_excess;
+        Ok(())
+    }
+
+    /// Grow a full tail buffer according to the [`AdaptiveTail`] policy, if any
+    fn grow_tail(&mut self) {
+        let Some(policy) = self.adaptive_tail else {
+            return;
+        };
+        let peaks = self.tail.peaks();
+        if !peaks.is_full() {
+            return;
+        }
+        let rate = self.nt as f64 / self.n as f64;
+        if let Some(capacity) = policy.grown_capacity(peaks.capacity(), rate) {
+            self.tail.grow(capacity);
+        }
+    }
+
+    /// Whether a fit has set the excess threshold since the creation or the
+    /// last reset
+    fn is_fitted(&self) -> bool {
+        !self.excess_threshold.is_nan()
+    }
+
+    /// Overwrite the observation counters `n` and `nt` and recompute the
+    /// anomaly threshold, keeping the fitted tail and the excess threshold
+    ///
+    /// The ratio `s = nt / n` estimates P(X > excess threshold) and scales
+    /// every quantile and probability. After a long run `n` is large and `s`
+    /// barely moves; resetting the counters makes the rate estimate responsive
+    /// again while the tail shape (gamma, sigma) stays as learned.
+    ///
+    /// Returns [`SpotError::CountersOutOfBounds`] unless `0 < n` and `nt <= n`,
+    /// and [`SpotError::AnomalyThresholdIsNaN`] if the model has not been fitted.
+    /// The detector is left unchanged on error.
+    pub fn reset_counters(&mut self, n: usize, nt: usize) -> SpotResult<()> {
+        if n == 0 || nt > n {
+            return Err(SpotError::CountersOutOfBounds);
+        }
+
+        let previous = (self.n, self.nt);
+        self.n = n;
+        self.nt = nt;
+
+        let threshold = self.quantile(F::of(self.q));
+        if threshold.is_nan() {
+            (self.n, self.nt) = previous;
+            return Err(SpotError::AnomalyThresholdIsNaN);
+        }
+        self.anomaly_threshold = threshold;
+        Ok(())
+    }
+
+    /// Replace the whole fitted state, checking that the tail buffer matches
+    /// `max_excess` and the counters
+    pub(crate) fn restore(
+        &mut self,
+        peaks: Peaks<F, B>,
+        (gamma, sigma): (F, F),
+        (excess_threshold, anomaly_threshold): (F, F),
+        (n, nt): (usize, usize),
+    ) -> SpotResult<()> {
+        if peaks.capacity() != self.tail.peaks().capacity() {
+            return Err(SpotError::ConfigMismatch);
+        }
+        if nt > n {
+            return Err(SpotError::CountersOutOfBounds);
+        }
+
+        self.tail.restore(peaks, gamma, sigma);
+        self.excess_threshold = excess_threshold;
+        self.anomaly_threshold = anomaly_threshold;
+        self.n = n;
+        self.nt = nt;
+        Ok(())
+    }
+
+    /// Whether the detector is in the warmup following [`fit`](Self::fit), during
+    /// which [`step`](Self::step) never flags an anomaly
+    ///
+    /// See [`SpotConfig::warmup_steps`].
+    pub fn in_warmup(&self) -> bool {
+        self.warmup_remaining > 0
+    }
+
+    /// Whether the tail holds at least [`SpotConfig::min_peaks`] excesses, so
+    /// that [`step`](Self::step) refits the GPD on new excesses
+    pub fn is_tail_ready(&self) -> bool {
+        self.tail.size() >= self.min_peaks
+    }
+
+    /// Get the current tail parameters
+    pub fn tail_parameters(&self) -> (F, F) {
+        (self.tail.gamma(), self.tail.sigma())
+    }
+
+    /// Get a snapshot of the counters, thresholds and tail parameters
+    pub fn summary(&self) -> SpotSummary<F> {
+        SpotSummary {
+            n: self.n,
+            nt: self.nt,
+            excess_threshold: self.excess_threshold,
+            anomaly_threshold: self.anomaly_threshold,
+            gamma: self.tail.gamma(),
+            sigma: self.tail.sigma(),
+        }
+    }
+
+    /// Check that the detector is in a numerically valid state
+    ///
+    /// Meant for long-running services: a single call confirming that the
+    /// model is fitted and that its thresholds and GPD parameters are usable.
+    ///
+    /// ```
+    /// use libspot_rs::{SpotConfig, SpotDetector};
+    ///
+    /// let config = SpotConfig {
+    ///     level: 0.9,
+    ///     max_excess: 50,
+    ///     ..SpotConfig::default()
+    /// };
+    /// let mut spot = SpotDetector::new(config).unwrap();
+    /// assert!(!spot.health().fitted);
+    ///
+    /// let data: Vec<f64> = (0..1000).map(|i| (i as f64 * 0.37).sin()).collect();
+    /// spot.fit(&data).unwrap();
+    /// assert!(spot.health().is_healthy());
+    /// ```
+    pub fn health(&self) -> SpotHealth {
+        let gamma = self.tail.gamma();
+        let sigma = self.tail.sigma();
+        SpotHealth {
+            fitted: self.is_fitted(),
+            thresholds_finite: self.excess_threshold.is_finite()
+                && self.anomaly_threshold.is_finite(),
+            gamma_in_range: gamma.is_finite() && self.tail.is_admissible(gamma),
+            sigma_positive: sigma.is_finite() && sigma > F::zero(),
+            tail_full: self.tail.size() == self.tail.peaks().capacity(),
+            adaptive_tail: self.adaptive_tail.is_some(),
+        }
+    }
+
+    /// Reset the detector's internal state, keeping the configuration and the
+    /// backing buffer. After calling this, [`fit`](Self::fit) must be called
+    /// again before further [`step`](Self::step) calls.
+    ///
+    /// This mirrors the `spot_reset` C API exposed by the FFI wrapper crate.
+    pub fn reset(&mut self) {
+        self.anomaly_threshold = F::nan();
+        self.excess_threshold = F::nan();
+        self.nt = 0;
+        self.n = 0;
+        self.tail.reset();
+        self.excess_tracker = None;
+        self.tracking_window = 0;
+        self.training_estimator = None;
+        self.warmup_remaining = 0;
+        self.clear_verdicts();
+        self.skipped = 0;
+        self.stats = RuntimeStats::default();
+        self.tail.clear_estimator_calls();
+    }
+
+    /// Get the work counters since the creation or the last [`reset`](Self::reset)
+    ///
+    /// ```
+    /// use libspot_rs::{SpotConfig, SpotDetector};
+    ///
+    /// let config = SpotConfig { level: 0.98, ..SpotConfig::default() };
+    /// let mut spot = SpotDetector::new(config).unwrap();
+    /// let data: Vec<f64> = (0..1000).map(|i| ((i * 37) % 1000) as f64 / 100.0).collect();
+    /// spot.fit(&data).unwrap();
+    /// spot.step(9.995).unwrap();
+    ///
+    /// let stats = spot.stats();
+    /// assert_eq!((stats.steps, stats.excesses, stats.refits), (1, 1, 1));
+    /// assert_eq!(stats.grimshaw_calls, 2); // training fit and refit
+    /// ```
+    pub fn stats(&self) -> RuntimeStats {
+        let (mom_calls, grimshaw_calls) = self.tail.estimator_calls();
+        RuntimeStats {
+            mom_calls,
+            grimshaw_calls,
+            ..self.stats
+        }
+    }
+
+    /// Get the tail, with the fitted GPD and the stored excesses
+    pub fn tail(&self) -> &Tail<F, B> {
+        &self.tail
+    }
+
+    /// Get the current size of the tail data
+    pub fn tail_size(&self) -> usize {
+        self.tail.size()
+    }
+
+    /// Get the minimum value in the peaks
+    pub fn peaks_min(&self) -> F {
+        self.tail.peaks().min()
+    }
+
+    /// Get the maximum value in the peaks
+    pub fn peaks_max(&self) -> F {
+        self.tail.peaks().max()
+    }
+
+    /// Get the mean of the peaks
+    pub fn peaks_mean(&self) -> F {
+        self.tail.peaks().mean()
+    }
+
+    /// Get the variance of the peaks
+    pub fn peaks_variance(&self) -> F {
+        self.tail.peaks().variance()
+    }
+
+    /// Get the peaks data as a vector (for debugging and export)
+    pub fn peaks_data(&self) -> Vec<F> {
+        self.tail.peaks().iter().collect()
+    }
+
+    /// Get the excesses currently stored in the tail, oldest first
+    ///
+    /// These are the values `z - t` the GPD is fitted on, so they can be used to
+    /// inspect or re-fit the tail externally. Same as
+    /// [`peaks_data`](Self::peaks_data), named after the FFI `get_excess_values`.
+    pub fn excess_values(&self) -> Vec<F> {
+        self.peaks_data()
+    }
+}
+
+/// Compact one-line summary, e.g. for logs (the `Debug` output includes the
+/// whole peaks buffer)
+impl<F: Float, B: RingBuffer<F>> fmt::Display for SpotDetector<F, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Spot{{ n={}, nt={}, T={:.6}, Z={:.6}, gamma={:.6}, sigma={:.6} }}",
+            self.n,
+            self.nt,
+            self.excess_threshold,
+            self.anomaly_threshold,
+            self.tail.gamma(),
+            self.tail.sigma()
+        )
+    }
+}
+
+/// 64-bit FNV-1a hash, fed with whole words
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, word: u64) {
+        for byte in word.to_le_bytes() {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    /// Hash the bits of `x`, with a single pattern for all NaNs
+    fn write_f64(&mut self, x: f64) {
+        self.write(if x.is_nan() {
+            f64::NAN.to_bits()
+        } else {
+            x.to_bits()
+        });
+    }
+
+    /// Hash an optional value as a presence flag followed by the value
+    fn write_option<T>(&mut self, value: Option<T>, write: impl FnOnce(&mut Self, T)) {
+        self.write(value.is_some() as u64);
+        if let Some(value) = value {
+            write(self, value);
+        }
+    }
+
+    /// Hash every field of `config`
+    fn write_config(&mut self, config: &SpotConfig) {
+        // Destructured so that a new field cannot be left out
+        let SpotConfig {
+            q,
+            low_tail,
+            discard_anomalies,
+            level,
+            max_excess,
+            track_excess_threshold,
+            pwm_estimator,
+            min_peaks,
+            grimshaw_options,
+            warmup_steps,
+            reject_infinite,
+            adaptive_tail,
+            fit_strategy,
+            min_gamma,
+            anomaly_rate_window,
+            stable_small_gamma,
+        } = *config;
+
+        self.write_f64(q);
+        self.write_f64(level);
+        self.write(low_tail as u64);
+        self.write(discard_anomalies as u64);
+        self.write(max_excess as u64);
+        self.write(min_peaks as u64);
+        self.write(warmup_steps as u64);
+        self.write(track_excess_threshold as u64);
+        self.write(pwm_estimator as u64);
+        self.write(reject_infinite as u64);
+        self.write(anomaly_rate_window as u64);
+        self.write(stable_small_gamma as u64);
+        self.write(match fit_strategy {
+            FitStrategy::Both => 0,
+            FitStrategy::MomOnly => 1,
+            FitStrategy::GrimshawOnly => 2,
+            FitStrategy::Auto => 3,
+        });
+        self.write_option(min_gamma, Self::write_f64);
+        self.write_option(grimshaw_options, |hash, options| {
+            hash.write_f64(options.epsilon);
+            hash.write(options.max_iter as u64);
+        });
+        self.write_option(adaptive_tail, |hash, policy| {
+            hash.write(policy.max_excess as u64);
+            hash.write_f64(policy.min_excess_rate);
+        });
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Number of values between two progress reports of
+/// [`SpotDetector::fit_with_progress`] in a pass over `len` values
+fn progress_chunk_size(len: usize) -> usize {
+    (len / 50).max(1)
+}
+
+/// Excess threshold estimated by the P2 `estimator` of the `level` quantile
+///
+/// This is the P2 estimate (0 until 5 values were pushed, like the C
+/// library), except that with `level == 0` it is exactly the most extreme
+/// value on the normal side, e.g. the minimum for the upper tail, since the P2
+/// estimate of the 0 quantile only approaches it.
+fn excess_threshold_estimate<F: Float>(estimator: &P2Estimator<F>, level: f64, low: bool) -> F {
+    if level == 0.0 && estimator.count() > 0 {
+        normal_extreme(estimator, low)
+    } else {
+        estimator.quantile_or_zero()
+    }
+}
+
+/// Most extreme value seen by `estimator` on the normal side of the tail
+fn normal_extreme<F: Float>(estimator: &P2Estimator<F>, low: bool) -> F {
+    let (min, max) = estimator.extremes();
+    if low {
+        max
+    } else {
+        min
+    }
+}
+
+/// Training data without its infinite values, failing with
+/// [`SpotError::DataIsInfinite`] instead if `reject_infinite`
+pub(crate) fn finite_training_data<F: Float>(
+    data: &[F],
+    reject_infinite: bool,
+) -> SpotResult<Cow<'_, [F]>> {
+    if !data.iter().any(|value| value.is_infinite()) {
+        return Ok(Cow::Borrowed(data));
+    }
+    if reject_infinite {
+        return Err(SpotError::DataIsInfinite);
+    }
+    Ok(Cow::Owned(
+        data.iter()
+            .copied()
+            .filter(|value| !value.is_infinite())
+            .collect(),
+    ))
+}
+
+/// Ring buffer of the last `step` verdicts, one byte each (1 for an anomaly)
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct VerdictWindow {
+    /// Verdicts in the order they were pushed, wrapping around at `cursor`
+    flags: Vec<u8>,
+    /// Maximum number of verdicts kept
+    capacity: usize,
+    /// Index of the next verdict to overwrite once the window is full
+    cursor: usize,
+    /// Number of anomalies among the verdicts
+    anomalies: usize,
+}
+
+impl VerdictWindow {
+    /// Create an empty window of `capacity` (non-zero) verdicts
+    fn new(capacity: usize) -> Self {
+        Self {
+            flags: Vec::with_capacity(capacity),
+            capacity,
+            cursor: 0,
+            anomalies: 0,
+        }
+    }
+
+    /// Keep a verdict, forgetting the oldest one once the window is full
+    fn push(&mut self, anomaly: bool) {
+        let flag = u8::from(anomaly);
+        if self.flags.len() < self.capacity {
+            self.flags.push(flag);
+        } else {
+            let erased = core::mem::replace(&mut self.flags[self.cursor], flag);
+            self.anomalies -= usize::from(erased);
+        }
+        self.anomalies += usize::from(flag);
+        self.cursor = (self.cursor + 1) % self.capacity;
+    }
+
+    /// Forget every verdict
+    fn clear(&mut self) {
+        self.flags.clear();
+        self.cursor = 0;
+        self.anomalies = 0;
+    }
+
+    /// Fraction of anomalies among the verdicts (NaN if there are none)
+    fn rate(&self) -> f64 {
+        if self.flags.is_empty() {
+            f64::NAN
+        } else {
+            self.anomalies as f64 / self.flags.len() as f64
+        }
+    }
+}
+
+/// Most extreme distinct values of a stream, the largest ones once
+/// multiplied by `up_down`
+#[derive(Default)]
+struct ExtremeValues<F> {
+    top: Option<F>,
+    top_count: usize,
+    runner_up: Option<F>,
+}
+
+impl<F: Float> ExtremeValues<F> {
+    /// Account for a non-NaN `value`
+    fn push(&mut self, value: F, up_down: F) {
+        match self.top.map(|top| up_down * (value - top)) {
+            Some(difference) if difference == F::zero() => self.top_count += 1,
+            Some(difference) if difference < F::zero() => {
+                if self
+                    .runner_up
+                    .is_none_or(|runner_up| up_down * (value - runner_up) > F::zero())
+                {
+                    self.runner_up = Some(value);
+                }
+            }
+            _ => {
+                self.runner_up = self.top;
+                self.top = Some(value);
+                self.top_count = 1;
+            }
+        }
+    }
+}
+
+/// Keep the `keep` most extreme values (the largest ones once multiplied by
+/// `up_down`) of the non-NaN `candidates`, in any order
+fn retain_most_extreme<F: Float>(candidates: &mut Vec<(usize, F)>, keep: usize, up_down: F) {
+    if candidates.len() <= keep {
+        return;
+    }
+    candidates.select_nth_unstable_by(keep - 1, |a, b| {
+        (up_down * b.1)
+            .partial_cmp(&(up_down * a.1))
+            .unwrap_or(Ordering::Equal)
+    });
+    candidates.truncate(keep);
+}
+
+/// Detectors are equal if they have the same configuration, counters,
+/// thresholds and tail (parameters and stored excesses), NaN comparing equal
+/// to NaN so that two unfitted detectors are equal
+///
+/// The warmup, tracking and training estimator states are not compared.
+impl<F: Float, B: RingBuffer<F>> PartialEq for SpotDetector<F, B> {
+    fn eq(&self, other: &Self) -> bool {
+        spot_approx_eq(self, other, F::zero())
+    }
+}
+
+/// Compare two detectors like [`PartialEq`], allowing the thresholds, the tail
+/// parameters and the stored excesses to differ by up to `epsilon`
+///
+/// `q` and `level` may differ by up to `epsilon` as well, while the other
+/// options and the counters must be equal. NaN values only match NaN values.
+pub fn spot_approx_eq<F: Float, B: RingBuffer<F>>(
+    a: &SpotDetector<F, B>,
+    b: &SpotDetector<F, B>,
+    epsilon: F,
+) -> bool {
+    let close = |x: F, y: F| x == y || (x.is_nan() && y.is_nan()) || (x - y).abs() <= epsilon;
+    let (a_peaks, b_peaks) = (a.tail.peaks().container(), b.tail.peaks().container());
+    let (Some(a_config), Some(b_config)) = (a.config(), b.config()) else {
+        return false;
+    };
+
+    close(F::of(a_config.q), F::of(b_config.q))
+        && close(F::of(a_config.level), F::of(b_config.level))
+        && SpotConfig {
+            q: b_config.q,
+            level: b_config.level,
+            ..a_config
+        } == b_config
+        && (a.n, a.nt) == (b.n, b.nt)
+        && close(a.excess_threshold, b.excess_threshold)
+        && close(a.anomaly_threshold, b.anomaly_threshold)
+        && close(a.tail.gamma(), b.tail.gamma())
+        && close(a.tail.sigma(), b.tail.sigma())
+        && a_peaks.size() == b_peaks.size()
+        && a_peaks.iter().zip(b_peaks.iter()).all(|(x, y)| close(x, y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::estimator::{FitStrategy, GrimshawOptions};
+    use crate::testing::exponential_data;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_spot_creation_valid_config() {
+        let config = SpotConfig::default();
+        let spot = SpotDetector::new(config).unwrap();
+
+        assert_relative_eq!(spot.q, 0.0001);
+        assert!(!spot.low);
+        assert!(spot.discard_anomalies);
+        assert_relative_eq!(spot.level, 0.998);
+        assert!(spot.anomaly_threshold().is_nan());
+        assert!(spot.excess_threshold().is_nan());
+        assert_eq!(spot.n(), 0);
+        assert_eq!(spot.nt(), 0);
+    }
+
+    #[test]
+    fn test_spot_invalid_level() {
+        let config = SpotConfig {
+            level: 1.5, // Invalid
+            ..SpotConfig::default()
+        };
+        let result = SpotDetector::new(config);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), SpotError::LevelOutOfBounds);
+    }
+
+    #[test]
+    fn test_spot_invalid_q() {
+        let config = SpotConfig {
+            q: 0.5, // Too high for level 0.998
+            ..SpotConfig::default()
+        };
+        let result = SpotDetector::new(config);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), SpotError::QOutOfBounds);
+    }
+
+    #[test]
+    fn test_spot_fit_basic() {
+        let config = SpotConfig::default();
+        let mut spot = SpotDetector::new(config).unwrap();
+
+        // Create simple training data
+        let data: Vec<f64> = (0..1000).map(|i| (i as f64 / 1000.0) * 2.0 - 1.0).collect();
+
+        let result = spot.fit(&data);
+        assert!(result.is_ok());
+
+        // After fit, thresholds should be valid
+        assert!(!spot.anomaly_threshold().is_nan());
+        assert!(!spot.excess_threshold().is_nan());
+        assert!(spot.anomaly_threshold().is_finite());
+        assert!(spot.excess_threshold().is_finite());
+        assert_eq!(spot.n(), 1000);
+        assert!(spot.nt() > 0); // Should have some excesses
+    }
+
+    #[test]
+    fn test_spot_step_normal() {
+        let config = SpotConfig::default();
+        let mut spot = SpotDetector::new(config).unwrap();
+
+        // Fit with simple data
+        let data: Vec<f64> = (0..100).map(|i| i as f64).collect();
+        spot.fit(&data).unwrap();
+
+        // Test normal value
+        let result = spot.step(50.0);
+        assert!(result.is_ok());
+        // Result depends on the thresholds, but should be valid
+    }
+
+    #[test]
+    fn test_spot_step_nan() {
+        let config = SpotConfig::default();
+        let mut spot = SpotDetector::new(config).unwrap();
+
+        let result = spot.step(f64::NAN);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), SpotError::DataIsNaN);
+    }
+
+    #[test]
+    fn test_spot_reset_returns_to_pristine_state() {
+        let config = SpotConfig::default();
+        let mut spot = SpotDetector::new(config.clone()).unwrap();
+
+        let data: Vec<f64> = (0..1000).map(|i| (i as f64 / 1000.0) * 2.0 - 1.0).collect();
+        spot.fit(&data).unwrap();
+        for v in &data {
+            let _ = spot.step(*v).unwrap();
+        }
+        assert!(spot.n() > 0);
+        assert!(!spot.anomaly_threshold().is_nan());
+
+        spot.reset();
+
+        // Looks like a freshly constructed detector.
+        assert!(spot.anomaly_threshold().is_nan());
+        assert!(spot.excess_threshold().is_nan());
+        assert_eq!(spot.n(), 0);
+        assert_eq!(spot.nt(), 0);
+        assert_eq!(spot.tail_size(), 0);
+        assert_eq!(spot.config(), Some(config.clone()));
+
+        // Re-fit produces identical numbers to a fresh detector.
+        let mut fresh = SpotDetector::new(config).unwrap();
+        spot.fit(&data).unwrap();
+        fresh.fit(&data).unwrap();
+        assert_relative_eq!(spot.anomaly_threshold(), fresh.anomaly_threshold());
+        assert_relative_eq!(spot.excess_threshold(), fresh.excess_threshold());
+        assert_eq!(spot.nt(), fresh.nt());
+        assert_eq!(spot.n(), fresh.n());
+    }
+
+    #[test]
+    fn test_spot_reset_before_fit_is_noop_safe() {
+        // Calling reset on a freshly constructed detector must not panic
+        // and must leave the detector in the same observable state.
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        spot.reset();
+        assert!(spot.anomaly_threshold().is_nan());
+        assert!(spot.excess_threshold().is_nan());
+        assert_eq!(spot.n(), 0);
+        assert_eq!(spot.nt(), 0);
+        assert_eq!(spot.tail_size(), 0);
+
+        // Fit still works normally afterwards.
+        let data: Vec<f64> = (0..500).map(|i| (i as f64 / 500.0) * 2.0 - 1.0).collect();
+        spot.fit(&data).unwrap();
+        assert!(!spot.anomaly_threshold().is_nan());
+    }
+
+    #[test]
+    fn test_spot_reset_is_idempotent() {
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        let data: Vec<f64> = (0..500).map(|i| (i as f64 / 500.0) * 2.0 - 1.0).collect();
+        spot.fit(&data).unwrap();
+        for v in &data {
+            let _ = spot.step(*v).unwrap();
+        }
+
+        spot.reset();
+        let after_first_n = spot.n();
+        let after_first_nt = spot.nt();
+        let after_first_size = spot.tail_size();
+
+        spot.reset();
+        assert_eq!(spot.n(), after_first_n);
+        assert_eq!(spot.nt(), after_first_nt);
+        assert_eq!(spot.tail_size(), after_first_size);
+        assert!(spot.anomaly_threshold().is_nan());
+        assert!(spot.excess_threshold().is_nan());
+    }
+
+    #[test]
+    fn test_spot_reset_then_fit_then_step_full_cycle() {
+        // Full lifecycle: fit -> step -> reset -> fit again -> step again must
+        // produce the same step classifications as a fresh detector running
+        // the same fit+step sequence.
+        let config = SpotConfig::default();
+        let train: Vec<f64> = (0..1000).map(|i| (i as f64 / 1000.0) * 2.0 - 1.0).collect();
+        let probe: Vec<f64> = (0..200).map(|i| (i as f64 / 100.0) - 1.0).collect();
+
+        let mut reused = SpotDetector::new(config.clone()).unwrap();
+        reused.fit(&train).unwrap();
+        for v in &probe {
+            let _ = reused.step(*v).unwrap();
+        }
+        reused.reset();
+        reused.fit(&train).unwrap();
+        let reused_classifications: Vec<SpotStatus> =
+            probe.iter().map(|&v| reused.step(v).unwrap()).collect();
+
+        let mut fresh = SpotDetector::new(config).unwrap();
+        fresh.fit(&train).unwrap();
+        let fresh_classifications: Vec<SpotStatus> =
+            probe.iter().map(|&v| fresh.step(v).unwrap()).collect();
+
+        assert_eq!(reused_classifications, fresh_classifications);
+        assert_relative_eq!(reused.anomaly_threshold(), fresh.anomaly_threshold());
+        assert_relative_eq!(reused.excess_threshold(), fresh.excess_threshold());
+        assert_eq!(reused.nt(), fresh.nt());
+        assert_eq!(reused.n(), fresh.n());
+    }
+
+    #[test]
+    fn test_spot_reset_refit_matches_fresh_tail_parameters() {
+        let config = SpotConfig {
+            level: 0.98,
+            ..SpotConfig::default()
+        };
+        let train = exponential_data(10_000, 12);
+        let mut reused = SpotDetector::new(config.clone()).unwrap();
+        reused.fit(&exponential_data(10_000, 13)).unwrap();
+        for &x in &exponential_data(20_000, 14) {
+            reused.step(x).unwrap();
+        }
+        // The peaks buffer has wrapped around before the reset
+        assert_eq!(reused.tail_size(), 200);
+
+        reused.reset();
+        assert_eq!(reused.config().unwrap().max_excess, 200);
+        reused.fit(&train).unwrap();
+
+        let mut fresh = SpotDetector::new(config).unwrap();
+        fresh.fit(&train).unwrap();
+
+        assert_eq!(reused.tail_parameters(), fresh.tail_parameters());
+        assert_eq!(reused.peaks_data(), fresh.peaks_data());
+        assert_eq!(reused.anomaly_threshold(), fresh.anomaly_threshold());
+    }
+
+    #[test]
+    fn test_spot_f32_matches_f64_tail_parameters() {
+        let config = SpotConfig {
+            level: 0.98,
+            ..SpotConfig::default()
+        };
+        let train = exponential_data(10_000, 20);
+        let train32: Vec<f32> = train.iter().map(|&x| x as f32).collect();
+
+        let mut double = SpotDetector::new(config.clone()).unwrap();
+        double.fit(&train).unwrap();
+        let mut single = SpotDetector::<f32>::new_generic(config).unwrap();
+        single.fit(&train32).unwrap();
+
+        // The P2 excess thresholds differ slightly, so the two tails are fitted
+        // on nearly (not exactly) the same excesses
+        assert!(single.nt().abs_diff(double.nt()) < 10);
+        assert_relative_eq!(
+            single.excess_threshold() as f64,
+            double.excess_threshold(),
+            max_relative = 1e-2
+        );
+        let (gamma, sigma) = double.tail_parameters();
+        let (gamma32, sigma32) = single.tail_parameters();
+        assert!(
+            (gamma32 as f64 - gamma).abs() < 1e-2,
+            "{gamma32} vs {gamma}"
+        );
+        assert_relative_eq!(sigma32 as f64, sigma, max_relative = 1e-2);
+        assert_relative_eq!(
+            single.anomaly_threshold() as f64,
+            double.anomaly_threshold(),
+            max_relative = 1e-2
+        );
+    }
+
+    #[test]
+    fn test_spot_array_ubend_matches_ubend() {
+        use crate::ubend::ArrayUbend;
+
+        let config = SpotConfig {
+            max_excess: 100,
+            ..SpotConfig::default()
+        };
+        let train = exponential_data(5_000, 21);
+        let mut spot = SpotDetector::fitted(config.clone(), &train).unwrap();
+        let mut array =
+            SpotDetector::<f64, ArrayUbend<100>>::new_with_buffer(config.clone()).unwrap();
+        array.fit(&train).unwrap();
+        assert_eq!(array.tail_parameters(), spot.tail_parameters());
+        assert_eq!(array.anomaly_threshold(), spot.anomaly_threshold());
+
+        for x in exponential_data(5_000, 22) {
+            assert_eq!(array.step(x).unwrap(), spot.step(x).unwrap());
+        }
+        assert_eq!(array.tail_parameters(), spot.tail_parameters());
+        assert_eq!(array.anomaly_threshold(), spot.anomaly_threshold());
+        assert_eq!(array.peaks_data(), spot.peaks_data());
+        assert_eq!((array.n(), array.nt()), (spot.n(), spot.nt()));
+
+        // The buffer cannot hold another max_excess, nor grow
+        let result = SpotDetector::<f64, ArrayUbend<100>>::new_with_buffer(SpotConfig::default());
+        assert_eq!(result.err(), Some(SpotError::MemoryAllocationFailed));
+        let mut fresh =
+            SpotDetector::<f64, ArrayUbend<100>>::new_with_buffer(config.clone()).unwrap();
+        assert_eq!(
+            fresh.set_max_excess(50),
+            Err(SpotError::MemoryAllocationFailed)
+        );
+        let mut adaptive = SpotDetector::<f64, ArrayUbend<100>>::new_with_buffer(SpotConfig {
+            adaptive_tail: Some(AdaptiveTail {
+                max_excess: 400,
+                min_excess_rate: 0.0,
+            }),
+            ..config
+        })
+        .unwrap();
+        adaptive.fit(&train).unwrap();
+        for x in exponential_data(5_000, 22) {
+            adaptive.step(x).unwrap();
+        }
+        assert_eq!(adaptive.tail().peaks().capacity(), 100);
+    }
+
+    #[test]
+    fn test_spot_fit_with_validation_excess_rate() {
+        let config = SpotConfig {
+            level: 0.98,
+            ..SpotConfig::default()
+        };
+        let mut spot = SpotDetector::new(config).unwrap();
+        let data = exponential_data(50_000, 7);
+
+        let report = spot.fit_with_validation(&data, 0.4).unwrap();
+
+        assert_eq!(report.training_size, 30_000);
+        assert_eq!(report.validation_size, 20_000);
+        assert_relative_eq!(report.expected_excess_rate, 0.02, epsilon = 1e-12);
+        assert!(
+            (report.excess_rate - report.expected_excess_rate).abs() < 0.005,
+            "realized excess rate {} too far from {}",
+            report.excess_rate,
+            report.expected_excess_rate
+        );
+        assert!(report.anomaly_rate <= report.excess_rate);
+        assert_eq!(spot.n(), 30_000 + 20_000 - report.anomalies);
+    }
+
+    #[test]
+    fn test_spot_fit_with_validation_invalid_fraction() {
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        let data = exponential_data(1000, 1);
+
+        for fraction in [0.0, 1.0, -0.5, f64::NAN] {
+            assert_eq!(
+                spot.fit_with_validation(&data, fraction).unwrap_err(),
+                SpotError::ValidationFractionOutOfBounds
+            );
+        }
+    }
+
+    #[test]
+    fn test_spot_quantiles_and_tiers() {
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        spot.fit(&exponential_data(10_000, 2)).unwrap();
+
+        let qs = [1e-3, 1e-4, 1e-5];
+        let thresholds = spot.quantiles(&qs);
+        assert_eq!(thresholds.len(), qs.len());
+        assert_eq!(thresholds[1], spot.quantile(1e-4));
+        assert!(thresholds.windows(2).all(|w| w[0] < w[1]), "{thresholds:?}");
+
+        assert_eq!(spot.classify_tiers(0.0, &qs), 0);
+        assert_eq!(spot.classify_tiers(thresholds[0] + 1e-9, &qs), 1);
+        assert_eq!(
+            spot.classify_tiers((thresholds[1] + thresholds[2]) / 2.0, &qs),
+            2
+        );
+        assert_eq!(spot.classify_tiers(thresholds[2] * 2.0, &qs), 3);
+        assert_eq!(spot.classify_tiers(f64::NAN, &qs), 0);
+    }
+
+    #[test]
+    fn test_spot_probabilities_match_single_calls() {
+        let zs = [0.0, 1.0, 2.5, 5.0, 10.0, f64::NAN, f64::INFINITY, -1.0];
+
+        let unfitted = SpotDetector::new(SpotConfig::default()).unwrap();
+        assert!(unfitted.probabilities(&zs).iter().all(|p| p.is_nan()));
+
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        spot.fit(&exponential_data(5_000, 4)).unwrap();
+        let probabilities = spot.probabilities(&zs);
+        assert_eq!(probabilities.len(), zs.len());
+        for (&z, &p) in zs.iter().zip(&probabilities) {
+            let expected = spot.probability(z);
+            assert!(
+                p == expected || (p.is_nan() && expected.is_nan()),
+                "{z}: {p} vs {expected}"
+            );
+        }
+        assert!(probabilities[5].is_nan());
+        assert!(spot.probabilities(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_spot_display_and_summary() {
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        spot.fit(&exponential_data(5_000, 5)).unwrap();
+
+        let summary = spot.summary();
+        assert_eq!((summary.n, summary.nt), (spot.n(), spot.nt()));
+        assert_eq!(summary.anomaly_threshold, spot.anomaly_threshold());
+        assert_eq!(summary.excess_threshold, spot.excess_threshold());
+        assert_eq!((summary.gamma, summary.sigma), spot.tail_parameters());
+
+        let display = spot.to_string();
+        assert!(display.starts_with("Spot{ n=5000, "), "{display}");
+        assert!(display.contains(&format!("T={:.6}", spot.excess_threshold())));
+        assert!(display.contains(&format!("Z={:.6}", spot.anomaly_threshold())));
+        assert!(!display.contains('\n'));
+
+        let unfitted = SpotDetector::new(SpotConfig::default()).unwrap();
+        assert!(unfitted.to_string().contains("T=NaN, Z=NaN"));
+    }
+
+    #[test]
+    fn test_spot_fit_update_close_to_full_fit() {
+        let a = exponential_data(10_000, 6);
+        let b = exponential_data(10_000, 7);
+        let all: Vec<f64> = a.iter().chain(&b).copied().collect();
+
+        let mut full = SpotDetector::new(SpotConfig::default()).unwrap();
+        full.fit(&all).unwrap();
+        let mut updated = SpotDetector::new(SpotConfig::default()).unwrap();
+        updated.fit(&a).unwrap();
+        updated.fit_update(&b).unwrap();
+
+        // The threshold comes from the same P2 estimator fed with the same values
+        assert_eq!(updated.excess_threshold(), full.excess_threshold());
+        assert_eq!(updated.n(), full.n());
+        let nt_diff = updated.nt().abs_diff(full.nt()) as f64;
+        assert!(
+            nt_diff <= 0.05 * full.nt() as f64,
+            "{} vs {}",
+            updated.nt(),
+            full.nt()
+        );
+        assert_relative_eq!(
+            updated.anomaly_threshold(),
+            full.anomaly_threshold(),
+            max_relative = 0.05
+        );
+
+        // Without a previous fit, fit_update is a plain fit
+        let mut fresh = SpotDetector::new(SpotConfig::default()).unwrap();
+        fresh.fit_update(&all).unwrap();
+        assert_eq!(fresh.anomaly_threshold(), full.anomaly_threshold());
+    }
+
+    #[test]
+    fn test_spot_min_peaks_keeps_threshold_until_tail_ready() {
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        spot.fit(&exponential_data(1000, 1)).unwrap();
+        let initial = spot.anomaly_threshold();
+        assert!(initial.is_finite());
+        assert!(spot.tail_size() < 10 && !spot.is_tail_ready());
+
+        let value = (spot.excess_threshold() + initial) / 2.0;
+        while spot.tail_size() < 9 {
+            assert_eq!(spot.step(value).unwrap(), SpotStatus::Excess);
+            assert_eq!(spot.anomaly_threshold(), initial);
+            assert!(!spot.is_tail_ready());
+        }
+
+        // The tenth peak makes the tail ready and triggers a refit
+        assert_eq!(spot.step(value).unwrap(), SpotStatus::Excess);
+        assert!(spot.is_tail_ready());
+        assert_ne!(spot.anomaly_threshold(), initial);
+        assert!(spot.anomaly_threshold().is_finite());
+
+        // Without the guard every excess refits, as in the C implementation
+        let mut eager = SpotDetector::new(SpotConfig {
+            min_peaks: 0,
+            ..SpotConfig::default()
+        })
+        .unwrap();
+        eager.fit(&exponential_data(1000, 1)).unwrap();
+        eager.step(value).unwrap();
+        assert_ne!(eager.anomaly_threshold(), initial);
+    }
+
+    #[test]
+    fn test_spot_fit_too_few_excesses() {
+        // Constant data leaves the tail empty
+        let data = [1.0; 1000];
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        assert_eq!(
+            spot.fit(&data),
+            Err(SpotError::TooFewExcesses { have: 0, need: 10 })
+        );
+
+        let mut unguarded = SpotDetector::new(SpotConfig {
+            min_peaks: 0,
+            ..SpotConfig::default()
+        })
+        .unwrap();
+        assert_eq!(unguarded.fit(&data), Err(SpotError::AnomalyThresholdIsNaN));
+    }
+
+    #[test]
+    fn test_spot_merge_halves_close_to_full_fit() {
+        let config = SpotConfig {
+            level: 0.99,
+            ..SpotConfig::default()
+        };
+        let data = exponential_data(20_000, 8);
+        let (left, right) = data.split_at(10_000);
+
+        let mut full = SpotDetector::new(config.clone()).unwrap();
+        full.fit(&data).unwrap();
+        let mut merged = SpotDetector::new(config.clone()).unwrap();
+        merged.fit(left).unwrap();
+        let mut shard = SpotDetector::new(config.clone()).unwrap();
+        shard.fit(right).unwrap();
+
+        let thresholds = (merged.excess_threshold(), shard.excess_threshold());
+        merged.merge(&shard).unwrap();
+
+        assert_eq!(merged.n(), full.n());
+        assert_eq!(merged.excess_threshold(), thresholds.0.max(thresholds.1));
+        assert!(merged.tail_size() <= 200);
+        let nt_diff = merged.nt().abs_diff(full.nt()) as f64;
+        assert!(
+            nt_diff <= 0.1 * full.nt() as f64,
+            "{} vs {}",
+            merged.nt(),
+            full.nt()
+        );
+        assert_relative_eq!(
+            merged.anomaly_threshold(),
+            full.anomaly_threshold(),
+            max_relative = 0.05
+        );
+
+        let mut other = SpotDetector::new(SpotConfig {
+            q: 1e-3,
+            ..config.clone()
+        })
+        .unwrap();
+        other.fit(right).unwrap();
+        assert_eq!(merged.merge(&other), Err(SpotError::ConfigMismatch));
+
+        // Options that only change the updates may differ
+        let mut relaxed = SpotDetector::new(SpotConfig {
+            discard_anomalies: !config.discard_anomalies,
+            ..config.clone()
+        })
+        .unwrap();
+        relaxed.fit(right).unwrap();
+        let mut target = merged.clone();
+        target.merge(&relaxed).unwrap();
+        assert_eq!(target.config(), merged.config());
+
+        let unfitted = SpotDetector::new(config).unwrap();
+        assert_eq!(
+            merged.merge(&unfitted),
+            Err(SpotError::ExcessThresholdIsNaN)
+        );
+    }
+
+    #[test]
+    fn test_spot_threshold_interval_widens_with_fewer_excesses() {
+        let config = SpotConfig {
+            level: 0.99,
+            max_excess: 1000,
+            ..SpotConfig::default()
+        };
+        let mut widths = Vec::new();
+        for size in [50_000, 5_000] {
+            let mut spot = SpotDetector::new(config.clone()).unwrap();
+            spot.fit(&exponential_data(size, 9)).unwrap();
+
+            let (lower, point, upper) = spot.threshold_interval(1e-4, 0.95);
+            assert_eq!(point, spot.quantile(1e-4));
+            assert!(lower < point && point < upper, "{lower} {point} {upper}");
+            let (narrow_lower, _, narrow_upper) = spot.threshold_interval(1e-4, 0.5);
+            assert!(lower < narrow_lower && narrow_upper < upper);
+            widths.push(upper - lower);
+
+            let (lower, point, upper) = spot.threshold_interval(1e-4, 1.0);
+            assert!(lower.is_nan() && upper.is_nan() && point.is_finite());
+        }
+        assert!(widths[1] > 2.0 * widths[0], "{widths:?}");
+
+        let unfitted = SpotDetector::new(config).unwrap();
+        let (lower, point, upper) = unfitted.threshold_interval(1e-4, 0.95);
+        assert!(lower.is_nan() && point.is_nan() && upper.is_nan());
+    }
+
+    #[test]
+    fn test_spot_prune_excesses_below_moves_gamma_to_tail_index() {
+        // GPD data with tail index 0.5, so the excesses follow the same GPD
+        let gamma = 0.5;
+        let data: Vec<f64> = exponential_data(8_000, 17)
+            .into_iter()
+            .map(|e| ((gamma * e).exp() - 1.0) / gamma)
+            .collect();
+        let config = SpotConfig {
+            level: 0.9,
+            max_excess: 2000,
+            ..SpotConfig::default()
+        };
+        let mut spot = SpotDetector::new(config).unwrap();
+        spot.fit(&data).unwrap();
+        let (gamma_fit, _) = spot.tail_parameters();
+
+        // A stale regime hovering just above the excess threshold fills half
+        // of the tail with tiny excesses
+        let et = spot.excess_threshold();
+        let stale = spot.tail.size();
+        for i in 0..stale {
+            let status = spot.step(et + 1e-3 * (i % 10 + 1) as f64).unwrap();
+            assert_eq!(status, SpotStatus::Excess);
+        }
+        let (gamma_stale, _) = spot.tail_parameters();
+        let (n, nt) = (spot.n(), spot.nt());
+
+        // Pruning the smallest half of the tail drops the stale excesses
+        let removed = spot.prune_excesses_below(0.011);
+        assert!(
+            removed >= stale && removed < stale + stale / 50,
+            "{removed}"
+        );
+        assert_eq!(spot.tail.size(), 2 * stale - removed);
+        assert_eq!((spot.n(), spot.nt()), (n, nt));
+        let (gamma_pruned, _) = spot.tail_parameters();
+        assert!(
+            (gamma_pruned - gamma).abs() < (gamma_stale - gamma).abs(),
+            "{gamma_fit} {gamma_stale} {gamma_pruned}"
+        );
+        assert!((gamma_pruned - gamma_fit).abs() < 0.02);
+        assert_eq!(spot.anomaly_threshold(), spot.quantile(1e-4));
+
+        assert_eq!(spot.prune_excesses_below(0.0), 0);
+    }
+
+    #[test]
+    fn test_spot_warmup_suppresses_anomalies() {
+        let config = SpotConfig {
+            level: 0.98,
+            q: 1e-3,
+            ..SpotConfig::default()
+        };
+        let training = exponential_data(2000, 5);
+        let mut stream = exponential_data(300, 6);
+        stream[10] = 1e3;
+        stream[200] = 1e6;
+
+        let run = |warmup_steps: usize| {
+            let mut spot = SpotDetector::new(SpotConfig {
+                warmup_steps,
+                ..config.clone()
+            })
+            .unwrap();
+            assert!(!spot.in_warmup());
+            spot.fit(&training).unwrap();
+            let statuses: Vec<_> = stream
+                .iter()
+                .enumerate()
+                .map(|(i, &x)| {
+                    assert_eq!(spot.in_warmup(), i < warmup_steps);
+                    spot.step(x).unwrap()
+                })
+                .collect();
+            (spot, statuses)
+        };
+
+        let (plain, plain_statuses) = run(0);
+        assert_eq!(plain_statuses[10], SpotStatus::Anomaly);
+        assert_eq!(plain_statuses[200], SpotStatus::Anomaly);
+
+        let (mut warm, statuses) = run(100);
+        assert!(statuses[..100].iter().all(|&s| s != SpotStatus::Anomaly));
+        // The spike joined the tail instead of being discarded
+        assert_eq!(statuses[10], SpotStatus::Excess);
+        assert_eq!(warm.n(), plain.n() + 1);
+        assert_eq!(statuses[200], SpotStatus::Anomaly);
+        assert!(!warm.in_warmup());
+
+        // A new fit starts a new warmup, a reset ends it
+        warm.fit(&training).unwrap();
+        assert!(warm.in_warmup());
+        warm.reset();
+        assert!(!warm.in_warmup());
+    }
+
+    #[test]
+    fn test_spot_try_fit_reports_failure_state() {
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        let failure = spot.try_fit(&[3.0; 500]).unwrap_err();
+        assert_eq!(failure.excess_threshold, 3.0);
+        assert_eq!(failure.nt, 0);
+        assert_eq!(
+            failure.error,
+            SpotError::TooFewExcesses { have: 0, need: 10 }
+        );
+        assert_eq!(
+            failure.to_string(),
+            "The tail holds too few excesses to be fitted (0 < 10) (excess threshold 3, 0 excesses)"
+        );
+        assert_eq!(SpotError::from(failure), failure.error);
+
+        let failure = spot.try_fit(&[f64::NAN; 10]).unwrap_err();
+        assert!(failure.excess_threshold.is_nan());
+        assert_eq!(failure.error, SpotError::ExcessThresholdIsNaN);
+
+        let data = exponential_data(2000, 3);
+        assert_eq!(spot.try_fit(&data), Ok(()));
+        let mut reference = SpotDetector::new(SpotConfig::default()).unwrap();
+        reference.fit(&data).unwrap();
+        assert_eq!(spot.anomaly_threshold(), reference.anomaly_threshold());
+    }
+
+    #[test]
+    fn test_spot_on_threshold_update_fires_on_changes() {
+        use std::sync::{Arc, Mutex};
+
+        let mut spot = SpotDetector::new(SpotConfig {
+            level: 0.98,
+            ..SpotConfig::default()
+        })
+        .unwrap();
+        spot.fit(&exponential_data(2000, 4)).unwrap();
+
+        let updates = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&updates);
+        spot.on_threshold_update(Box::new(move |old, new| {
+            sink.lock().unwrap().push((old, new))
+        }));
+
+        let mut expected = Vec::new();
+        for x in exponential_data(3000, 5) {
+            let before = spot.anomaly_threshold();
+            let status = spot.step(x).unwrap();
+            let after = spot.anomaly_threshold();
+            if before != after {
+                assert_eq!(status, SpotStatus::Excess);
+                expected.push((before, after));
+            }
+        }
+        assert!(expected.len() > 10);
+        assert_eq!(*updates.lock().unwrap(), expected);
+
+        // Refits outside of `step` do not fire it
+        spot.fit(&exponential_data(2000, 6)).unwrap();
+        assert_eq!(updates.lock().unwrap().len(), expected.len());
+
+        // The setters do, once per change
+        let before = spot.anomaly_threshold();
+        spot.set_q(1e-5).unwrap();
+        expected.push((before, spot.anomaly_threshold()));
+        assert_eq!(spot.set_q(f64::NAN), Err(SpotError::QOutOfBounds));
+        let before = spot.anomaly_threshold();
+        spot.set_excess_threshold(spot.excess_threshold() + 0.5)
+            .unwrap();
+        expected.push((before, spot.anomaly_threshold()));
+        spot.set_q(1e-5).unwrap();
+        assert_eq!(*updates.lock().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_spot_anomaly_score() {
+        let unfitted = SpotDetector::new(SpotConfig::default()).unwrap();
+        assert!(unfitted.anomaly_score(1.0).is_nan());
+
+        let data = exponential_data(5000, 8);
+        for low_tail in [false, true] {
+            let config = SpotConfig {
+                level: 0.98,
+                low_tail,
+                ..SpotConfig::default()
+            };
+            let sign = if low_tail { -1.0 } else { 1.0 };
+            let mut spot = SpotDetector::new(config).unwrap();
+            let mirrored: Vec<f64> = data.iter().map(|x| sign * x).collect();
+            spot.fit(&mirrored).unwrap();
+
+            let et = spot.excess_threshold();
+            assert_eq!(spot.anomaly_score(et - sign * 0.5), 0.0);
+            assert_relative_eq!(
+                spot.anomaly_score(spot.anomaly_threshold()),
+                1.0 - 1e-4,
+                epsilon = 1e-9
+            );
+            assert!(spot.anomaly_score(f64::NAN).is_nan());
+
+            let mut previous = 0.0;
+            for i in 0..200 {
+                let score = spot.anomaly_score(et + sign * i as f64 * 0.1);
+                assert!(score >= previous && score <= 1.0, "{i}: {score}");
+                previous = score;
+            }
+            assert!(previous > 0.999_999);
+        }
+    }
+
+    #[test]
+    fn test_spot_fit_from_quantile_matches_p2_fit() {
+        let config = SpotConfig {
+            level: 0.98,
+            ..SpotConfig::default()
+        };
+        let data = exponential_data(20_000, 12);
+        let mut sorted = data.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let exact = sorted[(0.98 * data.len() as f64) as usize];
+
+        let mut p2 = SpotDetector::new(config.clone()).unwrap();
+        p2.fit(&data).unwrap();
+        let mut spot = SpotDetector::new(config.clone()).unwrap();
+        spot.fit_from_quantile(&data, exact).unwrap();
+
+        assert_eq!(spot.excess_threshold(), exact);
+        assert_eq!(spot.nt(), data.iter().filter(|&&x| x > exact).count());
+        assert_eq!(spot.n(), data.len());
+        assert_relative_eq!(
+            spot.excess_threshold(),
+            p2.excess_threshold(),
+            max_relative = 0.02
+        );
+        assert_relative_eq!(
+            spot.anomaly_threshold(),
+            p2.anomaly_threshold(),
+            max_relative = 0.02
+        );
+
+        for bad in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let mut other = SpotDetector::new(config.clone()).unwrap();
+            assert_eq!(
+                other.fit_from_quantile(&data, bad),
+                Err(SpotError::ExcessThresholdIsNaN)
+            );
+            assert_eq!(other.n(), 0);
+        }
+    }
+
+    #[test]
+    fn test_spot_is_drifting_after_shift() {
+        let config = SpotConfig {
+            level: 0.98,
+            discard_anomalies: false,
+            ..SpotConfig::default()
+        };
+        let mut spot = SpotDetector::new(config).unwrap();
+        assert!(spot.excess_rate().is_nan());
+        assert!(!spot.is_drifting(0.005));
+
+        let data = exponential_data(10_000, 3);
+        spot.fit(&data[..5_000]).unwrap();
+        for &x in &data[5_000..] {
+            spot.step(x).unwrap();
+        }
+        assert_relative_eq!(spot.expected_excess_rate(), 0.02, epsilon = 1e-12);
+        assert!((spot.excess_rate() - 0.02).abs() < 0.005);
+        assert!(!spot.is_drifting(0.005));
+
+        // Doubling the values puts about 14% of them over the threshold
+        for x in exponential_data(5_000, 4) {
+            spot.step(2.0 * x).unwrap();
+        }
+        assert!(spot.excess_rate() > 0.04);
+        assert!(spot.is_drifting(0.005));
+    }
+
+    #[test]
+    fn test_spot_runtime_parameters() {
+        let config = SpotConfig {
+            level: 0.98,
+            q: 1e-3,
+            ..SpotConfig::default()
+        };
+        let mut spot = SpotDetector::new(config).unwrap();
+        spot.set_max_excess(500).unwrap();
+        spot.set_level(0.99).unwrap();
+        assert_eq!(spot.set_level(1.0), Err(SpotError::LevelOutOfBounds));
+        assert_eq!(spot.set_level(0.9995), Err(SpotError::QOutOfBounds));
+
+        let data = exponential_data(10_000, 5);
+        spot.fit(&data).unwrap();
+        assert_eq!(spot.config().unwrap().max_excess, 500);
+        assert_eq!(spot.config().unwrap().level, 0.99);
+
+        // Lowering q raises the threshold right away
+        let threshold = spot.anomaly_threshold();
+        spot.set_q(1e-5).unwrap();
+        assert!(spot.anomaly_threshold() > threshold);
+        assert_eq!(spot.anomaly_threshold(), spot.quantile(1e-5));
+        assert_eq!(spot.set_q(0.5), Err(SpotError::QOutOfBounds));
+        let threshold = spot.anomaly_threshold();
+        assert_eq!(spot.set_q(f64::NAN), Err(SpotError::QOutOfBounds));
+        assert_eq!(spot.anomaly_threshold(), threshold);
+        assert_eq!(spot.config().unwrap().q, 1e-5);
+
+        // The level and the buffer size need a refit
+        assert_eq!(spot.set_level(0.95), Err(SpotError::AlreadyFitted));
+        assert_eq!(spot.set_max_excess(100), Err(SpotError::AlreadyFitted));
+
+        let anomaly = spot.anomaly_threshold() + 1.0;
+        assert_eq!(spot.step(anomaly), Ok(SpotStatus::Anomaly));
+        let n = spot.n();
+        spot.set_discard_anomalies(false);
+        assert_eq!(spot.step(anomaly), Ok(SpotStatus::Excess));
+        assert_eq!(spot.n(), n + 1);
+
+        spot.reset();
+        spot.set_level(0.95).unwrap();
+    }
+
+    #[test]
+    fn test_spot_partial_eq() {
+        let config = SpotConfig {
+            level: 0.98,
+            ..SpotConfig::default()
+        };
+        let mut a = SpotDetector::new(config.clone()).unwrap();
+        let mut b = SpotDetector::new(config.clone()).unwrap();
+        // Unfitted detectors have NaN thresholds and still compare equal
+        assert!(a.anomaly_threshold().is_nan());
+        assert_eq!(a, b);
+        assert_ne!(a, SpotDetector::new(SpotConfig::default()).unwrap());
+
+        let data = exponential_data(5_000, 9);
+        a.fit(&data).unwrap();
+        assert_ne!(a, b);
+        b.fit(&data).unwrap();
+        assert_eq!(a, b);
+
+        a.step(5.0).unwrap();
+        b.step(5.0 + 1e-9).unwrap();
+        assert_ne!(a, b);
+        assert!(spot_approx_eq(&a, &b, 1e-6));
+        assert!(!spot_approx_eq(&a, &b, 1e-12));
+    }
+
+    #[test]
+    fn test_spot_fit_streaming_matches_fit() {
+        let data = exponential_data(100_000, 21);
+        for low_tail in [false, true] {
+            let config = SpotConfig {
+                low_tail,
+                ..SpotConfig::default()
+            };
+            let mut spot = SpotDetector::new(config.clone()).unwrap();
+            spot.fit(&data).unwrap();
+
+            // Exact with the right hint and with a hint off by a third
+            for hint in [data.len(), 2 * data.len() / 3] {
+                let mut streamed = SpotDetector::new(config.clone()).unwrap();
+                streamed.fit_streaming(data.iter().copied(), hint).unwrap();
+                assert_eq!(streamed, spot);
+                assert_eq!(streamed.anomaly_threshold(), spot.anomaly_threshold());
+            }
+        }
+    }
+
+    #[test]
+    fn test_spot_fit_streaming_matches_fit_with_nan_and_repeated_values() {
+        // Mostly one value, so that the fallback of `fit` moves the threshold
+        // to 1.6 and the 900 copies of 5.0 outnumber the kept candidates
+        let repeated: Vec<f64> = (0..1000)
+            .map(|i| {
+                if i % 10 < 9 {
+                    5.0
+                } else {
+                    1.0 + (i % 7) as f64 * 0.1
+                }
+            })
+            .collect();
+        for low_tail in [false, true] {
+            let sign = if low_tail { -1.0 } else { 1.0 };
+            let data: Vec<f64> = repeated.iter().map(|x| sign * x).collect();
+            let config = SpotConfig {
+                low_tail,
+                ..SpotConfig::default()
+            };
+            let mut spot = SpotDetector::new(config.clone()).unwrap();
+            spot.fit(&data).unwrap();
+            assert_eq!(spot.excess_threshold(), sign * 1.6);
+
+            for hint in [data.len(), 100] {
+                let mut streamed = SpotDetector::new(config.clone()).unwrap();
+                streamed.fit_streaming(data.iter().copied(), hint).unwrap();
+                assert_eq!(streamed, spot);
+                assert_eq!(streamed.anomaly_threshold(), spot.anomaly_threshold());
+            }
+        }
+
+        // NaN values count in `n` without being excesses, and a leading one
+        // makes the P2 estimate NaN, in both
+        let mut data = exponential_data(20_000, 22);
+        for nan_index in [10_000, 0] {
+            data[nan_index] = f64::NAN;
+            let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+            let mut streamed = spot.clone();
+            assert_eq!(
+                streamed.fit_streaming(data.iter().copied(), data.len()),
+                spot.fit(&data)
+            );
+            assert_eq!(streamed, spot);
+            assert_eq!(streamed.n(), data.len());
+        }
+        assert!(SpotDetector::new(SpotConfig::default())
+            .unwrap()
+            .fit(&data)
+            .is_err());
+    }
+
+    #[test]
+    fn test_spot_quantile_components() {
+        let data = exponential_data(20_000, 8);
+        for low_tail in [false, true] {
+            let config = SpotConfig {
+                low_tail,
+                ..SpotConfig::default()
+            };
+            let mut spot = SpotDetector::new(config).unwrap();
+            assert!(spot.quantile_components(1e-4).z.is_nan());
+            spot.fit(&data).unwrap();
+
+            for q in [1e-3, 1e-4, 1e-6] {
+                let breakdown = spot.quantile_components(q);
+                assert_eq!(breakdown.z, spot.quantile(q));
+                assert_eq!(breakdown.s, spot.nt() as f64 / spot.n() as f64);
+                assert_eq!(breakdown.s, spot.tail_ratio());
+                assert_eq!(breakdown.r, q / breakdown.s);
+                assert_eq!(breakdown.excess_threshold, spot.excess_threshold());
+                let sign = if low_tail { -1.0 } else { 1.0 };
+                assert_eq!(
+                    breakdown.z,
+                    breakdown.excess_threshold + sign * breakdown.tail_quantile
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_spot_infinite_values() {
+        let data = exponential_data(5_000, 12);
+        let mut with_inf = data.clone();
+        with_inf.insert(100, f64::INFINITY);
+        with_inf.insert(200, f64::NEG_INFINITY);
+
+        // Rejected by default, leaving the detector unfitted
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        assert_eq!(spot.fit(&with_inf), Err(SpotError::DataIsInfinite));
+        assert_eq!(
+            spot.fit_streaming(with_inf.iter().copied(), with_inf.len()),
+            Err(SpotError::DataIsInfinite)
+        );
+        assert!(spot.excess_threshold().is_nan());
+        spot.fit(&data).unwrap();
+        assert_eq!(spot.step(f64::INFINITY), Err(SpotError::DataIsInfinite));
+        assert_eq!(spot.step(f64::NEG_INFINITY), Err(SpotError::DataIsInfinite));
+
+        // Accepted but kept out of the model when the check is disabled
+        let config = SpotConfig {
+            reject_infinite: false,
+            discard_anomalies: false,
+            ..SpotConfig::default()
+        };
+        let mut lenient = SpotDetector::new(config.clone()).unwrap();
+        lenient.fit(&with_inf).unwrap();
+        let mut reference = SpotDetector::new(config).unwrap();
+        reference.fit(&data).unwrap();
+        assert_eq!(lenient, reference);
+
+        assert_eq!(lenient.step(f64::INFINITY), Ok(SpotStatus::Anomaly));
+        assert_eq!(lenient.step(f64::NEG_INFINITY), Ok(SpotStatus::Normal));
+        assert_eq!(lenient, reference);
+        assert!(lenient.peaks_max().is_finite());
+        assert!(lenient.anomaly_threshold().is_finite());
+    }
+
+    #[test]
+    fn test_spot_clone_is_independent() {
+        let config = SpotConfig {
+            level: 0.98,
+            discard_anomalies: false,
+            ..SpotConfig::default()
+        };
+        let mut spot = SpotDetector::new(config).unwrap();
+        spot.fit(&exponential_data(5_000, 14)).unwrap();
+        spot.on_threshold_update(Box::new(|_, _| {}));
+
+        let mut copy = spot.clone();
+        assert_eq!(copy, spot);
+        assert!(copy.threshold_hook.0.is_none());
+
+        for (x, y) in exponential_data(500, 15)
+            .into_iter()
+            .zip(exponential_data(500, 16))
+        {
+            spot.step(x).unwrap();
+            copy.step(3.0 * y).unwrap();
+        }
+        assert_ne!(copy.anomaly_threshold(), spot.anomaly_threshold());
+        assert_ne!(copy.tail_parameters(), spot.tail_parameters());
+        assert_ne!(copy.peaks_data(), spot.peaks_data());
+    }
+
+    #[test]
+    fn test_spot_adaptive_tail_grows_on_high_excess_stream() {
+        let detector = |min_excess_rate| {
+            let config = SpotConfig {
+                level: 0.9,
+                q: 1e-3,
+                max_excess: 50,
+                adaptive_tail: Some(AdaptiveTail {
+                    max_excess: 300,
+                    min_excess_rate,
+                }),
+                ..SpotConfig::default()
+            };
+            let mut spot = SpotDetector::new(config).unwrap();
+            spot.fit(&exponential_data(2_000, 3)).unwrap();
+            assert_eq!(spot.tail().peaks().capacity(), 50);
+            for x in exponential_data(5_000, 5) {
+                spot.step(x).unwrap();
+            }
+            spot
+        };
+
+        // About 10% of the values are excesses: the buffer doubles up to the ceiling
+        let adaptive = detector(0.05);
+        assert_eq!(adaptive.tail().peaks().capacity(), 300);
+        assert_eq!(adaptive.tail_size(), 300);
+        assert_eq!(adaptive.config().unwrap().max_excess, 50);
+        assert!(adaptive.is_compatible_with(&detector(0.5).config().unwrap()));
+
+        // Below the rate threshold the buffer keeps its size
+        let fixed = detector(0.5);
+        assert_eq!(fixed.tail().peaks().capacity(), 50);
+        assert_eq!(fixed.tail_size(), 50);
+    }
+
+    #[test]
+    fn test_spot_fitted_matches_new_then_fit() {
+        let config = SpotConfig {
+            level: 0.99,
+            ..SpotConfig::default()
+        };
+        let data = exponential_data(5_000, 23);
+        let spot = SpotDetector::fitted(config.clone(), &data).unwrap();
+        let mut reference = SpotDetector::new(config).unwrap();
+        reference.fit(&data).unwrap();
+        assert_eq!(spot.anomaly_threshold(), reference.anomaly_threshold());
+        assert_eq!(spot.excess_threshold(), reference.excess_threshold());
+        assert_eq!(spot, reference);
+
+        let invalid = SpotConfig {
+            q: 0.0,
+            ..SpotConfig::default()
+        };
+        assert_eq!(
+            SpotDetector::fitted(invalid, &data).unwrap_err(),
+            SpotError::QOutOfBounds
+        );
+        assert_eq!(
+            SpotDetector::fitted(SpotConfig::default(), &[1.0, f64::INFINITY]).unwrap_err(),
+            SpotError::DataIsInfinite
+        );
+    }
+
+    #[test]
+    fn test_spot_fit_weighted() {
+        let config = SpotConfig {
+            level: 0.99,
+            ..SpotConfig::default()
+        };
+        let data = exponential_data(10_000, 29);
+        let mut reference = SpotDetector::new(config.clone()).unwrap();
+        reference.fit(&data).unwrap();
+
+        // Uniform weights reproduce the unweighted fit, whatever their scale
+        for weight in [1.0, 0.25, 3.0] {
+            let mut spot = SpotDetector::new(config.clone()).unwrap();
+            spot.fit_weighted(&data, &vec![weight; data.len()]).unwrap();
+            assert_eq!((spot.n(), spot.nt()), (reference.n(), reference.nt()));
+            assert_eq!(spot.excess_threshold(), reference.excess_threshold());
+            assert_relative_eq!(
+                spot.anomaly_threshold(),
+                reference.anomaly_threshold(),
+                max_relative = 1e-9
+            );
+        }
+
+        // Doubling the weight of the excesses doubles their share of the data
+        // without changing the tail shape, which moves the threshold outwards
+        let t = reference.excess_threshold();
+        let weights: Vec<f64> = data
+            .iter()
+            .map(|&x| if x > t { 2.0 } else { 1.0 })
+            .collect();
+        let mut heavier = SpotDetector::new(config.clone()).unwrap();
+        heavier.fit_weighted(&data, &weights).unwrap();
+        let (n, nt) = (data.len() as f64, reference.nt() as f64);
+        assert_eq!(heavier.nt(), (2.0 * nt * n / (n + nt)).round() as usize);
+        let ((gamma, sigma), (ref_gamma, ref_sigma)) =
+            (heavier.tail_parameters(), reference.tail_parameters());
+        assert_relative_eq!(gamma, ref_gamma, max_relative = 1e-6);
+        assert_relative_eq!(sigma, ref_sigma, max_relative = 1e-6);
+        assert!(heavier.anomaly_threshold() > reference.anomaly_threshold());
+
+        // Doubling the weight of the largest excesses stretches the tail
+        let median = reference.tail().peaks().quantile(0.5) + t;
+        let weights: Vec<f64> = data
+            .iter()
+            .map(|&x| if x > median { 2.0 } else { 1.0 })
+            .collect();
+        let mut stretched = SpotDetector::new(config.clone()).unwrap();
+        stretched.fit_weighted(&data, &weights).unwrap();
+        assert_eq!(
+            stretched.tail().peaks().mean(),
+            reference.tail().peaks().mean()
+        );
+        let gpd_mean = |(gamma, sigma): (f64, f64)| sigma / (1.0 - gamma);
+        assert!(gpd_mean(stretched.tail_parameters()) > gpd_mean(reference.tail_parameters()));
+        assert!(stretched.anomaly_threshold() > reference.anomaly_threshold());
+
+        let mut spot = SpotDetector::new(config).unwrap();
+        for weights in [
+            vec![1.0; data.len() - 1],
+            vec![0.0; data.len()],
+            [vec![-1.0], vec![1.0; data.len() - 1]].concat(),
+            [vec![f64::NAN], vec![1.0; data.len() - 1]].concat(),
+        ] {
+            assert_eq!(
+                spot.fit_weighted(&data, &weights),
+                Err(SpotError::InvalidWeights)
+            );
+        }
+        assert!(spot.excess_threshold().is_nan());
+    }
+
+    #[test]
+    #[cfg(feature = "rand")]
+    fn test_spot_sample_tail_matches_gpd_mean() {
+        use rand_core::SeedableRng;
+        use rand_xorshift::XorShiftRng;
+
+        let mut rng = XorShiftRng::seed_from_u64(42);
+        let unfitted = SpotDetector::new(SpotConfig::default()).unwrap();
+        assert!(unfitted.sample_tail(&mut rng, 10).is_empty());
+
+        for low_tail in [false, true] {
+            let config = SpotConfig {
+                level: 0.98,
+                low_tail,
+                ..SpotConfig::default()
+            };
+            let sign = if low_tail { -1.0 } else { 1.0 };
+            let data: Vec<f64> = exponential_data(20_000, 31)
+                .iter()
+                .map(|x| sign * x)
+                .collect();
+            let spot = SpotDetector::fitted(config, &data).unwrap();
+            let (gamma, sigma) = spot.tail_parameters();
+            assert!(gamma < 0.5, "gamma = {}", gamma);
+
+            let samples = spot.sample_tail(&mut rng, 100_000);
+            assert_eq!(samples.len(), 100_000);
+            let t = spot.excess_threshold();
+            let excesses: Vec<f64> = samples.iter().map(|&x| sign * (x - t)).collect();
+            assert!(excesses.iter().all(|&y| y >= 0.0));
+            let mean = excesses.iter().sum::<f64>() / excesses.len() as f64;
+            assert_relative_eq!(mean, sigma / (1.0 - gamma), max_relative = 0.02);
+        }
+    }
+
+    #[test]
+    fn test_spot_health() {
+        let config = SpotConfig {
+            level: 0.98,
+            max_excess: 50,
+            ..SpotConfig::default()
+        };
+        let mut spot = SpotDetector::new(config.clone()).unwrap();
+        let health = spot.health();
+        assert!(!health.fitted && !health.thresholds_finite && !health.is_healthy());
+        assert!(!health.gamma_in_range && !health.sigma_positive);
+
+        spot.fit(&exponential_data(5000, 11)).unwrap();
+        assert!(spot.health().is_healthy());
+
+        // Poison sigma: only that check fails
+        let peaks = spot.tail.peaks().clone();
+        spot.tail.restore(peaks, spot.tail.gamma(), 0.0);
+        let health = spot.health();
+        assert!(!health.sigma_positive && !health.is_healthy());
+        assert!(health.fitted && health.thresholds_finite);
+        assert!(health.gamma_in_range && health.tail_full);
+
+        spot.reset();
+        assert_eq!(
+            spot.health(),
+            SpotDetector::new(config.clone()).unwrap().health()
+        );
+
+        // A grown adaptive tail is healthy before it fills up again
+        let mut adaptive = SpotDetector::new(SpotConfig {
+            level: 0.9,
+            adaptive_tail: Some(AdaptiveTail {
+                max_excess: 400,
+                min_excess_rate: 0.05,
+            }),
+            ..config
+        })
+        .unwrap();
+        adaptive.fit(&exponential_data(2_000, 3)).unwrap();
+        for x in exponential_data(5_000, 5) {
+            adaptive.step(x).unwrap();
+            if adaptive.tail().peaks().capacity() > 50 {
+                break;
+            }
+        }
+        let health = adaptive.health();
+        assert!(health.adaptive_tail && !health.tail_full);
+        assert!(health.is_healthy());
+    }
+
+    #[test]
+    fn test_spot_step_or_skip_ignores_nan() {
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        spot.fit(&exponential_data(5000, 3)).unwrap();
+        let mut reference = spot.clone();
+
+        let stream = exponential_data(1000, 4);
+        let mut nan_count = 0;
+        for (i, &x) in stream.iter().enumerate() {
+            if i % 7 == 0 {
+                assert_eq!(spot.step_or_skip(f64::NAN), None);
+                nan_count += 1;
+            }
+            assert_eq!(spot.step_or_skip(x), Some(reference.step(x)));
+        }
+
+        assert_eq!(spot.n(), reference.n());
+        assert_eq!(spot.skipped_count(), nan_count);
+        assert_eq!(spot.anomaly_threshold(), reference.anomaly_threshold());
+        assert_eq!(spot.tail_parameters(), reference.tail_parameters());
+
+        spot.reset();
+        assert_eq!(spot.skipped_count(), 0);
+    }
+
+    #[test]
+    fn test_spot_observed_anomaly_rate_tracks_q() {
+        // Keep the thresholds of the fit: since anomalies are discarded, the
+        // refits only see the excesses below the anomaly threshold and shrink it
+        let config = SpotConfig {
+            q: 0.002,
+            level: 0.98,
+            min_peaks: usize::MAX,
+            anomaly_rate_window: 50_000,
+            ..SpotConfig::default()
+        };
+        let mut spot = SpotDetector::new(config).unwrap();
+        assert!(spot.observed_anomaly_rate().is_nan());
+
+        spot.fit(&exponential_data(20_000, 5)).unwrap();
+        assert!(spot.observed_anomaly_rate().is_nan());
+
+        let stream = exponential_data(200_000, 6);
+        let mut anomalies = 0;
+        for (i, &x) in stream.iter().enumerate() {
+            if spot.step(x).unwrap() == SpotStatus::Anomaly && i >= 150_000 {
+                anomalies += 1;
+            }
+        }
+        let rate = spot.observed_anomaly_rate();
+        assert_eq!(rate, anomalies as f64 / 50_000.0);
+        assert!((rate - 0.002).abs() < 0.0006, "rate = {rate}");
+        assert_eq!(spot.calibration_error(), rate - 0.002);
+
+        // A refit starts a new window
+        spot.fit(&exponential_data(20_000, 7)).unwrap();
+        assert!(spot.observed_anomaly_rate().is_nan());
+        spot.step(1e6).unwrap();
+        assert_eq!(spot.observed_anomaly_rate(), 1.0);
+
+        // Disabled by default
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        spot.fit(&stream).unwrap();
+        spot.step(1.0).unwrap();
+        assert!(spot.calibration_error().is_nan());
+    }
+
+    #[test]
+    fn test_spot_observed_anomaly_rate_rises_with_default_config() {
+        let config = SpotConfig {
+            anomaly_rate_window: 100_000,
+            ..SpotConfig::default()
+        };
+        let q = config.q;
+        let mut spot = SpotDetector::new(config).unwrap();
+        spot.fit(&exponential_data(20_000, 8)).unwrap();
+
+        let stream = exponential_data(200_000, 9);
+        let mut statuses = Vec::with_capacity(stream.len());
+        let mut rates = Vec::new();
+        for (i, &x) in stream.iter().enumerate() {
+            statuses.push(spot.step(x).unwrap());
+            if (i + 1) % 100_000 == 0 {
+                rates.push(spot.observed_anomaly_rate());
+            }
+        }
+        let anomalies = statuses[100_000..]
+            .iter()
+            .filter(|&&status| status == SpotStatus::Anomaly)
+            .count();
+        assert_eq!(rates[1], anomalies as f64 / 100_000.0);
+        // The discarded anomalies never reach the refits, so the tail shrinks
+        // and the detector flags more and more values of an unchanged stream
+        assert!(rates[0] > q && rates[1] > rates[0], "rates = {rates:?}");
+        assert!(spot.calibration_error() > q);
+    }
+
+    #[test]
+    fn test_spot_set_excess_threshold_shifts_classifications() {
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        spot.fit(&exponential_data(10_000, 8)).unwrap();
+        let (t, z) = (spot.excess_threshold(), spot.anomaly_threshold());
+        let (gamma, sigma) = spot.tail_parameters();
+        let value = (t + z) / 2.0;
+        assert_eq!(spot.predict(value), Ok(SpotStatus::Excess));
+
+        // Raising the threshold moves the anomaly threshold by the same amount
+        spot.set_excess_threshold(t + 2.0).unwrap();
+        assert_eq!(spot.excess_threshold(), t + 2.0);
+        assert_relative_eq!(spot.anomaly_threshold(), z + 2.0, epsilon = 1e-12);
+        assert_eq!(spot.tail_parameters(), (gamma, sigma));
+        assert_eq!(spot.step(value), Ok(SpotStatus::Normal));
+        assert_eq!(spot.step(z + 1.0), Ok(SpotStatus::Excess));
+
+        // Lowering it flags the same value
+        spot.set_excess_threshold(t - (z - t)).unwrap();
+        assert_eq!(spot.step(value), Ok(SpotStatus::Anomaly));
+
+        assert_eq!(
+            spot.set_excess_threshold(f64::NAN),
+            Err(SpotError::DataIsNaN)
+        );
+        assert_eq!(
+            spot.set_excess_threshold(f64::INFINITY),
+            Err(SpotError::DataIsInfinite)
+        );
+    }
+
+    #[test]
+    fn test_spot_set_excess_threshold_needs_fit() {
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        assert_eq!(spot.set_excess_threshold(1.0), Err(SpotError::NotFitted));
+        assert!(!spot.is_fitted());
+        assert!(spot.excess_threshold().is_nan());
+
+        // The model can still be configured before its first fit
+        spot.set_level(0.99).unwrap();
+        spot.set_max_excess(500).unwrap();
+        spot.fit(&exponential_data(10_000, 8)).unwrap();
+        spot.set_excess_threshold(1.0).unwrap();
+
+        spot.reset();
+        assert_eq!(spot.set_excess_threshold(1.0), Err(SpotError::NotFitted));
+    }
+
+    #[test]
+    fn test_spot_recompute_excess_threshold() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let data = exponential_data(10_000, 9);
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        spot.fit(&data).unwrap();
+        let original = spot.clone();
+        for &x in &exponential_data(1000, 10) {
+            spot.step(x).unwrap();
+        }
+
+        // Same level, same data: back to the original fit
+        spot.recompute_excess_threshold(&data, 0.998).unwrap();
+        assert_eq!(spot.excess_threshold(), original.excess_threshold());
+        assert_eq!(spot.anomaly_threshold(), original.anomaly_threshold());
+        assert_eq!(spot.tail_parameters(), original.tail_parameters());
+        assert_eq!((spot.n(), spot.nt()), (original.n(), original.nt()));
+
+        // A lower level matches a fresh fit with that level
+        spot.recompute_excess_threshold(&data, 0.99).unwrap();
+        let config = SpotConfig {
+            level: 0.99,
+            ..SpotConfig::default()
+        };
+        assert_eq!(spot.config(), Some(config.clone()));
+        let fresh = SpotDetector::fitted(config, &data).unwrap();
+        assert_eq!(spot.excess_threshold(), fresh.excess_threshold());
+        assert_eq!(spot.anomaly_threshold(), fresh.anomaly_threshold());
+        assert!(spot.excess_threshold() < original.excess_threshold());
+
+        assert_eq!(
+            spot.recompute_excess_threshold(&data, 1.0),
+            Err(SpotError::LevelOutOfBounds)
+        );
+        assert_eq!(
+            spot.recompute_excess_threshold(&data, 1.0 - 1e-5),
+            Err(SpotError::QOutOfBounds)
+        );
+        assert_eq!(spot.config().unwrap().level, 0.99);
+
+        // A failed refit leaves the level, the tail and the thresholds as they were
+        let before = spot.clone();
+        for (bad, error) in [
+            (vec![f64::NAN; 100], SpotError::ExcessThresholdIsNaN),
+            (
+                [data.clone(), vec![f64::INFINITY]].concat(),
+                SpotError::DataIsInfinite,
+            ),
+        ] {
+            assert_eq!(spot.recompute_excess_threshold(&bad, 0.98), Err(error));
+            assert_eq!(spot.config(), before.config());
+            assert_eq!(
+                spot.tail().peaks().container().data(),
+                before.tail().peaks().container().data()
+            );
+            assert_eq!(spot.tail_parameters(), before.tail_parameters());
+            assert_eq!(spot.excess_threshold(), before.excess_threshold());
+            assert_eq!(spot.anomaly_threshold(), before.anomaly_threshold());
+            assert_eq!((spot.n(), spot.nt()), (before.n(), before.nt()));
+        }
+
+        // The threshold callback survives a successful refit
+        let updates = Arc::new(AtomicUsize::new(0));
+        let sink = Arc::clone(&updates);
+        spot.on_threshold_update(Box::new(move |_, _| {
+            sink.fetch_add(1, Ordering::Relaxed);
+        }));
+        spot.recompute_excess_threshold(&data, 0.98).unwrap();
+        spot.step(spot.excess_threshold() + 1e-3).unwrap();
+        assert_eq!(updates.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_spot_fit_on_repeated_values() {
+        // 90% of the data is the same value: the P2 estimate of the excess
+        // threshold lands on it and no value is strictly above
+        let data: Vec<f64> = (0..1000)
+            .map(|i| {
+                if i % 10 < 9 {
+                    5.0
+                } else {
+                    1.0 + (i % 7) as f64 * 0.1
+                }
+            })
+            .collect();
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        spot.fit(&data).unwrap();
+        assert_eq!(spot.excess_threshold(), 1.6);
+        assert_eq!(spot.nt(), 900);
+        assert!(spot.anomaly_threshold().is_finite());
+        assert!(spot.anomaly_threshold() >= 5.0);
+        assert_eq!(spot.step(5.0), Ok(SpotStatus::Excess));
+
+        // Same on the lower tail
+        let mirrored: Vec<f64> = data.iter().map(|x| -x).collect();
+        let config = SpotConfig {
+            low_tail: true,
+            ..SpotConfig::default()
+        };
+        let mut low = SpotDetector::new(config).unwrap();
+        low.fit(&mirrored).unwrap();
+        assert_eq!(low.excess_threshold(), -1.6);
+        assert_eq!(low.nt(), 900);
+        assert!(low.anomaly_threshold().is_finite());
+
+        // Nothing to fall back on with constant data
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        assert!(spot.fit(&[5.0; 1000]).is_err());
+    }
+
+    #[test]
+    fn test_spot_fit_paths_on_repeated_values() {
+        let data: Vec<f64> = (0..1000)
+            .map(|i| {
+                if i % 10 < 9 {
+                    5.0
+                } else {
+                    1.0 + (i % 7) as f64 * 0.1
+                }
+            })
+            .collect();
+        let new_spot = || SpotDetector::new(SpotConfig::default()).unwrap();
+        let mut spot = new_spot();
+        spot.fit(&data).unwrap();
+        let fitted = |other: &SpotDetector| {
+            assert_eq!(other.excess_threshold(), 1.6);
+            assert_eq!(other.nt(), 900);
+            assert!(other.anomaly_threshold().is_finite());
+        };
+
+        // The weighted estimators give another GPD
+        let mut weighted = new_spot();
+        weighted
+            .fit_weighted(&data, &vec![1.0; data.len()])
+            .unwrap();
+        fitted(&weighted);
+
+        let mut streamed = new_spot();
+        streamed.fit_streaming(data.iter().copied(), 100).unwrap();
+        fitted(&streamed);
+        assert_eq!(streamed.anomaly_threshold(), spot.anomaly_threshold());
+
+        let mut from_quantile = new_spot();
+        from_quantile.fit_from_quantile(&data, 5.0).unwrap();
+        fitted(&from_quantile);
+        assert_eq!(from_quantile.anomaly_threshold(), spot.anomaly_threshold());
+
+        // The stored peaks are values of 5.0, so the update needs a lower one
+        let mut updated = spot.clone();
+        let mut more = vec![5.0; 9];
+        more.push(1.6);
+        updated.fit_update(&more).unwrap();
+        assert_eq!(updated.excess_threshold(), 1.6);
+        assert_eq!(updated.nt(), 909);
+
+        for path in [
+            new_spot().fit_weighted(&[5.0; 100], &[1.0; 100]),
+            new_spot().fit_streaming([5.0; 100].into_iter(), 100),
+            new_spot().fit_from_quantile(&[5.0; 100], 5.0),
+        ] {
+            assert!(path.is_err());
+        }
+    }
+
+    #[test]
+    fn test_spot_fit_with_progress_matches_fit() {
+        let data = exponential_data(20_000, 12);
+        let mut calls = Vec::new();
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        spot.fit_with_progress(&data, |processed, total| calls.push((processed, total)))
+            .unwrap();
+
+        assert_eq!(calls.last(), Some(&(40_000, 40_000)));
+        assert!(calls.len() <= 102, "{} calls", calls.len());
+        assert!(calls.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert!(calls.contains(&(20_000, 40_000)));
+
+        let mut plain = SpotDetector::new(SpotConfig::default()).unwrap();
+        plain.fit(&data).unwrap();
+        assert_eq!(spot, plain);
+
+        // Small training sets report every value
+        let mut calls = 0;
+        let mut small = SpotDetector::new(SpotConfig::default()).unwrap();
+        let _ = small.fit_with_progress(&data[..20], |_, _| calls += 1);
+        assert_eq!(calls, 40);
+    }
+
+    #[test]
+    fn test_spot_excess_threshold_error_on_uniform_data() {
+        let data: Vec<f64> = exponential_data(20_000, 13)
+            .into_iter()
+            .map(|x| (-x).exp())
+            .collect();
+
+        for low_tail in [false, true] {
+            let config = SpotConfig {
+                low_tail,
+                level: 0.98,
+                ..SpotConfig::default()
+            };
+            let mut spot = SpotDetector::new(config).unwrap();
+            assert!(spot.excess_threshold_error(&data).is_nan());
+
+            spot.fit(&data).unwrap();
+            let error = spot.excess_threshold_error(&data);
+            assert!(error < 2e-3, "low_tail = {low_tail}: error = {error}");
+            let exact = empirical_quantile(&data, spot.tail_probability());
+            assert_eq!(error, (spot.excess_threshold() - exact).abs());
+        }
+    }
+
+    #[test]
+    fn test_spot_fingerprint() {
+        let data = exponential_data(5000, 14);
+        let fitted = || SpotDetector::fitted(SpotConfig::default(), &data).unwrap();
+        let mut a = fitted();
+        let b = fitted();
+        assert_eq!(a.fingerprint(), b.fingerprint());
+
+        a.step(0.1).unwrap();
+        assert_ne!(a.fingerprint(), b.fingerprint());
+
+        // Unfitted detectors with NaN thresholds
+        let unfitted = SpotDetector::new(SpotConfig::default()).unwrap();
+        let mut reset = fitted();
+        reset.reset();
+        assert_eq!(unfitted.fingerprint(), reset.fingerprint());
+        assert_ne!(unfitted.fingerprint(), b.fingerprint());
+
+        // The configuration is part of the fingerprint
+        let other = SpotDetector::new(SpotConfig {
+            q: 1e-3,
+            ..SpotConfig::default()
+        })
+        .unwrap();
+        assert_ne!(unfitted.fingerprint(), other.fingerprint());
+
+        // Every option is, so differently configured models never share a key
+        let configs = [
+            SpotConfig {
+                fit_strategy: FitStrategy::GrimshawOnly,
+                ..SpotConfig::default()
+            },
+            SpotConfig {
+                pwm_estimator: true,
+                ..SpotConfig::default()
+            },
+            SpotConfig {
+                min_gamma: Some(-0.5),
+                ..SpotConfig::default()
+            },
+            SpotConfig {
+                grimshaw_options: Some(GrimshawOptions::default()),
+                ..SpotConfig::default()
+            },
+            SpotConfig {
+                adaptive_tail: Some(AdaptiveTail {
+                    max_excess: 400,
+                    min_excess_rate: 0.0,
+                }),
+                ..SpotConfig::default()
+            },
+            SpotConfig {
+                reject_infinite: false,
+                ..SpotConfig::default()
+            },
+            SpotConfig {
+                track_excess_threshold: true,
+                ..SpotConfig::default()
+            },
+            SpotConfig {
+                anomaly_rate_window: 100,
+                ..SpotConfig::default()
+            },
+            SpotConfig {
+                stable_small_gamma: true,
+                ..SpotConfig::default()
+            },
+        ];
+        let mut fingerprints: Vec<u64> = configs
+            .into_iter()
+            .map(|config| SpotDetector::new(config).unwrap().fingerprint())
+            .collect();
+        fingerprints.push(unfitted.fingerprint());
+        fingerprints.sort_unstable();
+        fingerprints.dedup();
+        assert_eq!(fingerprints.len(), 10);
+
+        // The configured capacity is hashed, not the grown one
+        let mut grown = b.clone();
+        grown.tail.grow(2 * SpotConfig::default().max_excess);
+        assert_eq!(grown.fingerprint(), b.fingerprint());
+    }
+
+    #[test]
+    fn test_spot_step_unchecked_matches_step() {
+        let config = SpotConfig {
+            anomaly_rate_window: 100,
+            ..SpotConfig::default()
+        };
+        let data = exponential_data(20_000, 3);
+        let mut checked = SpotDetector::new(config.clone()).unwrap();
+        let mut unchecked = SpotDetector::new(config).unwrap();
+        checked.fit(&data[..5_000]).unwrap();
+        unchecked.fit(&data[..5_000]).unwrap();
+
+        for &x in data[5_000..].iter().chain(&[50.0, -1.0]) {
+            assert_eq!(unchecked.step_unchecked(x), checked.step(x).unwrap());
+        }
+        assert_eq!(unchecked.anomaly_threshold(), checked.anomaly_threshold());
+        assert_eq!(unchecked.n(), checked.n());
+        assert_eq!(
+            unchecked.observed_anomaly_rate(),
+            checked.observed_anomaly_rate()
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "non-finite")]
+    fn test_spot_step_unchecked_panics_on_nan() {
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        spot.fit(&exponential_data(1_000, 3)).unwrap();
+        spot.step_unchecked(f64::NAN);
+    }
+
+    #[test]
+    fn test_spot_stats_count_steps_and_refits() {
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        let data = exponential_data(20_000, 5);
+        spot.fit(&data[..5_000]).unwrap();
+        let trained = spot.stats();
+        assert_eq!((trained.steps, trained.excesses, trained.refits), (0, 0, 0));
+        assert_eq!((trained.mom_calls, trained.grimshaw_calls), (1, 1));
+
+        let mut excesses = 0;
+        for &x in &data[5_000..] {
+            if spot.step(x).unwrap() == SpotStatus::Excess {
+                excesses += 1;
+            }
+        }
+        let _ = spot.step(f64::NAN);
+        spot.step_unchecked(0.0);
+
+        let stats = spot.stats();
+        assert_eq!(stats.steps, 15_002);
+        assert_eq!(stats.excesses, excesses);
+        assert!(excesses > 0);
+        // The tail is ready after the training fit, so every excess refits it
+        assert_eq!(stats.refits, stats.excesses);
+        assert_eq!(stats.mom_calls, 1 + stats.refits);
+        assert_eq!(stats.grimshaw_calls, 1 + stats.refits);
+
+        spot.reset();
+        assert_eq!(spot.stats(), RuntimeStats::default());
+    }
+
+    #[test]
+    fn test_spot_capacity_hint_does_not_change_fits() {
+        let mut data = exponential_data(6_000, 9);
+        for i in (0..data.len()).step_by(500) {
+            data[i] = f64::INFINITY;
+        }
+        let (train, update) = data.split_at(4_000);
+        let config = SpotConfig {
+            reject_infinite: false,
+            ..SpotConfig::default()
+        };
+
+        let mut plain = SpotDetector::new(config.clone()).unwrap();
+        let mut hinted = SpotDetector::new(config)
+            .unwrap()
+            .with_capacity_hint(train.len());
+        let capacity = hinted.scratch.capacity();
+        assert!(capacity >= train.len());
+
+        plain.fit(train).unwrap();
+        hinted.fit(train).unwrap();
+        assert_eq!(plain.n(), 3_992);
+        assert_eq!(hinted.n(), plain.n());
+        assert_eq!(hinted.tail_parameters(), plain.tail_parameters());
+        assert_eq!(hinted.anomaly_threshold(), plain.anomaly_threshold());
 
-        let s = (self.nt as f64) / (self.n as f64);
-        self.tail
-            .probability(s, self.up_down * (z - self.excess_threshold))
-    }
+        plain.fit_update(update).unwrap();
+        hinted.fit_update(update).unwrap();
+        assert_eq!(hinted.anomaly_threshold(), plain.anomaly_threshold());
 
-    /// Get the current anomaly threshold
-    pub fn anomaly_threshold(&self) -> f64 {
-        self.anomaly_threshold
-    }
+        let et = plain.excess_threshold();
+        plain.fit_from_quantile(train, et).unwrap();
+        hinted.fit_from_quantile(train, et).unwrap();
+        assert_eq!(hinted.anomaly_threshold(), plain.anomaly_threshold());
 
-    /// Get the current excess threshold
-    pub fn excess_threshold(&self) -> f64 {
-        self.excess_threshold
+        // The hinted buffer was reused rather than reallocated
+        assert_eq!(hinted.scratch.capacity(), capacity);
+        assert!(hinted.scratch.is_empty());
     }
 
-    /// Get the current configuration (reconstructed)
-    pub fn config(&self) -> Option<SpotConfig> {
-        Some(SpotConfig {
-            q: self.q,
-            low_tail: self.low,
-            discard_anomalies: self.discard_anomalies,
-            level: self.level,
-            max_excess: self.tail.peaks().container().capacity(),
+    #[test]
+    fn test_spot_fit_with_zero_level() {
+        let data: Vec<f64> = exponential_data(2_000, 17)
+            .iter()
+            .map(|x| x + 1.0)
+            .collect();
+        let min = data.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = data.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let config = SpotConfig {
+            level: 0.0,
+            q: 1e-3,
+            ..SpotConfig::default()
+        };
+
+        let mut spot = SpotDetector::new(config.clone()).unwrap();
+        spot.fit(&data).unwrap();
+        assert_eq!(spot.excess_threshold(), min);
+        let ties = data.iter().filter(|&&x| x == min).count();
+        assert_eq!(spot.nt(), data.len() - ties);
+        assert!(spot.anomaly_threshold() > spot.excess_threshold());
+        assert_eq!(spot.step(min - 1.0), Ok(SpotStatus::Normal));
+        assert_eq!(spot.step(min + 0.5), Ok(SpotStatus::Excess));
+
+        let mut low = SpotDetector::new(SpotConfig {
+            low_tail: true,
+            ..config
         })
+        .unwrap();
+        low.fit(&data).unwrap();
+        assert_eq!(low.excess_threshold(), max);
+        assert!(low.anomaly_threshold() < low.excess_threshold());
     }
 
-    /// Get the total number of data points seen
-    pub fn n(&self) -> usize {
-        self.n
-    }
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_spot_with_channel_sends_every_step() {
+        let data = exponential_data(10_000, 21);
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        spot.fit(&data[..5_000]).unwrap();
+        let mut reference = spot.clone();
 
-    /// Get the total number of excesses
-    pub fn nt(&self) -> usize {
-        self.nt
-    }
+        let (tx, rx) = std::sync::mpsc::channel();
+        spot.with_channel(tx);
+        for &x in &data[5_000..] {
+            spot.step(x).unwrap();
+        }
+        assert!(spot.step(f64::NAN).is_err());
+        spot.step_unchecked(50.0);
 
-    /// Get the current tail parameters
-    pub fn tail_parameters(&self) -> (f64, f64) {
-        (self.tail.gamma(), self.tail.sigma())
-    }
+        let reports: Vec<StepReport> = rx.try_iter().collect();
+        assert_eq!(reports.len(), 5_001);
+        for (report, &x) in reports.iter().zip(data[5_000..].iter().chain(&[50.0])) {
+            let expected = reference.step_detailed(x).unwrap();
+            assert_eq!(report.status, expected.status);
+            assert_eq!(report.anomaly_threshold, expected.anomaly_threshold);
+            // The probability is NaN below the excess threshold
+            assert_eq!(report.probability.to_bits(), expected.probability.to_bits());
+        }
+        assert_eq!(reports[5_000].status, SpotStatus::Anomaly);
 
-    /// Reset the detector's internal state, keeping the configuration and the
-    /// backing buffer. After calling this, [`fit`](Self::fit) must be called
-    /// again before further [`step`](Self::step) calls.
-    ///
-    /// This mirrors the `spot_reset` C API exposed by the FFI wrapper crate.
-    pub fn reset(&mut self) {
-        self.anomaly_threshold = f64::NAN;
-        self.excess_threshold = f64::NAN;
-        self.nt = 0;
-        self.n = 0;
-        self.tail.reset();
+        // Sending to a dropped receiver does not fail the step
+        drop(rx);
+        assert!(spot.step(1.0).is_ok());
     }
 
-    /// Get the current size of the tail data
-    pub fn tail_size(&self) -> usize {
-        self.tail.size()
-    }
+    #[test]
+    fn test_spot_predict_does_not_mutate() {
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        spot.fit(&exponential_data(10_000, 17)).unwrap();
+        let (n, nt) = (spot.n(), spot.nt());
+        let (z, t) = (spot.anomaly_threshold(), spot.excess_threshold());
+        let tail_parameters = spot.tail_parameters();
 
-    /// Get the minimum value in the peaks
-    pub fn peaks_min(&self) -> f64 {
-        self.tail.peaks().min()
-    }
+        let probes = [0.0, t - 1e-9, t, (t + z) / 2.0, z, z + 1e-9, 2.0 * z];
+        for _ in 0..100 {
+            for &x in &probes {
+                spot.predict(x).unwrap();
+            }
+        }
+        assert_eq!((spot.n(), spot.nt()), (n, nt));
+        assert_eq!((spot.anomaly_threshold(), spot.excess_threshold()), (z, t));
+        assert_eq!(spot.tail_parameters(), tail_parameters);
+        assert_eq!(spot.predict(f64::NAN), Err(SpotError::DataIsNaN));
 
-    /// Get the maximum value in the peaks
-    pub fn peaks_max(&self) -> f64 {
-        self.tail.peaks().max()
+        // Same verdict as step on a copy of the model, around both thresholds
+        for &x in &probes {
+            let mut copy = spot.clone();
+            assert_eq!(spot.predict(x), copy.step(x), "x = {}", x);
+        }
+        assert_eq!(spot.predict(t - 1e-9), Ok(SpotStatus::Normal));
+        assert_eq!(spot.predict(t), Ok(SpotStatus::Excess));
+        assert_eq!(spot.predict(z + 1e-9), Ok(SpotStatus::Anomaly));
     }
 
-    /// Get the mean of the peaks
-    pub fn peaks_mean(&self) -> f64 {
-        self.tail.peaks().mean()
+    /// Count the stream values that land in the tail (excesses and anomalies)
+    fn tail_rate(spot: &mut SpotDetector, stream: &[f64]) -> f64 {
+        let in_tail = stream
+            .iter()
+            .filter(|&&x| spot.step(x).unwrap() != SpotStatus::Normal)
+            .count();
+        in_tail as f64 / stream.len() as f64
     }
 
-    /// Get the variance of the peaks
-    pub fn peaks_variance(&self) -> f64 {
-        self.tail.peaks().variance()
-    }
+    #[test]
+    fn test_spot_track_excess_threshold_follows_location_shift() {
+        let training = exponential_data(5_000, 3);
+        let noise = exponential_data(50_000, 11);
+        let stream: Vec<f64> = noise
+            .iter()
+            .enumerate()
+            .map(|(i, x)| x + 5e-5 * i as f64)
+            .collect();
 
-    /// Get the peaks data as a vector (for debugging and export)
-    pub fn peaks_data(&self) -> Vec<f64> {
-        self.tail.peaks().container().data()
-    }
-}
+        let fixed_config = SpotConfig {
+            level: 0.98,
+            q: 0.001,
+            ..SpotConfig::default()
+        };
+        let tracked_config = SpotConfig {
+            track_excess_threshold: true,
+            ..fixed_config.clone()
+        };
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use approx::assert_relative_eq;
+        let mut fixed = SpotDetector::new(fixed_config).unwrap();
+        fixed.fit(&training).unwrap();
+        let fixed_rate = tail_rate(&mut fixed, &stream);
 
-    #[test]
-    fn test_spot_creation_valid_config() {
-        let config = SpotConfig::default();
-        let spot = SpotDetector::new(config).unwrap();
+        let mut tracked = SpotDetector::new(tracked_config).unwrap();
+        tracked.fit(&training).unwrap();
+        let initial_threshold = tracked.excess_threshold();
+        let tracked_rate = tail_rate(&mut tracked, &stream);
 
-        assert_relative_eq!(spot.q, 0.0001);
-        assert!(!spot.low);
-        assert!(spot.discard_anomalies);
-        assert_relative_eq!(spot.level, 0.998);
-        assert!(spot.anomaly_threshold().is_nan());
-        assert!(spot.excess_threshold().is_nan());
-        assert_eq!(spot.n(), 0);
-        assert_eq!(spot.nt(), 0);
+        assert!(tracked.excess_threshold() > initial_threshold + 1.5);
+        assert!(
+            (tracked_rate - 0.02).abs() < 0.015,
+            "tracked excess rate {tracked_rate}"
+        );
+        assert!(
+            fixed_rate > 2.0 * tracked_rate,
+            "fixed {fixed_rate} vs tracked {tracked_rate}"
+        );
+        assert!(tracked.peaks_min() > 0.0);
+        assert!(tracked.anomaly_threshold().is_finite());
     }
 
     #[test]
-    fn test_spot_invalid_level() {
+    fn test_spot_track_excess_threshold_keeps_the_tail() {
         let config = SpotConfig {
-            level: 1.5, // Invalid
+            level: 0.98,
+            track_excess_threshold: true,
             ..SpotConfig::default()
         };
-        let result = SpotDetector::new(config);
-        assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), SpotError::LevelOutOfBounds);
+        let mut spot = SpotDetector::new(config).unwrap();
+        spot.fit(&exponential_data(5_000, 3)).unwrap();
+        let nt = spot.nt();
+        let excesses = spot.excess_values();
+        let parameters = spot.tail_parameters();
+
+        // A window of normal values moves the threshold to their P2 quantile,
+        // before the last one is classified
+        let location = spot.excess_threshold() - 1.0;
+        for _ in 0..4_999 {
+            assert_eq!(spot.step(location), Ok(SpotStatus::Normal));
+        }
+        assert_eq!(spot.step(location - 1.0), Ok(SpotStatus::Normal));
+        assert_eq!(spot.excess_threshold(), location);
+        assert_eq!(spot.nt(), nt);
+        assert_eq!(spot.excess_values(), excesses);
+        assert_eq!(spot.tail_parameters(), parameters);
+        // Relocated before the last value was counted
+        let s = spot.nt() as f64 / (spot.n() - 1) as f64;
+        assert_eq!(
+            spot.anomaly_threshold(),
+            location + spot.tail().quantile(s, 1e-4)
+        );
     }
 
     #[test]
-    fn test_spot_invalid_q() {
+    fn test_spot_track_excess_threshold_ignores_short_windows() {
         let config = SpotConfig {
-            q: 0.5, // Too high for level 0.998
+            level: 0.5,
+            q: 0.1,
+            min_peaks: 0,
+            track_excess_threshold: true,
             ..SpotConfig::default()
         };
-        let result = SpotDetector::new(config);
-        assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), SpotError::QOutOfBounds);
+        let mut spot = SpotDetector::new(config).unwrap();
+        spot.fit(&[1.0, 2.0, 3.0, 4.0]).unwrap();
+        let threshold = spot.excess_threshold();
+
+        // No P2 estimate over a window of 4 values
+        for x in [0.5, 0.25, 0.75, 0.5] {
+            spot.step(x).unwrap();
+        }
+        assert_eq!(spot.excess_threshold(), threshold);
+        assert!(spot.anomaly_threshold().is_finite());
     }
 
     #[test]
-    fn test_spot_fit_basic() {
-        let config = SpotConfig::default();
-        let mut spot = SpotDetector::new(config).unwrap();
+    fn test_spot_step_detailed_reports_state_after_step() {
+        let mut detailed = SpotDetector::new(SpotConfig::default()).unwrap();
+        let mut plain = SpotDetector::new(SpotConfig::default()).unwrap();
+        let train = exponential_data(10_000, 17);
+        detailed.fit(&train).unwrap();
+        plain.fit(&train).unwrap();
 
-        // Create simple training data
-        let data: Vec<f64> = (0..1000).map(|i| (i as f64 / 1000.0) * 2.0 - 1.0).collect();
+        for &x in &[0.5, 7.0, 8.5, 50.0] {
+            let report = detailed.step_detailed(x).unwrap();
+            assert_eq!(report.status, plain.step(x).unwrap());
+            assert_eq!(report.value, x);
+            assert_eq!(report.anomaly_threshold, detailed.anomaly_threshold());
+            assert_eq!(report.excess_threshold, detailed.excess_threshold());
+            assert_eq!(report.probability, detailed.probability(x));
+        }
+        assert_eq!(detailed.anomaly_threshold(), plain.anomaly_threshold());
+        assert_eq!(detailed.step_detailed(f64::NAN), Err(SpotError::DataIsNaN));
+    }
 
-        let result = spot.fit(&data);
-        assert!(result.is_ok());
+    #[test]
+    fn test_spot_step_batch_matches_step() {
+        let train = exponential_data(10_000, 15);
+        let stream = exponential_data(5_000, 16);
 
-        // After fit, thresholds should be valid
-        assert!(!spot.anomaly_threshold().is_nan());
-        assert!(!spot.excess_threshold().is_nan());
-        assert!(spot.anomaly_threshold().is_finite());
-        assert!(spot.excess_threshold().is_finite());
-        assert_eq!(spot.n(), 1000);
-        assert!(spot.nt() > 0); // Should have some excesses
+        let mut batched = SpotDetector::new(SpotConfig::default()).unwrap();
+        let mut single = SpotDetector::new(SpotConfig::default()).unwrap();
+        batched.fit(&train).unwrap();
+        single.fit(&train).unwrap();
+
+        let expected: Vec<SpotStatus> = stream.iter().map(|&x| single.step(x).unwrap()).collect();
+        assert_eq!(batched.step_batch(&stream).unwrap(), expected);
+        assert_eq!(batched.n(), single.n());
+        assert_eq!(batched.nt(), single.nt());
+        assert_eq!(batched.anomaly_threshold(), single.anomaly_threshold());
+
+        // The output buffer is cleared before being reused
+        let mut out = vec![SpotStatus::Anomaly; 3];
+        batched.step_batch_into(&stream[..10], &mut out).unwrap();
+        let expected: Vec<SpotStatus> = stream[..10]
+            .iter()
+            .map(|&x| single.step(x).unwrap())
+            .collect();
+        assert_eq!(out, expected);
     }
 
     #[test]
-    fn test_spot_step_normal() {
-        let config = SpotConfig::default();
-        let mut spot = SpotDetector::new(config).unwrap();
+    fn test_spot_step_batch_stops_at_first_error() {
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        spot.fit(&exponential_data(10_000, 17)).unwrap();
+        let n = spot.n();
 
-        // Fit with simple data
-        let data: Vec<f64> = (0..100).map(|i| i as f64).collect();
-        spot.fit(&data).unwrap();
+        let xs = [0.1, 0.2, f64::NAN, 0.3];
+        assert_eq!(spot.step_batch(&xs), Err(SpotError::DataIsNaN));
+        assert_eq!(spot.n(), n + 2);
 
-        // Test normal value
-        let result = spot.step(50.0);
-        assert!(result.is_ok());
-        // Result depends on the thresholds, but should be valid
+        let mut out = Vec::new();
+        assert_eq!(
+            spot.step_batch_into(&xs, &mut out),
+            Err(SpotError::DataIsNaN)
+        );
+        assert_eq!(out.len(), 2);
     }
 
     #[test]
-    fn test_spot_step_nan() {
-        let config = SpotConfig::default();
-        let mut spot = SpotDetector::new(config).unwrap();
+    fn test_spot_excess_values() {
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        spot.fit(&exponential_data(10_000, 18)).unwrap();
+        assert_eq!(spot.excess_values().len(), spot.tail_size());
 
-        let result = spot.step(f64::NAN);
-        assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), SpotError::DataIsNaN);
+        let excess = spot.excess_threshold() + 0.5;
+        assert!(excess < spot.anomaly_threshold());
+        for _ in 0..3 {
+            assert_eq!(spot.step(excess).unwrap(), SpotStatus::Excess);
+        }
+
+        let values = spot.excess_values();
+        assert_eq!(values.len(), spot.tail_size());
+        assert_eq!(values, spot.peaks_data());
+        // Insertion order: the newest excesses come last
+        let newest = excess - spot.excess_threshold();
+        assert!(values[values.len() - 3..].iter().all(|&v| v == newest));
+        assert!(values.iter().all(|&v| v > 0.0));
     }
 
     #[test]
-    fn test_spot_reset_returns_to_pristine_state() {
-        let config = SpotConfig::default();
-        let mut spot = SpotDetector::new(config.clone()).unwrap();
-
-        let data: Vec<f64> = (0..1000).map(|i| (i as f64 / 1000.0) * 2.0 - 1.0).collect();
+    fn test_spot_reset_counters_updates_threshold() {
+        let data = exponential_data(20_000, 3);
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
         spot.fit(&data).unwrap();
-        for v in &data {
-            let _ = spot.step(*v).unwrap();
+        for &x in &exponential_data(50_000, 4) {
+            spot.step(x).unwrap();
         }
-        assert!(spot.n() > 0);
-        assert!(!spot.anomaly_threshold().is_nan());
 
-        spot.reset();
+        let tail_parameters = spot.tail_parameters();
+        let excess_threshold = spot.excess_threshold();
+        let old_threshold = spot.anomaly_threshold();
 
-        // Looks like a freshly constructed detector.
-        assert!(spot.anomaly_threshold().is_nan());
-        assert!(spot.excess_threshold().is_nan());
-        assert_eq!(spot.n(), 0);
-        assert_eq!(spot.nt(), 0);
-        assert_eq!(spot.tail_size(), 0);
-        assert_eq!(spot.config(), Some(config.clone()));
+        // Pretend the recent excess rate doubled
+        let n = 1_000;
+        let nt = (2.0 * (spot.nt() as f64 / spot.n() as f64) * n as f64) as usize;
+        spot.reset_counters(n, nt).unwrap();
 
-        // Re-fit produces identical numbers to a fresh detector.
-        let mut fresh = SpotDetector::new(config).unwrap();
-        spot.fit(&data).unwrap();
-        fresh.fit(&data).unwrap();
-        assert_relative_eq!(spot.anomaly_threshold(), fresh.anomaly_threshold());
-        assert_relative_eq!(spot.excess_threshold(), fresh.excess_threshold());
-        assert_eq!(spot.nt(), fresh.nt());
-        assert_eq!(spot.n(), fresh.n());
+        assert_eq!(spot.n(), n);
+        assert_eq!(spot.nt(), nt);
+        assert_eq!(spot.tail_parameters(), tail_parameters);
+        assert_eq!(spot.excess_threshold(), excess_threshold);
+        assert_eq!(
+            spot.anomaly_threshold(),
+            spot.quantile(spot.config().unwrap().q)
+        );
+        // A higher rate of excesses pushes the anomaly threshold up
+        assert!(spot.anomaly_threshold() > old_threshold);
     }
 
     #[test]
-    fn test_spot_reset_before_fit_is_noop_safe() {
-        // Calling reset on a freshly constructed detector must not panic
-        // and must leave the detector in the same observable state.
+    fn test_spot_reset_counters_invalid() {
         let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
-        spot.reset();
-        assert!(spot.anomaly_threshold().is_nan());
-        assert!(spot.excess_threshold().is_nan());
+        assert_eq!(
+            spot.reset_counters(10, 5),
+            Err(SpotError::AnomalyThresholdIsNaN)
+        );
         assert_eq!(spot.n(), 0);
-        assert_eq!(spot.nt(), 0);
-        assert_eq!(spot.tail_size(), 0);
 
-        // Fit still works normally afterwards.
-        let data: Vec<f64> = (0..500).map(|i| (i as f64 / 500.0) * 2.0 - 1.0).collect();
-        spot.fit(&data).unwrap();
-        assert!(!spot.anomaly_threshold().is_nan());
+        spot.fit(&exponential_data(5_000, 5)).unwrap();
+        let (n, nt) = (spot.n(), spot.nt());
+        let threshold = spot.anomaly_threshold();
+        assert_eq!(
+            spot.reset_counters(0, 0),
+            Err(SpotError::CountersOutOfBounds)
+        );
+        assert_eq!(
+            spot.reset_counters(10, 11),
+            Err(SpotError::CountersOutOfBounds)
+        );
+        assert_eq!((spot.n(), spot.nt()), (n, nt));
+        assert_eq!(spot.anomaly_threshold(), threshold);
     }
 
     #[test]
-    fn test_spot_reset_is_idempotent() {
+    fn test_spot_nan_queries() {
         let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
-        let data: Vec<f64> = (0..500).map(|i| (i as f64 / 500.0) * 2.0 - 1.0).collect();
-        spot.fit(&data).unwrap();
-        for v in &data {
-            let _ = spot.step(*v).unwrap();
-        }
-
-        spot.reset();
-        let after_first_n = spot.n();
-        let after_first_nt = spot.nt();
-        let after_first_size = spot.tail_size();
+        assert!(spot.quantile(f64::NAN).is_nan());
+        assert!(spot.probability(f64::NAN).is_nan());
 
-        spot.reset();
-        assert_eq!(spot.n(), after_first_n);
-        assert_eq!(spot.nt(), after_first_nt);
-        assert_eq!(spot.tail_size(), after_first_size);
-        assert!(spot.anomaly_threshold().is_nan());
-        assert!(spot.excess_threshold().is_nan());
+        spot.fit(&exponential_data(10_000, 6)).unwrap();
+        let before = observable_state(&spot);
+        assert!(spot.quantile(f64::NAN).is_nan());
+        assert!(spot.probability(f64::NAN).is_nan());
+        assert_eq!(observable_state(&spot), before);
     }
 
     #[test]
-    fn test_spot_reset_then_fit_then_step_full_cycle() {
-        // Full lifecycle: fit -> step -> reset -> fit again -> step again must
-        // produce the same step classifications as a fresh detector running
-        // the same fit+step sequence.
-        let config = SpotConfig::default();
-        let train: Vec<f64> = (0..1000).map(|i| (i as f64 / 1000.0) * 2.0 - 1.0).collect();
-        let probe: Vec<f64> = (0..200).map(|i| (i as f64 / 100.0) - 1.0).collect();
+    fn test_spot_infinite_queries() {
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        spot.fit(&exponential_data(10_000, 6)).unwrap();
 
-        let mut reused = SpotDetector::new(config.clone()).unwrap();
-        reused.fit(&train).unwrap();
-        for v in &probe {
-            let _ = reused.step(*v).unwrap();
+        let (gamma, sigma) = spot.tail_parameters();
+        let endpoint = spot.quantile(0.0);
+        if gamma < 0.0 {
+            assert_relative_eq!(endpoint, spot.excess_threshold() - sigma / gamma);
+        } else {
+            assert_eq!(endpoint, f64::INFINITY);
         }
-        reused.reset();
-        reused.fit(&train).unwrap();
-        let reused_classifications: Vec<SpotStatus> =
-            probe.iter().map(|&v| reused.step(v).unwrap()).collect();
+        assert_eq!(spot.probability(f64::INFINITY), 0.0);
 
-        let mut fresh = SpotDetector::new(config).unwrap();
-        fresh.fit(&train).unwrap();
-        let fresh_classifications: Vec<SpotStatus> =
-            probe.iter().map(|&v| fresh.step(v).unwrap()).collect();
+        let mut low = SpotDetector::new(SpotConfig {
+            low_tail: true,
+            ..SpotConfig::default()
+        })
+        .unwrap();
+        low.fit(
+            &exponential_data(10_000, 6)
+                .iter()
+                .map(|x| -x)
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+        assert_eq!(low.probability(f64::NEG_INFINITY), 0.0);
+        assert!(low.quantile(0.0) < low.anomaly_threshold());
+    }
 
-        assert_eq!(reused_classifications, fresh_classifications);
-        assert_relative_eq!(reused.anomaly_threshold(), fresh.anomaly_threshold());
-        assert_relative_eq!(reused.excess_threshold(), fresh.excess_threshold());
-        assert_eq!(reused.nt(), fresh.nt());
-        assert_eq!(reused.n(), fresh.n());
+    /// Snapshot of the observable detector state
+    fn observable_state(spot: &SpotDetector) -> (usize, usize, f64, f64, (f64, f64)) {
+        (
+            spot.n(),
+            spot.nt(),
+            spot.excess_threshold(),
+            spot.anomaly_threshold(),
+            spot.tail_parameters(),
+        )
     }
 
     #[test]
@@ -505,6 +4576,23 @@ mod tests {
             discard_anomalies: false,
             level: 0.99,
             max_excess: 100,
+            track_excess_threshold: true,
+            pwm_estimator: true,
+            min_peaks: 5,
+            grimshaw_options: Some(GrimshawOptions {
+                epsilon: 1e-12,
+                max_iter: 500,
+            }),
+            warmup_steps: 20,
+            reject_infinite: false,
+            adaptive_tail: Some(AdaptiveTail {
+                max_excess: 400,
+                min_excess_rate: 0.1,
+            }),
+            fit_strategy: FitStrategy::GrimshawOnly,
+            min_gamma: Some(-0.5),
+            anomaly_rate_window: 100,
+            stable_small_gamma: true,
         };
 
         let spot = SpotDetector::new(original_config.clone()).unwrap();
@@ -517,7 +4605,7 @@ mod tests {
             original_config.discard_anomalies
         );
         assert_relative_eq!(retrieved_config.level, original_config.level);
-        assert_eq!(retrieved_config.max_excess, original_config.max_excess);
+        assert_eq!(retrieved_config, original_config);
     }
 
     #[test]
@@ -540,6 +4628,30 @@ mod tests {
         assert!(p >= 0.0);
     }
 
+    #[test]
+    fn test_spot_probability_extreme_value_heavy_tail() {
+        // Pareto-like data: u^(-1/2) has a heavy tail (gamma around 0.5)
+        let data: Vec<f64> = exponential_data(20_000, 9)
+            .iter()
+            .map(|e| (0.5 * e).exp())
+            .collect();
+        let config = SpotConfig {
+            level: 0.98,
+            ..SpotConfig::default()
+        };
+        let mut spot = SpotDetector::new(config).unwrap();
+        spot.fit(&data).unwrap();
+
+        let (gamma, _) = spot.tail_parameters();
+        assert!(
+            gamma > 0.0,
+            "expected a heavy-tailed fit, got gamma={gamma}"
+        );
+        assert_eq!(spot.probability(1e300), 0.0);
+        assert_eq!(spot.probability(f64::INFINITY), 0.0);
+        assert!(spot.probability(spot.anomaly_threshold()) > 0.0);
+    }
+
     #[test]
     fn test_spot_excess_detection() {
         let config = SpotConfig {
@@ -1,5 +1,7 @@
 //! Status codes for SPOT operations
 
+use crate::error::SpotError;
+
 /// Status codes returned by SPOT operations that match the C implementation exactly
 ///
 /// # Serialization
@@ -16,17 +18,27 @@ pub enum SpotStatus {
     Anomaly = 2,
 }
 
-impl From<i32> for SpotStatus {
-    fn from(code: i32) -> Self {
+/// Convert from the C `int` status code, rejecting unknown codes
+impl TryFrom<i32> for SpotStatus {
+    type Error = SpotError;
+
+    fn try_from(code: i32) -> Result<Self, Self::Error> {
         match code {
-            0 => SpotStatus::Normal,
-            1 => SpotStatus::Excess,
-            2 => SpotStatus::Anomaly,
-            _ => SpotStatus::Normal, // Default fallback
+            0 => Ok(SpotStatus::Normal),
+            1 => Ok(SpotStatus::Excess),
+            2 => Ok(SpotStatus::Anomaly),
+            _ => Err(SpotError::InvalidStatusCode(code)),
         }
     }
 }
 
+/// Convert to the C `int` status code
+impl From<SpotStatus> for i32 {
+    fn from(status: SpotStatus) -> Self {
+        status as i32
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -40,14 +52,23 @@ mod tests {
     }
 
     #[test]
-    fn test_spot_status_from_i32() {
-        // Test conversion from C int values
-        assert_eq!(SpotStatus::from(0), SpotStatus::Normal);
-        assert_eq!(SpotStatus::from(1), SpotStatus::Excess);
-        assert_eq!(SpotStatus::from(2), SpotStatus::Anomaly);
-
-        // Test default fallback for unknown values
-        assert_eq!(SpotStatus::from(-1), SpotStatus::Normal);
-        assert_eq!(SpotStatus::from(99), SpotStatus::Normal);
+    fn test_spot_status_i32_roundtrip() {
+        for status in [SpotStatus::Normal, SpotStatus::Excess, SpotStatus::Anomaly] {
+            let code = i32::from(status);
+            assert_eq!(SpotStatus::try_from(code), Ok(status));
+        }
+        assert_eq!(SpotStatus::try_from(1), Ok(SpotStatus::Excess));
+    }
+
+    #[test]
+    fn test_spot_status_try_from_invalid_code() {
+        assert_eq!(
+            SpotStatus::try_from(3),
+            Err(SpotError::InvalidStatusCode(3))
+        );
+        assert_eq!(
+            SpotStatus::try_from(-1),
+            Err(SpotError::InvalidStatusCode(-1))
+        );
     }
 }
@@ -0,0 +1,99 @@
+//! Iterator adapter stepping a SPOT detector over a stream of values
+
+use crate::error::SpotResult;
+use crate::float::Float;
+use crate::spot::SpotDetector;
+use crate::status::SpotStatus;
+use crate::ubend::{RingBuffer, Ubend};
+
+/// Iterator returned by [`SpotDetector::stream`]
+///
+/// Each value is passed to [`step`](SpotDetector::step) only when the
+/// corresponding item is pulled, and is yielded back with its classification.
+/// An error (e.g. [`SpotError::DataIsNaN`](crate::SpotError::DataIsNaN)) is
+/// yielded as is and does not end the stream, so collecting into a
+/// `SpotResult<Vec<_>>` stops at the first one.
+#[derive(Debug)]
+pub struct SpotStream<'a, I, F: Float = f64, B: RingBuffer<F> = Ubend<F>> {
+    spot: &'a mut SpotDetector<F, B>,
+    values: I,
+}
+
+impl<'a, I, F: Float, B: RingBuffer<F>> SpotStream<'a, I, F, B> {
+    pub(crate) fn new(spot: &'a mut SpotDetector<F, B>, values: I) -> Self {
+        Self { spot, values }
+    }
+}
+
+impl<I, F, B> Iterator for SpotStream<'_, I, F, B>
+where
+    I: Iterator<Item = F>,
+    F: Float,
+    B: RingBuffer<F>,
+{
+    type Item = SpotResult<(F, SpotStatus)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.values.next()?;
+        Some(self.spot.step(value).map(|status| (value, status)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.values.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::SpotConfig;
+    use crate::error::{SpotError, SpotResult};
+    use crate::spot::SpotDetector;
+    use crate::status::SpotStatus;
+
+    fn fitted() -> SpotDetector {
+        let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+        let data: Vec<f64> = (0..1000)
+            .map(|i| ((i * 37) % 1000) as f64 / 100.0)
+            .collect();
+        spot.fit(&data).unwrap();
+        spot
+    }
+
+    #[test]
+    fn test_stream_matches_manual_steps() {
+        let values: Vec<f64> = (0..500).map(|i| ((i * 53) % 1200) as f64 / 100.0).collect();
+
+        let mut manual = fitted();
+        let expected: Vec<(f64, SpotStatus)> = values
+            .iter()
+            .map(|&x| (x, manual.step(x).unwrap()))
+            .collect();
+
+        let mut streamed = fitted();
+        let statuses = streamed
+            .stream(values.iter().copied())
+            .collect::<SpotResult<Vec<_>>>()
+            .unwrap();
+
+        assert_eq!(statuses, expected);
+        assert_eq!(streamed.n(), manual.n());
+        assert_eq!(streamed.anomaly_threshold(), manual.anomaly_threshold());
+    }
+
+    #[test]
+    fn test_stream_is_lazy_and_short_circuits() {
+        let mut spot = fitted();
+        let n = spot.n();
+
+        let mut stream = spot.stream(vec![1.0, f64::NAN, 2.0].into_iter());
+        assert_eq!(stream.size_hint(), (3, Some(3)));
+        assert_eq!(stream.next(), Some(Ok((1.0, SpotStatus::Normal))));
+        assert_eq!(stream.next(), Some(Err(SpotError::DataIsNaN)));
+        assert_eq!(spot.n(), n + 1);
+
+        let result: SpotResult<Vec<_>> =
+            spot.stream(vec![1.0, f64::NAN, 2.0].into_iter()).collect();
+        assert_eq!(result, Err(SpotError::DataIsNaN));
+        assert_eq!(spot.n(), n + 2);
+    }
+}
@@ -0,0 +1,187 @@
+//! Thread-safe sharing of a SPOT detector
+//!
+//! [`SpotDetector`] is plain data, so it is both `Send` and `Sync`: it can be
+//! moved to another thread, and shared references can be read concurrently.
+//! Updating it requires `&mut self`, which rules out sharing a detector while
+//! it is being stepped. [`SharedSpot`] wraps the detector in a [`RwLock`] so
+//! that one thread can keep stepping while others query the model.
+
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::config::SpotConfig;
+use crate::error::SpotResult;
+use crate::float::Float;
+use crate::spot::SpotDetector;
+use crate::status::SpotStatus;
+
+/// SPOT detector behind a read-write lock
+///
+/// Updates ([`fit`](Self::fit), [`step`](Self::step)) take the write lock and
+/// queries ([`quantile`](Self::quantile), [`anomaly_threshold`](Self::anomaly_threshold),
+/// ...) take the read lock, so readers never observe a half-applied step and
+/// any number of them can run concurrently between two updates. Share it with
+/// an `Arc<SharedSpot>`.
+///
+/// The detector never panics while holding the lock, but if a caller-provided
+/// closure passed to [`read`](Self::read) or [`write`](Self::write) does, the
+/// lock poisoning is ignored and later calls keep using the detector.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::thread;
+/// use libspot_rs::{SharedSpot, SpotConfig};
+///
+/// let spot = Arc::new(SharedSpot::new(SpotConfig::default()).unwrap());
+/// let data: Vec<f64> = (0..1000).map(|i| ((i * 37) % 1000) as f64 / 100.0).collect();
+/// spot.fit(&data).unwrap();
+///
+/// let reader = {
+///     let spot = Arc::clone(&spot);
+///     thread::spawn(move || spot.anomaly_threshold())
+/// };
+/// spot.step(5.0).unwrap();
+/// assert!(reader.join().unwrap().is_finite());
+/// ```
+#[derive(Debug)]
+pub struct SharedSpot<F: Float = f64> {
+    inner: RwLock<SpotDetector<F>>,
+}
+
+impl SharedSpot {
+    /// Create a new shared `f64` detector with the given configuration
+    pub fn new(config: SpotConfig) -> SpotResult<Self> {
+        SpotDetector::new(config).map(Self::from_detector)
+    }
+}
+
+impl<F: Float> SharedSpot<F> {
+    /// Wrap an existing detector
+    pub fn from_detector(spot: SpotDetector<F>) -> Self {
+        Self {
+            inner: RwLock::new(spot),
+        }
+    }
+
+    /// Unwrap the detector
+    pub fn into_inner(self) -> SpotDetector<F> {
+        self.inner
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Run `f` with shared access to the detector (read lock)
+    pub fn read<R>(&self, f: impl FnOnce(&SpotDetector<F>) -> R) -> R {
+        f(&self.read_guard())
+    }
+
+    /// Run `f` with exclusive access to the detector (write lock)
+    pub fn write<R>(&self, f: impl FnOnce(&mut SpotDetector<F>) -> R) -> R {
+        f(&mut self.write_guard())
+    }
+
+    /// Fit the model using initial training data (write lock)
+    pub fn fit(&self, data: &[F]) -> SpotResult<()> {
+        self.write_guard().fit(data)
+    }
+
+    /// Process a single data point and return its classification (write lock)
+    pub fn step(&self, value: F) -> SpotResult<SpotStatus> {
+        self.write_guard().step(value)
+    }
+
+    /// Get the quantile for a given probability (read lock)
+    pub fn quantile(&self, q: F) -> F {
+        self.read_guard().quantile(q)
+    }
+
+    /// Get the probability for a given value (read lock)
+    pub fn probability(&self, z: F) -> F {
+        self.read_guard().probability(z)
+    }
+
+    /// Get the current anomaly threshold (read lock)
+    pub fn anomaly_threshold(&self) -> F {
+        self.read_guard().anomaly_threshold()
+    }
+
+    /// Get the current excess threshold (read lock)
+    pub fn excess_threshold(&self) -> F {
+        self.read_guard().excess_threshold()
+    }
+
+    fn read_guard(&self) -> RwLockReadGuard<'_, SpotDetector<F>> {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write_guard(&self) -> RwLockWriteGuard<'_, SpotDetector<F>> {
+        self.inner.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_spot_detector_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SpotDetector>();
+        assert_send_sync::<SpotDetector<f32>>();
+        assert_send_sync::<SharedSpot>();
+    }
+
+    #[test]
+    fn test_shared_spot_concurrent_reads_during_updates() {
+        let spot = Arc::new(SharedSpot::new(SpotConfig::default()).unwrap());
+        let training: Vec<f64> = (0..1000)
+            .map(|i| ((i * 37) % 1000) as f64 / 100.0)
+            .collect();
+        spot.fit(&training).unwrap();
+
+        let done = Arc::new(AtomicBool::new(false));
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let spot = Arc::clone(&spot);
+                let done = Arc::clone(&done);
+                thread::spawn(move || {
+                    let mut reads = 0;
+                    let mut last_n = 0;
+                    while !done.load(Ordering::Relaxed) || reads == 0 {
+                        // A single lock gives a consistent snapshot of the model
+                        let (n, nt, excess, anomaly) = spot
+                            .read(|s| (s.n(), s.nt(), s.excess_threshold(), s.anomaly_threshold()));
+                        assert!(n >= last_n && n >= 1000);
+                        assert!(nt <= n);
+                        assert!(excess < anomaly && anomaly.is_finite());
+                        last_n = n;
+                        reads += 1;
+                    }
+                    reads
+                })
+            })
+            .collect();
+
+        for i in 0..5000 {
+            spot.step(((i * 53) % 1200) as f64 / 100.0).unwrap();
+        }
+        done.store(true, Ordering::Relaxed);
+
+        for reader in readers {
+            assert!(reader.join().unwrap() > 0);
+        }
+
+        let mut expected = SpotDetector::new(SpotConfig::default()).unwrap();
+        expected.fit(&training).unwrap();
+        for i in 0..5000 {
+            expected.step(((i * 53) % 1200) as f64 / 100.0).unwrap();
+        }
+        let spot = Arc::try_unwrap(spot).unwrap().into_inner();
+        assert_eq!(spot.n(), expected.n());
+        assert_eq!(spot.anomaly_threshold(), expected.anomaly_threshold());
+    }
+}
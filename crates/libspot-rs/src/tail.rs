@@ -3,154 +3,655 @@
 //! This module implements the Tail structure that models the tail of a distribution
 //! using Generalized Pareto Distribution (GPD) parameters.
 
-use crate::error::SpotResult;
+use crate::error::{SpotError, SpotResult};
 
-use crate::estimator::{grimshaw_estimator, mom_estimator};
+use crate::estimator::{
+    grimshaw_estimator_with, weighted_grimshaw_estimator, weighted_mom_estimator, EstimatorKind,
+    FitStrategy, GpdEstimator, GrimshawOptions,
+};
+use crate::float::Float;
 use crate::math::{xexp, xlog, xpow};
 use crate::peaks::Peaks;
+use crate::report::FitReport;
+use crate::ubend::{RingBuffer, Ubend};
+
+/// Below this |gamma|, `quantile` and `probability` may use `exp_m1`/`ln_1p`
+/// (see [`Tail::with_stable_small_gamma`]): `(r^-gamma - 1) / gamma` otherwise
+/// cancels out as gamma goes to 0
+const SMALL_GAMMA: f64 = 1e-4;
+
+/// Number of MoM and Grimshaw estimates computed by a [`Tail`]
+#[derive(Debug, Clone, Copy, Default)]
+struct EstimatorCalls {
+    mom: u64,
+    grimshaw: u64,
+}
+
+impl EstimatorCalls {
+    /// Count a call to the given estimator (PWM calls are not counted)
+    fn count(&mut self, estimator: EstimatorKind) {
+        match estimator {
+            EstimatorKind::MomentMatch => self.mom += 1,
+            EstimatorKind::Grimshaw => self.grimshaw += 1,
+            EstimatorKind::Pwm => {}
+        }
+    }
+}
 
 /// Structure that embeds GPD parameters (GPD tail actually)
 ///
 /// # Serialization
 ///
-/// When the `serde` feature is enabled, this struct can be serialized and deserialized.
-/// This allows saving and restoring the GPD tail model parameters.
+/// When the `serde` feature is enabled, this struct can be serialized and deserialized
+/// (if its buffer can). This allows saving and restoring the GPD tail model parameters.
+///
+/// # Buffer
+///
+/// The excesses are kept in [`Peaks`] over a [`Ubend`] by default; see
+/// [`from_peaks`](Self::from_peaks) to use another [`RingBuffer`].
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Tail {
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        bound(
+            serialize = "B: serde::Serialize",
+            deserialize = "B: serde::Deserialize<'de>"
+        ),
+        from = "TailData<F, B>"
+    )
+)]
+pub struct Tail<F: Float = f64, B: RingBuffer<F> = Ubend<F>> {
     /// GPD gamma parameter
-    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_f64"))]
-    gamma: f64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_float"))]
+    gamma: F,
     /// GPD sigma parameter
-    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_f64"))]
-    sigma: f64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_float"))]
+    sigma: F,
+    /// Whether the fitted GPD is the exponential special case (`gamma == 0.0`)
+    ///
+    /// Cached whenever the parameters change so that the hot quantile and
+    /// probability paths do not re-compare gamma. Not serialized, but
+    /// recomputed from gamma on deserialization.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    exponential: bool,
+    /// Whether [`fit`](Self::fit) also tries the PWM estimator
+    #[cfg_attr(feature = "serde", serde(default))]
+    pwm: bool,
+    /// Root finding options of the Grimshaw estimator (C defaults if unset)
+    #[cfg_attr(feature = "serde", serde(default))]
+    grimshaw_options: Option<GrimshawOptions>,
+    /// Estimators tried by [`fit`](Self::fit)
+    #[cfg_attr(feature = "serde", serde(default))]
+    fit_strategy: FitStrategy,
+    /// Smallest gamma an estimate may have to be selected (no bound if unset)
+    #[cfg_attr(feature = "serde", serde(default))]
+    min_gamma: Option<f64>,
+    /// Whether `quantile` and `probability` avoid the cancellation of the C
+    /// formulas for gamma near 0
+    #[cfg_attr(feature = "serde", serde(default))]
+    stable_small_gamma: bool,
+    /// Number of estimates computed by the fits (not serialized)
+    #[cfg_attr(feature = "serde", serde(skip))]
+    calls: EstimatorCalls,
     /// Underlying Peaks structure
-    peaks: Peaks,
+    peaks: Peaks<F, B>,
+}
+
+/// Serialized fields of a [`Tail`], from which the cached exponential-case
+/// flag is recomputed
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(bound(deserialize = "B: serde::Deserialize<'de>"))]
+struct TailData<F: Float, B: RingBuffer<F>> {
+    #[serde(with = "crate::ser::nan_safe_float")]
+    gamma: F,
+    #[serde(with = "crate::ser::nan_safe_float")]
+    sigma: F,
+    #[serde(default)]
+    pwm: bool,
+    #[serde(default)]
+    grimshaw_options: Option<GrimshawOptions>,
+    #[serde(default)]
+    fit_strategy: FitStrategy,
+    #[serde(default)]
+    min_gamma: Option<f64>,
+    #[serde(default)]
+    stable_small_gamma: bool,
+    peaks: Peaks<F, B>,
+}
+
+#[cfg(feature = "serde")]
+impl<F: Float, B: RingBuffer<F>> From<TailData<F, B>> for Tail<F, B> {
+    fn from(data: TailData<F, B>) -> Self {
+        let mut tail = Self {
+            gamma: data.gamma,
+            sigma: data.sigma,
+            exponential: false,
+            pwm: data.pwm,
+            grimshaw_options: data.grimshaw_options,
+            fit_strategy: data.fit_strategy,
+            min_gamma: data.min_gamma,
+            stable_small_gamma: data.stable_small_gamma,
+            calls: EstimatorCalls::default(),
+            peaks: data.peaks,
+        };
+        tail.set_gpd(data.gamma, data.sigma);
+        tail
+    }
 }
 
 impl Tail {
-    /// Initialize a new Tail structure with the given size
+    /// Initialize a new `f64` Tail structure with the given size
     pub fn new(size: usize) -> SpotResult<Self> {
-        Ok(Self {
-            gamma: f64::NAN,
-            sigma: f64::NAN,
-            peaks: Peaks::new(size)?,
-        })
+        Self::new_generic(size)
+    }
+}
+
+impl<F: Float> Tail<F> {
+    /// Initialize a new Tail structure of any [`Float`] type with the given size
+    pub fn new_generic(size: usize) -> SpotResult<Self> {
+        Ok(Self::from_peaks(Peaks::new_generic(size)?))
+    }
+}
+
+impl<F: Float, B: RingBuffer<F>> Tail<F, B> {
+    /// Initialize an unfitted Tail structure over the given peaks
+    ///
+    /// ```
+    /// use libspot_rs::{ArrayUbend, Peaks, Tail};
+    ///
+    /// let mut tail = Tail::from_peaks(Peaks::from_buffer(ArrayUbend::<4>::new()));
+    /// for x in [0.5, 1.0, 1.5, 2.5] {
+    ///     tail.push(x);
+    /// }
+    /// assert!(tail.fit().is_finite());
+    /// ```
+    pub fn from_peaks(peaks: Peaks<F, B>) -> Self {
+        Self {
+            gamma: F::nan(),
+            sigma: F::nan(),
+            exponential: false,
+            pwm: false,
+            grimshaw_options: None,
+            fit_strategy: FitStrategy::default(),
+            min_gamma: None,
+            stable_small_gamma: false,
+            calls: EstimatorCalls::default(),
+            peaks,
+        }
+    }
+
+    /// Enable or disable the PWM estimator in [`fit`](Self::fit)
+    pub fn with_pwm(mut self, pwm: bool) -> Self {
+        self.pwm = pwm;
+        self
+    }
+
+    /// Whether [`fit`](Self::fit) also tries the PWM estimator
+    pub fn uses_pwm(&self) -> bool {
+        self.pwm
+    }
+
+    /// Compute [`quantile`](Self::quantile) and [`probability`](Self::probability)
+    /// with `exp_m1`/`ln_1p` for 0 < |gamma| < 1e-4
+    ///
+    /// The C formulas compute `(r^-gamma - 1) / gamma`, which cancels out as
+    /// gamma goes to 0: their relative error is about `1e-15 / |gamma|` (e.g.
+    /// 1e-11 for gamma = 1e-4, 1e-6 for gamma = 1e-9). Enabling this removes
+    /// it, at the cost of results that no longer match the C implementation
+    /// bit for bit. Disabled by default.
+    pub fn with_stable_small_gamma(mut self, stable_small_gamma: bool) -> Self {
+        self.stable_small_gamma = stable_small_gamma;
+        self
+    }
+
+    /// Whether [`quantile`](Self::quantile) and [`probability`](Self::probability)
+    /// avoid the cancellation of the C formulas for gamma near 0
+    pub fn uses_stable_small_gamma(&self) -> bool {
+        self.stable_small_gamma
+    }
+
+    /// Whether the `exp_m1`/`ln_1p` formulas apply to the current gamma
+    fn is_small_gamma(&self) -> bool {
+        self.stable_small_gamma && self.gamma.abs() < F::of(SMALL_GAMMA)
+    }
+
+    /// Set the root finding options of the Grimshaw estimator used by [`fit`](Self::fit)
+    ///
+    /// `None` keeps the constants of the C implementation.
+    pub fn with_grimshaw_options(mut self, options: Option<GrimshawOptions>) -> Self {
+        self.grimshaw_options = options;
+        self
+    }
+
+    /// Get the root finding options of the Grimshaw estimator, if customized
+    pub fn grimshaw_options(&self) -> Option<GrimshawOptions> {
+        self.grimshaw_options
+    }
+
+    /// Set the estimators tried by [`fit`](Self::fit)
+    ///
+    /// The default, [`FitStrategy::Both`], matches the C implementation.
+    pub fn with_fit_strategy(mut self, fit_strategy: FitStrategy) -> Self {
+        self.fit_strategy = fit_strategy;
+        self
+    }
+
+    /// Get the estimators tried by [`fit`](Self::fit)
+    pub fn fit_strategy(&self) -> FitStrategy {
+        self.fit_strategy
+    }
+
+    /// Reject the estimates with a gamma below `min_gamma` when fitting
+    ///
+    /// For gamma < -0.5 the GPD likelihood is irregular (unbounded below -1):
+    /// an estimate in this range can reach a spuriously high log-likelihood and
+    /// win the selection, e.g. the method of moments on bounded data. With
+    /// `Some(-0.5)`, the usual choice, such estimates are never selected and the
+    /// fit falls back on the other estimators. `None` (the default) keeps every
+    /// estimate like the C implementation.
+    pub fn with_min_gamma(mut self, min_gamma: Option<f64>) -> Self {
+        self.min_gamma = min_gamma;
+        self
+    }
+
+    /// Get the smallest gamma a fit may select, if bounded
+    pub fn min_gamma(&self) -> Option<f64> {
+        self.min_gamma
+    }
+
+    /// Whether an estimate with this gamma may be selected
+    pub(crate) fn is_admissible(&self, gamma: F) -> bool {
+        !matches!(self.min_gamma, Some(min_gamma) if gamma < F::of(min_gamma))
     }
 
     /// Add a new data point into the tail
-    pub fn push(&mut self, x: f64) {
+    pub fn push(&mut self, x: F) {
         self.peaks.push(x);
     }
 
+    /// Increase the capacity of the tail buffer, keeping the stored excesses
+    ///
+    /// See [`Peaks::grow`]. The GPD parameters are left untouched.
+    pub fn grow(&mut self, new_capacity: usize) {
+        self.peaks.grow(new_capacity);
+    }
+
+    /// Keep only the excesses for which `f` returns `true`
+    ///
+    /// See [`Peaks::retain`]. The GPD parameters are left untouched until the
+    /// next [`fit`](Self::fit). Returns the number of removed excesses.
+    pub fn retain(&mut self, f: impl FnMut(F) -> bool) -> usize {
+        self.peaks.retain(f)
+    }
+
     /// Reset the tail to its initial state, keeping the allocated buffer.
     pub(crate) fn reset(&mut self) {
-        self.gamma = f64::NAN;
-        self.sigma = f64::NAN;
+        self.set_gpd(F::nan(), F::nan());
         self.peaks.reset();
     }
 
+    /// Replace the peaks and the GPD parameters, keeping the estimator options
+    pub(crate) fn restore(&mut self, peaks: Peaks<F, B>, gamma: F, sigma: F) {
+        self.peaks = peaks;
+        self.set_gpd(gamma, sigma);
+    }
+
+    /// Get the number of MoM and Grimshaw estimates computed by
+    /// [`fit`](Self::fit) and the weighted fits
+    pub(crate) fn estimator_calls(&self) -> (u64, u64) {
+        (self.calls.mom, self.calls.grimshaw)
+    }
+
+    /// Zero the [estimator calls](Self::estimator_calls)
+    pub(crate) fn clear_estimator_calls(&mut self) {
+        self.calls = EstimatorCalls::default();
+    }
+
+    /// Set the GPD parameters and refresh the exponential-case flag
+    fn set_gpd(&mut self, gamma: F, sigma: F) {
+        self.gamma = gamma;
+        self.sigma = sigma;
+        // Use exact equality check like C implementation (no tolerance)
+        self.exponential = gamma == F::zero();
+    }
+
     /// Fit the GPD parameters using the available estimators
     /// Returns the log-likelihood of the best fit
-    pub fn fit(&mut self) -> f64 {
+    ///
+    /// MoM and Grimshaw are tried as in the C implementation, unless another
+    /// [`FitStrategy`] is set. PWM is added to the candidates when enabled with
+    /// [`with_pwm`](Self::with_pwm).
+    pub fn fit(&mut self) -> F {
+        self.fit_report()
+            .map_or(F::nan(), |report| report.log_likelihood)
+    }
+
+    /// Fit the GPD parameters like [`fit`](Self::fit) and report the winning
+    /// estimator with its parameters and log-likelihood
+    ///
+    /// Returns `None`, leaving the parameters unchanged, if the tail is empty
+    /// or every estimate is below the [minimum gamma](Self::with_min_gamma).
+    pub fn fit_report(&mut self) -> Option<FitReport<F>> {
         if self.peaks.size() == 0 {
-            return f64::NAN;
+            return None;
         }
 
-        // Match C implementation exactly: try each estimator and pick best
-        let mut max_llhood = f64::NAN;
-        let mut tmp_gamma;
-        let mut tmp_sigma;
+        // Match C implementation exactly: try each estimator in order (MoM is
+        // index 0 in C, Grimshaw index 1) and keep the first best one
+        let size = self.peaks.size();
+        let mut best: Option<FitReport<F>> = None;
+        for estimator in [
+            EstimatorKind::MomentMatch,
+            EstimatorKind::Grimshaw,
+            EstimatorKind::Pwm,
+        ] {
+            if (estimator == EstimatorKind::Pwm && !self.pwm)
+                || !self.fit_strategy.tries(estimator, size)
+            {
+                continue;
+            }
+            self.calls.count(estimator);
 
-        // Try MoM estimator first (index 0 in C)
-        let llhood = {
-            let (gamma, sigma, llhood) = mom_estimator(&self.peaks);
-            tmp_gamma = gamma;
-            tmp_sigma = sigma;
-            llhood
-        };
+            let (gamma, sigma, log_likelihood) = match (estimator, self.grimshaw_options) {
+                (EstimatorKind::Grimshaw, Some(options)) => {
+                    grimshaw_estimator_with(&self.peaks, options)
+                }
+                _ => estimator.estimate(&self.peaks),
+            };
+            if !self.is_admissible(gamma) {
+                continue;
+            }
+            if is_better(best.map(|best| best.log_likelihood), log_likelihood) {
+                best = Some(FitReport {
+                    gamma,
+                    sigma,
+                    log_likelihood,
+                    estimator,
+                });
+            }
+        }
+
+        let best = best?;
+        #[cfg(feature = "log")]
+        log::debug!(
+            "Selected {:?} fit on {size} excesses: gamma = {}, sigma = {}",
+            best.estimator,
+            best.gamma,
+            best.sigma
+        );
+        self.set_gpd(best.gamma, best.sigma);
+        Some(best)
+    }
 
-        if max_llhood.is_nan() || llhood > max_llhood {
-            max_llhood = llhood;
-            self.gamma = tmp_gamma;
-            self.sigma = tmp_sigma;
+    /// Fit the GPD parameters with the given estimators, keeping the one with
+    /// the highest log-likelihood (the first one on ties)
+    ///
+    /// Returns the log-likelihood of the best fit, or NaN (leaving the parameters
+    /// unchanged) if the tail or `estimators` is empty. [`fit`](Self::fit) is
+    /// equivalent to `fit_with(&[&MomEstimator, &GrimshawEstimator])`, plus
+    /// [`PwmEstimator`](crate::PwmEstimator) when enabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use libspot_rs::{GpdEstimator, GrimshawEstimator, MomEstimator, Peaks, Tail};
+    ///
+    /// /// Exponential tail fitted by maximum likelihood
+    /// struct Exponential;
+    ///
+    /// impl GpdEstimator for Exponential {
+    ///     fn estimate(&self, peaks: &Peaks) -> (f64, f64, f64) {
+    ///         let sigma = peaks.mean();
+    ///         let n = peaks.size() as f64;
+    ///         (0.0, sigma, -n * sigma.ln() - n)
+    ///     }
+    /// }
+    ///
+    /// let mut tail = Tail::new(100).unwrap();
+    /// for i in 0..100 {
+    ///     tail.push(-(1.0 - (i as f64 + 0.5) / 100.0).ln());
+    /// }
+    /// let llhood = tail.fit_with(&[&MomEstimator, &GrimshawEstimator, &Exponential]);
+    /// assert!(llhood.is_finite());
+    /// ```
+    pub fn fit_with(&mut self, estimators: &[&dyn GpdEstimator<F, B>]) -> F {
+        if self.peaks.size() == 0 {
+            return F::nan();
         }
 
-        // Try Grimshaw estimator (index 1 in C)
-        let llhood = {
-            let (gamma, sigma, llhood) = grimshaw_estimator(&self.peaks);
-            tmp_gamma = gamma;
-            tmp_sigma = sigma;
-            llhood
+        let mut best: Option<(F, F, F)> = None;
+        for estimator in estimators {
+            let (gamma, sigma, log_likelihood) = estimator.estimate(&self.peaks);
+            if !self.is_admissible(gamma) {
+                continue;
+            }
+            if is_better(best.map(|(_, _, best)| best), log_likelihood) {
+                best = Some((gamma, sigma, log_likelihood));
+            }
+        }
+
+        let Some((gamma, sigma, log_likelihood)) = best else {
+            return F::nan();
         };
+        self.set_gpd(gamma, sigma);
+        log_likelihood
+    }
 
-        if max_llhood.is_nan() || llhood > max_llhood {
-            // Back to original logic
-            max_llhood = llhood;
-            self.gamma = tmp_gamma;
-            self.sigma = tmp_sigma;
+    /// Fit the GPD parameters with each excess weighted by the matching entry
+    /// of `weights`, in the raw order of the peaks container
+    ///
+    /// Keeps the best of the weighted MoM and Grimshaw estimators allowed by the
+    /// [`FitStrategy`] like [`fit`](Self::fit) (PWM has no weighted variant).
+    /// Returns the weighted
+    /// log-likelihood, or NaN (leaving the parameters unchanged) if the tail is
+    /// empty.
+    pub(crate) fn fit_weighted(&mut self, weights: &[F]) -> F {
+        let size = self.peaks.size();
+        if size == 0 {
+            return F::nan();
         }
 
-        max_llhood
+        let values = &self.peaks.container().raw_data()[..size];
+        let weights = &weights[..size];
+        let options = self.grimshaw_options.unwrap_or_default();
+        let mut best: Option<(F, F, F)> = None;
+        for estimator in [EstimatorKind::MomentMatch, EstimatorKind::Grimshaw] {
+            if !self.fit_strategy.tries(estimator, size) {
+                continue;
+            }
+            self.calls.count(estimator);
+            let (gamma, sigma, log_likelihood) = if estimator == EstimatorKind::Grimshaw {
+                weighted_grimshaw_estimator(values, weights, options)
+            } else {
+                weighted_mom_estimator(values, weights)
+            };
+            if !self.is_admissible(gamma) {
+                continue;
+            }
+            if is_better(best.map(|(_, _, best)| best), log_likelihood) {
+                best = Some((gamma, sigma, log_likelihood));
+            }
+        }
+
+        let Some((gamma, sigma, log_likelihood)) = best else {
+            return F::nan();
+        };
+        self.set_gpd(gamma, sigma);
+        log_likelihood
     }
 
     /// Compute the probability P(X > z) = p given the tail threshold difference d = z - t
-    pub fn probability(&self, s: f64, d: f64) -> f64 {
-        if self.gamma.is_nan() || self.sigma.is_nan() || self.sigma <= 0.0 {
-            return f64::NAN;
+    ///
+    /// Returns exactly `0.0` beyond the upper endpoint of the support (gamma < 0)
+    /// and when `d` is so large in a heavy tail that the computation overflows.
+    ///
+    /// See [`with_stable_small_gamma`](Self::with_stable_small_gamma) for
+    /// 0 < |gamma| < 1e-4.
+    pub fn probability(&self, s: F, d: F) -> F {
+        if self.gamma.is_nan() || self.sigma.is_nan() || self.sigma <= F::zero() {
+            return F::nan();
         }
 
-        // Use exact equality check like C implementation (no tolerance)
-        if self.gamma == 0.0 {
+        if self.exponential {
             s * xexp(-d / self.sigma)
         } else {
             let r = d * (self.gamma / self.sigma);
-            s * xpow(1.0 + r, -1.0 / self.gamma)
+            let base = F::one() + r;
+            if d > F::zero() && (base <= F::zero() || base == F::infinity()) {
+                return F::zero();
+            }
+            if self.is_small_gamma() {
+                return s * xexp(-r.ln_1p() / self.gamma);
+            }
+            s * xpow(base, -F::one() / self.gamma)
+        }
+    }
+
+    /// Compute the GPD probability density at the excess `d`
+    ///
+    /// This is the density of the excesses themselves, i.e. `-d/dd` of
+    /// [`probability`](Self::probability) for `s = 1`. Returns NaN if the tail
+    /// is not fitted or `d` is outside the support (`d < 0`, or beyond the upper
+    /// endpoint `-sigma / gamma` when gamma < 0).
+    pub fn pdf(&self, d: F) -> F {
+        if self.gamma.is_nan() || self.sigma.is_nan() || self.sigma <= F::zero() {
+            return F::nan();
+        }
+        if d.is_nan() || d < F::zero() {
+            return F::nan();
+        }
+
+        if self.exponential {
+            xexp(-d / self.sigma) / self.sigma
+        } else {
+            let base = F::one() + d * (self.gamma / self.sigma);
+            if base < F::zero() {
+                return F::nan();
+            }
+            xpow(base, -F::one() / self.gamma - F::one()) / self.sigma
         }
     }
 
     /// Compute the extreme quantile for given probability q
     /// s is the ratio Nt/n (an estimator of P(X>t) = 1-F(t))
     /// q is the desired low probability
-    pub fn quantile(&self, s: f64, q: f64) -> f64 {
-        if self.gamma.is_nan() || self.sigma.is_nan() || self.sigma <= 0.0 {
-            return f64::NAN;
+    ///
+    /// For `q == 0.0` this is +∞ when gamma >= 0 and the upper endpoint of the
+    /// support when gamma < 0.
+    ///
+    /// See [`with_stable_small_gamma`](Self::with_stable_small_gamma) for
+    /// 0 < |gamma| < 1e-4.
+    pub fn quantile(&self, s: F, q: F) -> F {
+        if self.gamma.is_nan() || self.sigma.is_nan() || self.sigma <= F::zero() {
+            return F::nan();
         }
 
         let r = q / s;
-        // Use exact equality check like C implementation (no tolerance)
-        if self.gamma == 0.0 {
+        if self.exponential {
             -self.sigma * xlog(r)
+        } else if self.is_small_gamma() {
+            (self.sigma / self.gamma) * (-self.gamma * xlog(r)).exp_m1()
         } else {
-            (self.sigma / self.gamma) * (xpow(r, -self.gamma) - 1.0)
+            (self.sigma / self.gamma) * (xpow(r, -self.gamma) - F::one())
         }
     }
 
+    /// Compute the standard error of [`quantile`](Self::quantile) for the same
+    /// `s` and `q` with the delta method
+    ///
+    /// Uses the asymptotic covariance of the maximum likelihood GPD estimates
+    /// over the current peaks (Smith, 1987), which only holds for gamma > -0.5;
+    /// returns NaN otherwise or if the tail is not fitted. The uncertainty on `s`
+    /// is not taken into account.
+    pub fn quantile_std_error(&self, s: F, q: F) -> F {
+        let size = self.peaks.size();
+        let half = F::of(0.5);
+        if size == 0 || self.gamma.is_nan() || self.sigma.is_nan() || self.sigma <= F::zero() {
+            return F::nan();
+        }
+        if self.gamma <= -half {
+            return F::nan();
+        }
+
+        let (gamma, sigma) = (self.gamma, self.sigma);
+        let r = q / s;
+        let log_ratio = -xlog(r);
+        // Partial derivatives of the quantile with respect to gamma and sigma
+        let (d_gamma, d_sigma) = if self.exponential {
+            (half * sigma * log_ratio * log_ratio, log_ratio)
+        } else {
+            let y = xpow(r, -gamma);
+            (
+                sigma / gamma * (y * log_ratio - (y - F::one()) / gamma),
+                (y - F::one()) / gamma,
+            )
+        };
+
+        // Cov(gamma, sigma) = (1 + gamma) / n * [[1 + gamma, -sigma], [-sigma, 2 sigma^2]]
+        let two = F::of(2.0);
+        let variance = (F::one() + gamma) / F::from_usize(size)
+            * ((F::one() + gamma) * d_gamma * d_gamma - two * sigma * d_gamma * d_sigma
+                + two * sigma * sigma * d_sigma * d_sigma);
+        variance.sqrt()
+    }
+
     /// Get the current gamma parameter
-    pub fn gamma(&self) -> f64 {
+    pub fn gamma(&self) -> F {
         self.gamma
     }
 
     /// Get the current sigma parameter
-    pub fn sigma(&self) -> f64 {
+    pub fn sigma(&self) -> F {
         self.sigma
     }
 
+    /// Set GPD parameters estimated outside of the crate
+    ///
+    /// This drives [`quantile`](Self::quantile) and
+    /// [`probability`](Self::probability) with a GPD fitted by another library.
+    /// The stored excesses are left as is, and the next [`fit`](Self::fit)
+    /// replaces the parameters. Fails with [`SpotError::InvalidGpdParameters`],
+    /// leaving the tail unchanged, unless both parameters are finite and
+    /// `sigma > 0`.
+    pub fn set_parameters(&mut self, gamma: F, sigma: F) -> SpotResult<()> {
+        if !gamma.is_finite() || !sigma.is_finite() || sigma <= F::zero() {
+            return Err(SpotError::InvalidGpdParameters);
+        }
+        self.set_gpd(gamma, sigma);
+        Ok(())
+    }
+
     /// Get the current size of the tail data
     pub fn size(&self) -> usize {
         self.peaks.size()
     }
 
     /// Get access to the underlying peaks structure
-    pub fn peaks(&self) -> &Peaks {
+    pub fn peaks(&self) -> &Peaks<F, B> {
         &self.peaks
     }
 }
 
+/// Whether a candidate fit replaces the best one so far (the first candidate,
+/// or a higher log-likelihood than a NaN or lower best)
+fn is_better<F: Float>(best: Option<F>, log_likelihood: F) -> bool {
+    match best {
+        None => true,
+        Some(best) => best.is_nan() || log_likelihood > best,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::error::SpotError;
+    use crate::estimator::{
+        compute_log_likelihood, pwm_estimator, GrimshawEstimator, MomEstimator,
+    };
+    use crate::testing::SeededExp;
+    use approx::assert_relative_eq;
 
     #[test]
     fn test_tail_reset_clears_gpd_params_and_peaks() {
@@ -170,6 +671,26 @@ mod tests {
         assert!(tail.sigma().is_nan());
     }
 
+    #[test]
+    fn test_tail_exponential_flag_matches_gamma_after_every_fit() {
+        let mut tail = Tail::new(50).unwrap();
+        let mut rng = SeededExp::new(6);
+        let mut exponential_fits = 0;
+        for _ in 0..300 {
+            tail.push(rng.next());
+            tail.fit();
+            assert_eq!(tail.exponential, tail.gamma() == 0.0);
+            exponential_fits += tail.exponential as usize;
+        }
+        // The sample is chosen so that the exponential branch is exercised too.
+        assert!(exponential_fits > 0);
+
+        tail.set_gpd(0.0, 2.0);
+        assert!(tail.exponential);
+        tail.reset();
+        assert!(!tail.exponential);
+    }
+
     #[test]
     fn test_tail_creation() {
         let tail = Tail::new(10).unwrap();
@@ -230,8 +751,7 @@ mod tests {
         let mut tail = Tail::new(10).unwrap();
 
         // Manually set parameters for testing
-        tail.gamma = 0.0;
-        tail.sigma = 1.0;
+        tail.set_gpd(0.0, 1.0);
 
         let q = tail.quantile(0.1, 0.01);
         assert!(!q.is_nan());
@@ -243,8 +763,7 @@ mod tests {
         let mut tail = Tail::new(10).unwrap();
 
         // Manually set parameters for testing
-        tail.gamma = 0.1;
-        tail.sigma = 1.0;
+        tail.set_gpd(0.1, 1.0);
 
         let q = tail.quantile(0.1, 0.01);
         assert!(!q.is_nan());
@@ -256,8 +775,7 @@ mod tests {
         let mut tail = Tail::new(10).unwrap();
 
         // Manually set parameters for testing
-        tail.gamma = 0.0;
-        tail.sigma = 1.0;
+        tail.set_gpd(0.0, 1.0);
 
         let p = tail.probability(0.1, 2.0);
         assert!(!p.is_nan());
@@ -269,21 +787,377 @@ mod tests {
         let mut tail = Tail::new(10).unwrap();
 
         // Manually set parameters for testing
-        tail.gamma = 0.1;
-        tail.sigma = 1.0;
+        tail.set_gpd(0.1, 1.0);
 
         let p = tail.probability(0.1, 2.0);
         assert!(!p.is_nan());
         assert!(p >= 0.0);
     }
 
+    #[test]
+    fn test_tail_probability_overflow_is_zero() {
+        let mut tail = Tail::new(10).unwrap();
+
+        // Heavy tail, huge excess
+        tail.set_gpd(0.5, 1.0);
+        assert_eq!(tail.probability(0.1, 1e300), 0.0);
+        assert_eq!(tail.probability(0.1, f64::MAX), 0.0);
+        assert_eq!(tail.probability(0.1, f64::INFINITY), 0.0);
+
+        // 1 + d * gamma / sigma overflows to infinity
+        tail.set_gpd(2.0, 1e-10);
+        assert_eq!(tail.probability(0.1, 1e300), 0.0);
+
+        // Exponential tail
+        tail.set_gpd(0.0, 1.0);
+        assert_eq!(tail.probability(0.1, 1e300), 0.0);
+
+        // Beyond the upper endpoint (sigma / |gamma| = 4) of a bounded tail
+        tail.set_gpd(-0.25, 1.0);
+        assert_eq!(tail.probability(0.1, 5.0), 0.0);
+        assert!(tail.probability(0.1, 3.0) > 0.0);
+    }
+
+    #[test]
+    fn test_tail_quantile_at_zero_probability() {
+        let mut tail = Tail::new(10).unwrap();
+
+        tail.set_gpd(0.5, 1.0);
+        assert_eq!(tail.quantile(0.1, 0.0), f64::INFINITY);
+
+        tail.set_gpd(0.0, 1.0);
+        assert_eq!(tail.quantile(0.1, 0.0), f64::INFINITY);
+
+        tail.set_gpd(-0.25, 1.0);
+        assert_eq!(tail.quantile(0.1, 0.0), 4.0);
+    }
+
+    /// Exact GPD quantiles at the midpoints of `n` equal probability bins
+    fn gpd_tail(n: usize, gamma: f64, sigma: f64, pwm: bool) -> Tail {
+        let mut tail = Tail::new(n).unwrap().with_pwm(pwm);
+        for i in 0..n {
+            let u = (i as f64 + 0.5) / n as f64;
+            tail.push(sigma / gamma * ((1.0 - u).powf(-gamma) - 1.0));
+        }
+        tail
+    }
+
+    #[test]
+    fn test_tail_fit_with_pwm_keeps_best_log_likelihood() {
+        for (gamma, sigma) in [(0.7, 1.0), (0.2, 2.0), (-0.3, 1.5)] {
+            let mut c_pair = gpd_tail(30, gamma, sigma, false);
+            let c_llhood = c_pair.fit();
+
+            let mut all = gpd_tail(30, gamma, sigma, true);
+            let llhood = all.fit();
+
+            let (_, _, pwm_llhood) = pwm_estimator(&all.peaks);
+            assert_eq!(llhood, c_llhood.max(pwm_llhood));
+            if pwm_llhood > c_llhood {
+                assert_eq!((all.gamma(), all.sigma()), {
+                    let (g, s, _) = pwm_estimator(&all.peaks);
+                    (g, s)
+                });
+            } else {
+                assert_eq!((all.gamma(), all.sigma()), (c_pair.gamma(), c_pair.sigma()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_tail_pdf_integrates_survival() {
+        let mut tail = Tail::new(1).unwrap();
+        for (gamma, sigma, end) in [(0.0, 1.5, 30.0), (0.3, 1.0, 50.0), (-0.25, 2.0, 8.0)] {
+            tail.set_gpd(gamma, sigma);
+
+            // Trapezoidal rule over [0, end], the rest of the mass is the survival
+            let steps = 100_000;
+            let h = end / steps as f64;
+            let mut integral = 0.5 * (tail.pdf(0.0) + tail.pdf(end));
+            for i in 1..steps {
+                integral += tail.pdf(i as f64 * h);
+            }
+            integral *= h;
+            assert_relative_eq!(integral + tail.probability(1.0, end), 1.0, epsilon = 1e-6);
+
+            // The density is minus the derivative of the survival function
+            for d in [0.1, 1.0, 3.0, 7.5] {
+                let eps = 1e-6;
+                let derivative =
+                    (tail.probability(1.0, d + eps) - tail.probability(1.0, d - eps)) / (2.0 * eps);
+                assert_relative_eq!(-derivative, tail.pdf(d), max_relative = 1e-6);
+            }
+
+            assert!(tail.pdf(-0.1).is_nan());
+        }
+
+        // Beyond the upper endpoint (8 for gamma = -0.25 and sigma = 2)
+        assert!(tail.pdf(8.5).is_nan());
+        tail.set_gpd(f64::NAN, 1.0);
+        assert!(tail.pdf(1.0).is_nan());
+    }
+
+    #[test]
+    fn test_tail_retain_rebuilds_peaks() {
+        let mut tail = gpd_tail(100, 0.3, 1.0, false);
+        tail.fit();
+        let (gamma, sigma) = (tail.gamma(), tail.sigma());
+        let median = tail.peaks().quantile(0.5);
+
+        assert_eq!(tail.retain(|x| x >= median), 50);
+        assert_eq!(tail.size(), 50);
+        assert_eq!(
+            tail.peaks().min(),
+            tail.peaks()
+                .container()
+                .iter()
+                .fold(f64::INFINITY, f64::min)
+        );
+        let sum: f64 = tail.peaks().container().iter().sum();
+        assert!((tail.peaks().sum() - sum).abs() < 1e-12);
+        // Parameters are only updated by the next fit
+        assert_eq!((tail.gamma(), tail.sigma()), (gamma, sigma));
+
+        assert_eq!(tail.retain(|_| true), 0);
+        assert_eq!(tail.retain(|_| false), 50);
+        assert_eq!(tail.size(), 0);
+        assert!(tail.peaks().min().is_nan());
+    }
+
+    #[test]
+    fn test_tail_fit_report_grimshaw_wins_heavy_tail() {
+        // With gamma >= 0.5 the variance is infinite and MoM is far off
+        let mut tail = gpd_tail(200, 0.6, 1.0, false);
+        let mut plain = gpd_tail(200, 0.6, 1.0, false);
+
+        let report = tail.fit_report().unwrap();
+        assert_eq!(report.estimator, EstimatorKind::Grimshaw);
+        assert_eq!((report.gamma, report.sigma), (tail.gamma(), tail.sigma()));
+        assert!((report.gamma - 0.6).abs() < 0.1, "gamma = {}", report.gamma);
+        assert_eq!(report.log_likelihood, plain.fit());
+        assert_eq!((plain.gamma(), plain.sigma()), (tail.gamma(), tail.sigma()));
+
+        let (_, _, mom_llhood) = EstimatorKind::MomentMatch.estimate(&tail.peaks);
+        assert!(report.log_likelihood > mom_llhood);
+    }
+
+    #[test]
+    fn test_tail_fit_strategy() {
+        let grimshaw_calls = |tail: &Tail<f64>| tail.estimator_calls().1;
+
+        // Grimshaw wins on a heavy tail, so running it alone changes nothing
+        let mut both = gpd_tail(200, 0.6, 1.0, false);
+        let mut grimshaw =
+            gpd_tail(200, 0.6, 1.0, false).with_fit_strategy(FitStrategy::GrimshawOnly);
+        assert_eq!(
+            both.fit_report().unwrap().estimator,
+            EstimatorKind::Grimshaw
+        );
+        let report = grimshaw.fit_report().unwrap();
+        assert_eq!(report.estimator, EstimatorKind::Grimshaw);
+        assert_eq!(
+            (grimshaw.gamma(), grimshaw.sigma()),
+            (both.gamma(), both.sigma())
+        );
+
+        assert_eq!(grimshaw_calls(&grimshaw), 1);
+
+        let mut mom = gpd_tail(200, 0.6, 1.0, false).with_fit_strategy(FitStrategy::MomOnly);
+        assert_eq!(
+            mom.fit_report().unwrap().estimator,
+            EstimatorKind::MomentMatch
+        );
+        assert_eq!(mom.estimator_calls(), (1, 0));
+
+        // Auto skips Grimshaw on small tails only
+        let small = FitStrategy::AUTO_MOM_MAX_PEAKS - 1;
+        let mut auto = gpd_tail(small, 0.6, 1.0, false).with_fit_strategy(FitStrategy::Auto);
+        assert_eq!(
+            auto.fit_report().unwrap().estimator,
+            EstimatorKind::MomentMatch
+        );
+        assert_eq!(grimshaw_calls(&auto), 0);
+        let mut auto = gpd_tail(200, 0.6, 1.0, false).with_fit_strategy(FitStrategy::Auto);
+        assert_eq!(
+            auto.fit_report().unwrap().estimator,
+            EstimatorKind::Grimshaw
+        );
+        assert_eq!(grimshaw_calls(&auto), 1);
+        assert_eq!((auto.gamma(), auto.sigma()), (both.gamma(), both.sigma()));
+
+        // PWM stays a candidate whatever the strategy
+        let mut pwm = gpd_tail(small, 0.6, 1.0, true).with_fit_strategy(FitStrategy::GrimshawOnly);
+        let (_, _, pwm_llhood) = pwm_estimator(&pwm.peaks);
+        assert!(pwm.fit() >= pwm_llhood);
+        assert_eq!(Tail::new(10).unwrap().fit_strategy(), FitStrategy::Both);
+    }
+
+    #[test]
+    fn test_tail_min_gamma_rejects_irregular_estimates() {
+        // Evenly spread values: a GPD with gamma = -1, where the likelihood is
+        // irregular and the method of moments wins with gamma close to -1
+        let uniform_tail = |min_gamma| {
+            let mut tail = Tail::new(100).unwrap().with_min_gamma(min_gamma);
+            for i in 0..100 {
+                tail.push((i as f64 + 0.5) / 100.0);
+            }
+            tail
+        };
+
+        let mut unguarded = uniform_tail(None);
+        let report = unguarded.fit_report().unwrap();
+        assert_eq!(report.estimator, EstimatorKind::MomentMatch);
+        assert!(report.gamma < -0.9, "gamma = {}", report.gamma);
+
+        // The guarded fit falls back on Grimshaw
+        let mut guarded = uniform_tail(Some(-0.5));
+        assert_eq!(guarded.min_gamma(), Some(-0.5));
+        let report = guarded.fit_report().unwrap();
+        assert_eq!(report.estimator, EstimatorKind::Grimshaw);
+        assert!(report.gamma >= -0.5 && report.sigma > 0.0);
+        let (gamma, sigma, _) = GrimshawEstimator.estimate(&guarded.peaks);
+        assert_eq!((guarded.gamma(), guarded.sigma()), (gamma, sigma));
+        assert_eq!(
+            guarded.fit_with(&[&MomEstimator, &GrimshawEstimator]),
+            report.log_likelihood
+        );
+
+        // The cutoff is configurable
+        let mut loose = uniform_tail(Some(-1.5));
+        assert_eq!(loose.fit_report().unwrap(), unguarded.fit_report().unwrap());
+
+        // Nothing is selected when every estimate is rejected
+        let mut strict = uniform_tail(Some(1.0));
+        assert_eq!(strict.fit_report(), None);
+        assert!(strict.gamma().is_nan());
+    }
+
+    #[test]
+    fn test_tail_min_gamma_rejects_grimshaw_estimates() {
+        // A short GPD tail on which the maximum likelihood (Grimshaw) estimate
+        // itself is below -0.5, and beats the method of moments
+        let mut unguarded = gpd_tail(200, -0.6, 1.0, false);
+        let report = unguarded.fit_report().unwrap();
+        assert_eq!(report.estimator, EstimatorKind::Grimshaw);
+        assert!(report.gamma < -0.6, "gamma = {}", report.gamma);
+        let (mom_gamma, mom_sigma, _) = MomEstimator.estimate(&unguarded.peaks);
+        assert!(mom_gamma > -0.61 && mom_gamma < -0.5);
+
+        // Rejecting the Grimshaw estimate only selects the method of moments
+        let mut guarded = unguarded.clone().with_min_gamma(Some(-0.61));
+        let report = guarded.fit_report().unwrap();
+        assert_eq!(report.estimator, EstimatorKind::MomentMatch);
+        assert_eq!((guarded.gamma(), guarded.sigma()), (mom_gamma, mom_sigma));
+
+        // Rejecting both leaves the parameters as they were: those of the
+        // previous fit, or NaN (and so NaN quantiles) for a tail never fitted
+        let mut rejected = guarded.clone().with_min_gamma(Some(-0.5));
+        assert_eq!(rejected.fit_report(), None);
+        assert!(rejected.fit().is_nan());
+        assert_eq!((rejected.gamma(), rejected.sigma()), (mom_gamma, mom_sigma));
+        let mut fresh = gpd_tail(200, -0.6, 1.0, false).with_min_gamma(Some(-0.5));
+        assert_eq!(fresh.fit_report(), None);
+        assert!(fresh.gamma().is_nan() && fresh.quantile(0.01, 1e-4).is_nan());
+        assert_eq!(fresh.estimator_calls(), (1, 1));
+    }
+
+    #[test]
+    fn test_tail_fit_report_empty_and_pwm() {
+        let mut tail = Tail::new(8).unwrap().with_pwm(true);
+        assert_eq!(tail.fit_report(), None);
+
+        for x in [0.08, 1.06, 1.35, 0.12, 0.18, 0.11, 0.14, 0.03] {
+            tail.push(x);
+        }
+        assert_eq!(tail.fit_report().unwrap().estimator, EstimatorKind::Pwm);
+    }
+
+    /// Estimator returning fixed parameters with their log-likelihood
+    struct Fixed(f64, f64);
+
+    impl GpdEstimator for Fixed {
+        fn estimate(&self, peaks: &Peaks) -> (f64, f64, f64) {
+            (
+                self.0,
+                self.1,
+                compute_log_likelihood(peaks, self.0, self.1),
+            )
+        }
+    }
+
+    #[test]
+    fn test_tail_fit_with_custom_estimator() {
+        let mut tail = gpd_tail(200, 0.6, 1.0, false);
+        let mut plain = gpd_tail(200, 0.6, 1.0, false);
+        let fixed = Fixed(0.6, 1.0);
+
+        // The true parameters beat MoM on this heavy tail
+        let llhood = tail.fit_with(&[&MomEstimator, &fixed]);
+        assert_eq!((tail.gamma(), tail.sigma()), (0.6, 1.0));
+        assert_eq!(llhood, fixed.estimate(&tail.peaks).2);
+
+        // ... but not the maximum likelihood estimate
+        let llhood = tail.fit_with(&[&MomEstimator, &GrimshawEstimator, &fixed]);
+        assert_eq!(llhood, plain.fit());
+        assert_eq!((tail.gamma(), tail.sigma()), (plain.gamma(), plain.sigma()));
+        assert_eq!(
+            tail.fit_with(&[&EstimatorKind::MomentMatch, &EstimatorKind::Grimshaw]),
+            llhood
+        );
+
+        assert!(tail.fit_with(&[]).is_nan());
+        assert_eq!((tail.gamma(), tail.sigma()), (plain.gamma(), plain.sigma()));
+        assert!(Tail::new(4).unwrap().fit_with(&[&fixed]).is_nan());
+    }
+
+    #[test]
+    fn test_tail_grimshaw_options() {
+        let mut tail = gpd_tail(200, 0.6, 1.0, false);
+        assert_eq!(tail.grimshaw_options(), None);
+        assert_eq!(
+            tail.fit_report().unwrap().estimator,
+            EstimatorKind::Grimshaw
+        );
+
+        // Without iterations Grimshaw falls back to the exponential candidate
+        let options = GrimshawOptions {
+            max_iter: 0,
+            ..GrimshawOptions::default()
+        };
+        let mut capped = gpd_tail(200, 0.6, 1.0, false).with_grimshaw_options(Some(options));
+        assert_eq!(capped.grimshaw_options(), Some(options));
+        let (gamma, _, _) = grimshaw_estimator_with(&capped.peaks, options);
+        capped.fit();
+        assert_eq!(gamma, 0.0);
+        assert_ne!(capped.gamma(), tail.gamma());
+    }
+
+    #[test]
+    fn test_tail_fit_with_pwm_small_tail() {
+        // On this small tail PWM beats both C estimators
+        let data = [0.08, 1.06, 1.35, 0.12, 0.18, 0.11, 0.14, 0.03];
+        let mut c_pair = Tail::new(8).unwrap();
+        let mut all = Tail::new(8).unwrap().with_pwm(true);
+        for x in data {
+            c_pair.push(x);
+            all.push(x);
+        }
+
+        let c_llhood = c_pair.fit();
+        let llhood = all.fit();
+        let (gamma, sigma, pwm_llhood) = pwm_estimator(&all.peaks);
+
+        assert!(llhood > c_llhood);
+        assert_eq!(llhood, pwm_llhood);
+        assert_eq!((all.gamma(), all.sigma()), (gamma, sigma));
+    }
+
     #[test]
     fn test_tail_invalid_parameters() {
         let mut tail = Tail::new(10).unwrap();
 
         // Test with invalid sigma
-        tail.gamma = 0.1;
-        tail.sigma = 0.0;
+        tail.set_gpd(0.1, 0.0);
 
         let q = tail.quantile(0.1, 0.01);
         assert!(q.is_nan());
@@ -292,6 +1166,135 @@ mod tests {
         assert!(p.is_nan());
     }
 
+    #[test]
+    fn test_tail_quantile_inverts_probability() {
+        // Deterministic pseudo-random uniforms in [0, 1)
+        let mut state = 0x2545_f491_4f6c_dd1d_u64;
+        let mut uniform = move || {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 11) as f64 / (1u64 << 53) as f64
+        };
+
+        // The C formulas lose the inversion to cancellation for gamma near 0
+        let mut tail = Tail::new(1).unwrap().with_stable_small_gamma(true);
+        for i in 0..2000 {
+            // Exponential (gamma == 0), almost exponential and general tails
+            let gamma = match i % 4 {
+                0 => 0.0,
+                1 => 1e-9 * (uniform() - 0.5),
+                _ => 1.2 * uniform() - 0.4,
+            };
+            let sigma = 0.1 + 5.0 * uniform();
+            let s = 0.001 + 0.5 * uniform();
+            tail.set_gpd(gamma, sigma);
+            assert_eq!(tail.exponential, gamma == 0.0);
+
+            // An excess inside the support
+            let mut d = 10.0 * sigma * uniform();
+            if gamma < 0.0 {
+                d = d.min(-0.9 * sigma / gamma);
+            }
+            let p = tail.probability(s, d);
+            assert!(p > 0.0 && p <= s, "gamma = {gamma}, p = {p}");
+            let recovered = tail.quantile(s, p);
+            assert!(
+                (recovered - d).abs() <= 1e-9 * d.max(1.0),
+                "gamma = {gamma}, sigma = {sigma}, d = {d}, recovered = {recovered}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_tail_small_gamma_departs_from_c_by_rounding_only() {
+        // The C formulas, with the continued fraction power
+        let c_quantile = |gamma: f64, sigma: f64, r: f64| (sigma / gamma) * (xpow(r, -gamma) - 1.0);
+        let c_probability = |gamma: f64, sigma: f64, s: f64, d: f64| {
+            s * xpow(1.0 + d * (gamma / sigma), -1.0 / gamma)
+        };
+
+        // By default, the C formulas are used for any gamma
+        let mut c_tail = Tail::new(1).unwrap();
+        assert!(!c_tail.uses_stable_small_gamma());
+        for gamma in [1e-9, -1e-6, 9.9e-5] {
+            c_tail.set_gpd(gamma, 1.5);
+            assert_eq!(
+                c_tail.quantile(0.02, 1e-4),
+                c_quantile(gamma, 1.5, 1e-4 / 0.02)
+            );
+            assert_eq!(
+                c_tail.probability(0.02, 3.0),
+                c_probability(gamma, 1.5, 0.02, 3.0)
+            );
+        }
+
+        let mut tail = Tail::new(1).unwrap().with_stable_small_gamma(true);
+        for gamma in [1e-9, -1e-9, 1e-7, 1e-6, -1e-6, 1e-5, -3e-5, 9.9e-5, -9.9e-5] {
+            for (s, q) in [(0.05, 1e-3), (0.02, 1e-4), (0.01, 1e-6)] {
+                tail.set_gpd(gamma, 1.5);
+                let bound = 1e-15 / gamma.abs();
+                let z = tail.quantile(s, q);
+                let c_z = c_quantile(gamma, 1.5, q / s);
+                assert!(((z - c_z) / c_z).abs() < bound, "gamma = {gamma}, q = {q}");
+                let p = tail.probability(s, z);
+                let c_p = c_probability(gamma, 1.5, s, z);
+                assert!(((p - c_p) / c_p).abs() < bound, "gamma = {gamma}, q = {q}");
+            }
+        }
+
+        // Above the cutoff, the C formulas are used as is
+        for gamma in [1e-4, -1e-4, 0.3] {
+            tail.set_gpd(gamma, 1.5);
+            assert_eq!(
+                tail.quantile(0.02, 1e-4),
+                c_quantile(gamma, 1.5, 1e-4 / 0.02)
+            );
+            assert_eq!(
+                tail.probability(0.02, 3.0),
+                c_probability(gamma, 1.5, 0.02, 3.0)
+            );
+        }
+    }
+
+    #[test]
+    fn test_tail_set_parameters() {
+        let mut tail = Tail::new(10).unwrap();
+        tail.set_parameters(0.5, 2.0).unwrap();
+        assert_eq!((tail.gamma(), tail.sigma()), (0.5, 2.0));
+
+        // q = (sigma / gamma) * ((q / s)^-gamma - 1) = 4 * (0.01^-0.5 - 1) = 36
+        assert_relative_eq!(tail.quantile(0.1, 0.001), 36.0, max_relative = 1e-12);
+        // p = s * (1 + gamma d / sigma)^(-1 / gamma) = 0.1 * 10^-2
+        assert_relative_eq!(tail.probability(0.1, 36.0), 0.001, max_relative = 1e-12);
+
+        // Exponential tail: q = -sigma ln(q / s), p = s exp(-d / sigma)
+        tail.set_parameters(0.0, 2.0).unwrap();
+        assert_relative_eq!(
+            tail.quantile(0.1, 0.001),
+            2.0 * 100f64.ln(),
+            max_relative = 1e-12
+        );
+        assert_relative_eq!(
+            tail.probability(0.1, 2.0),
+            0.1 * (-1f64).exp(),
+            max_relative = 1e-12
+        );
+
+        for (gamma, sigma) in [
+            (0.1, 0.0),
+            (0.1, -1.0),
+            (f64::NAN, 1.0),
+            (0.1, f64::INFINITY),
+        ] {
+            assert_eq!(
+                tail.set_parameters(gamma, sigma),
+                Err(SpotError::InvalidGpdParameters)
+            );
+        }
+        assert_eq!((tail.gamma(), tail.sigma()), (0.0, 2.0));
+    }
+
     #[test]
     fn test_tail_consistency() {
         let mut tail = Tail::new(10).unwrap();
@@ -317,4 +1320,26 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_tail_over_array_ubend_matches_ubend() {
+        use crate::ubend::ArrayUbend;
+
+        let mut tail = Tail::new(100).unwrap();
+        let mut array = Tail::from_peaks(Peaks::from_buffer(ArrayUbend::<100>::new()));
+        let mut rng = SeededExp::new(4);
+        for _ in 0..250 {
+            let x = rng.next();
+            tail.push(x);
+            array.push(x);
+        }
+
+        assert_eq!(array.fit(), tail.fit());
+        assert_eq!((array.gamma(), array.sigma()), (tail.gamma(), tail.sigma()));
+        assert_eq!(array.quantile(0.05, 1e-4), tail.quantile(0.05, 1e-4));
+        assert_eq!(
+            array.fit_with(&[&MomEstimator, &GrimshawEstimator]),
+            tail.fit_with(&[&MomEstimator, &GrimshawEstimator])
+        );
+    }
 }
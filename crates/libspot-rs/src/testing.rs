@@ -0,0 +1,84 @@
+//! Reproducible data for examples and tests
+//!
+//! Enabled with the `testing` feature. The generator does not depend on the
+//! platform or on libc, so the same seed gives the same stream everywhere.
+
+/// Seeded generator of standard exponential variates (rate 1)
+///
+/// The state is advanced by the 64-bit linear congruential generator
+/// `state = state * 6364136223846793005 + 1442695040888963407` (wrapping), and
+/// each variate is `-ln(u)` with `u = ((state >> 11) + 0.5) / 2^53`, a uniform
+/// in (0, 1) built from the 53 high bits. The first values for the seed 42 are
+/// `0.5652284378010116`, `1.489597310806909` and `0.8846992425010287`.
+///
+/// This is not the generator of the C examples (`srand`/`rand` from libc), so
+/// the results differ from theirs.
+///
+/// ```
+/// use libspot_rs::testing::SeededExp;
+/// use libspot_rs::{SpotConfig, SpotDetector};
+///
+/// let mut rng = SeededExp::new(42);
+/// let data: Vec<f64> = (0..10_000).map(|_| rng.next()).collect();
+/// let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+/// spot.fit(&data).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct SeededExp {
+    state: u64,
+}
+
+impl SeededExp {
+    /// Create a generator starting from `seed`
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Draw the next exponential variate
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> f64 {
+        self.state = self
+            .state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        let u = ((self.state >> 11) as f64 + 0.5) / (1u64 << 53) as f64;
+        -num_traits::Float::ln(u)
+    }
+}
+
+/// `n` exponential variates drawn with [`SeededExp`], the fixture of the
+/// unit tests
+#[cfg(test)]
+pub(crate) fn exponential_data(n: usize, seed: u64) -> alloc::vec::Vec<f64> {
+    let mut rng = SeededExp::new(seed);
+    (0..n).map(|_| rng.next()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_seeded_exp_documented_values() {
+        let mut rng = SeededExp::new(42);
+        let expected = [
+            0.5652284378010116,
+            1.489597310806909,
+            0.8846992425010287,
+            0.4614038339718091,
+            0.3854451408406808,
+        ];
+        for value in expected {
+            assert_relative_eq!(rng.next(), value, max_relative = 1e-15);
+        }
+
+        // Same seed, same stream
+        let a: Vec<f64> = {
+            let mut rng = SeededExp::new(7);
+            (0..100).map(|_| rng.next()).collect()
+        };
+        let mut rng = SeededExp::new(7);
+        assert!(a.iter().all(|&x| x == rng.next() && x > 0.0));
+    }
+}
@@ -0,0 +1,154 @@
+//! Timestamped SPOT detection
+//!
+//! Streams often come as `(timestamp, value)` pairs. [`TimedSpot`] wraps a
+//! [`SpotDetector`] to carry the timestamps along the statuses and remember
+//! when the last anomaly occurred, leaving the algorithm itself unchanged.
+
+use crate::config::SpotConfig;
+use crate::error::SpotResult;
+use crate::float::Float;
+use crate::spot::SpotDetector;
+use crate::status::SpotStatus;
+
+/// Classification of a timestamped value
+///
+/// # Serialization
+///
+/// When the `serde` feature is enabled, this struct can be serialized and deserialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimedStatus {
+    /// Status of the value
+    pub status: SpotStatus,
+    /// Timestamp of the value
+    pub timestamp: i64,
+}
+
+/// SPOT detector keeping track of the timestamps of the processed values
+///
+/// The timestamps are opaque `i64` values (e.g. Unix milliseconds): they are
+/// only stored, so they do not have to be increasing.
+///
+/// # Example
+///
+/// ```
+/// use libspot_rs::{SpotConfig, SpotStatus, TimedSpot};
+///
+/// let mut spot = TimedSpot::new(SpotConfig::default()).unwrap();
+/// let data: Vec<f64> = (0..1000).map(|i| ((i * 37) % 1000) as f64 / 100.0).collect();
+/// spot.spot_mut().fit(&data).unwrap();
+///
+/// let status = spot.step_at(1_700_000_000, 50.0).unwrap();
+/// assert_eq!(status.status, SpotStatus::Anomaly);
+/// assert_eq!(spot.last_anomaly_ts(), Some(1_700_000_000));
+/// ```
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = ""))]
+pub struct TimedSpot<F: Float = f64> {
+    /// Underlying detector
+    spot: SpotDetector<F>,
+    /// Timestamp of the last processed value
+    last_ts: Option<i64>,
+    /// Timestamp of the last anomaly
+    last_anomaly_ts: Option<i64>,
+}
+
+impl TimedSpot {
+    /// Create a new timed `f64` detector with the given configuration
+    pub fn new(config: SpotConfig) -> SpotResult<Self> {
+        SpotDetector::new(config).map(Self::from_detector)
+    }
+}
+
+impl<F: Float> TimedSpot<F> {
+    /// Wrap an existing detector
+    pub fn from_detector(spot: SpotDetector<F>) -> Self {
+        Self {
+            spot,
+            last_ts: None,
+            last_anomaly_ts: None,
+        }
+    }
+
+    /// Unwrap the detector
+    pub fn into_inner(self) -> SpotDetector<F> {
+        self.spot
+    }
+
+    /// Get the underlying detector
+    pub fn spot(&self) -> &SpotDetector<F> {
+        &self.spot
+    }
+
+    /// Get the underlying detector mutably, e.g. to fit it
+    pub fn spot_mut(&mut self) -> &mut SpotDetector<F> {
+        &mut self.spot
+    }
+
+    /// Process a value observed at `ts` and return its status with the timestamp
+    ///
+    /// Fails like [`SpotDetector::step`], in which case the timestamps are not
+    /// updated.
+    pub fn step_at(&mut self, ts: i64, x: F) -> SpotResult<TimedStatus> {
+        let status = self.spot.step(x)?;
+        self.last_ts = Some(ts);
+        if status == SpotStatus::Anomaly {
+            self.last_anomaly_ts = Some(ts);
+        }
+        Ok(TimedStatus {
+            status,
+            timestamp: ts,
+        })
+    }
+
+    /// Get the timestamp of the last processed value
+    pub fn last_ts(&self) -> Option<i64> {
+        self.last_ts
+    }
+
+    /// Get the timestamp of the last value flagged as an anomaly
+    pub fn last_anomaly_ts(&self) -> Option<i64> {
+        self.last_anomaly_ts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::SpotError;
+
+    #[test]
+    fn test_timed_spot_tracks_anomaly_timestamps() {
+        let mut spot = TimedSpot::new(SpotConfig::default()).unwrap();
+        let data: Vec<f64> = (0..1000)
+            .map(|i| ((i * 37) % 1000) as f64 / 100.0)
+            .collect();
+        spot.spot_mut().fit(&data).unwrap();
+        assert_eq!((spot.last_ts(), spot.last_anomaly_ts()), (None, None));
+
+        let anomaly = spot.spot().anomaly_threshold() + 10.0;
+        let stream = [
+            (100, 5.0),
+            (110, anomaly),
+            (120, 1.0),
+            (130, 9.995),
+            (140, 2.0),
+        ];
+        for (ts, x) in stream {
+            let timed = spot.step_at(ts, x).unwrap();
+            assert_eq!(timed.timestamp, ts);
+            assert_eq!(spot.last_ts(), Some(ts));
+            if ts >= 110 {
+                assert_eq!(spot.last_anomaly_ts(), Some(110));
+            } else {
+                assert_eq!(spot.last_anomaly_ts(), None);
+            }
+            assert_eq!(timed.status == SpotStatus::Anomaly, ts == 110);
+        }
+
+        // Failed steps leave the timestamps unchanged
+        assert_eq!(spot.step_at(150, f64::NAN), Err(SpotError::DataIsNaN));
+        assert_eq!(spot.last_ts(), Some(140));
+    }
+}
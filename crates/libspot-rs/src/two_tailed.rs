@@ -0,0 +1,450 @@
+//! Two-tailed SPOT detector
+//!
+//! [`TwoTailedSpot`] models both tails of the distribution with a single
+//! observation counter, so high and low anomalies are detected without running
+//! two [`SpotDetector`](crate::SpotDetector)s side by side.
+
+use crate::config::SpotConfig;
+use crate::error::{SpotError, SpotResult};
+use crate::p2::P2Estimator;
+use crate::spot::finite_training_data;
+use crate::status::SpotStatus;
+use crate::tail::Tail;
+
+/// Classification of a value against both tails
+///
+/// # Serialization
+///
+/// When the `serde` feature is enabled, this struct can be serialized and deserialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TwoTailedStatus {
+    /// Status with respect to the upper tail
+    pub upper: SpotStatus,
+    /// Status with respect to the lower tail
+    pub lower: SpotStatus,
+}
+
+impl TwoTailedStatus {
+    /// Whether the value is an anomaly in either tail
+    pub fn is_anomaly(&self) -> bool {
+        self.upper == SpotStatus::Anomaly || self.lower == SpotStatus::Anomaly
+    }
+}
+
+/// One side of a [`TwoTailedSpot`]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Side {
+    /// Internal constant (+1.0 for the upper tail, -1.0 for the lower tail)
+    up_down: f64,
+    /// Normal/abnormal threshold
+    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_float"))]
+    anomaly_threshold: f64,
+    /// Tail threshold
+    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_float"))]
+    excess_threshold: f64,
+    /// Number of excesses in this tail
+    nt: usize,
+    /// GPD Tail
+    tail: Tail,
+}
+
+impl Side {
+    fn new(up_down: f64, config: &SpotConfig) -> SpotResult<Self> {
+        Ok(Self {
+            up_down,
+            anomaly_threshold: f64::NAN,
+            excess_threshold: f64::NAN,
+            nt: 0,
+            tail: Tail::new(config.max_excess)?
+                .with_pwm(config.pwm_estimator)
+                .with_grimshaw_options(config.grimshaw_options)
+                .with_fit_strategy(config.fit_strategy)
+                .with_min_gamma(config.min_gamma)
+                .with_stable_small_gamma(config.stable_small_gamma),
+        })
+    }
+
+    /// Signed distance of `value` beyond `threshold` in the direction of the tail
+    fn beyond(&self, value: f64, threshold: f64) -> f64 {
+        self.up_down * (value - threshold)
+    }
+
+    /// Quantile of the tail for the anomaly probability `q`
+    fn quantile(&self, q: f64, n: usize) -> f64 {
+        let s = (self.nt as f64) / (n as f64);
+        self.excess_threshold + self.up_down * self.tail.quantile(s, q)
+    }
+}
+
+/// SPOT detector monitoring the upper and the lower tail at once
+///
+/// The upper tail starts at the `level` quantile and the lower tail at the
+/// `1 - level` quantile of the training data. Both tails share the count of
+/// observed values, so each behaves like a [`SpotDetector`](crate::SpotDetector) on its own side, except that
+/// an anomaly discarded by one tail is not counted by the other either.
+///
+/// # Serialization
+///
+/// When the `serde` feature is enabled, the detector can be serialized and deserialized.
+///
+/// # Example
+///
+/// ```
+/// use libspot_rs::{SpotConfig, SpotStatus, TwoTailedSpot};
+///
+/// let mut spot = TwoTailedSpot::new(SpotConfig::default()).unwrap();
+/// let data: Vec<f64> = (0..1000).map(|i| ((i * 37) % 1000) as f64 / 100.0).collect();
+/// spot.fit(&data).unwrap();
+///
+/// let status = spot.step(-5.0).unwrap();
+/// assert_eq!(status.lower, SpotStatus::Anomaly);
+/// assert_eq!(status.upper, SpotStatus::Normal);
+/// ```
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TwoTailedSpot {
+    /// Probability of an anomaly in each tail
+    q: f64,
+    /// Location of the upper tail (the lower one starts at `1 - level`)
+    level: f64,
+    /// Flag anomalies (true = flag, false = don't flag)
+    discard_anomalies: bool,
+    /// Total number of seen data
+    n: usize,
+    /// Minimum number of peaks before `step` refits the GPD of a tail (0, the
+    /// former behavior, for models serialized before the option existed)
+    #[cfg_attr(feature = "serde", serde(default))]
+    min_peaks: usize,
+    /// Reject infinite values instead of keeping them out of the model
+    #[cfg_attr(
+        feature = "serde",
+        serde(default = "crate::config::default_reject_infinite")
+    )]
+    reject_infinite: bool,
+    /// Upper tail
+    upper: Side,
+    /// Lower tail
+    lower: Side,
+}
+
+impl TwoTailedSpot {
+    /// Create a new two-tailed detector with the given configuration
+    ///
+    /// The detector does not support `low_tail`, `track_excess_threshold`,
+    /// `warmup_steps`, `adaptive_tail` or `anomaly_rate_window`; setting any of
+    /// them returns [`SpotError::UnsupportedOption`].
+    pub fn new(config: SpotConfig) -> SpotResult<Self> {
+        config.validate()?;
+        if config.low_tail
+            || config.track_excess_threshold
+            || config.warmup_steps != 0
+            || config.adaptive_tail.is_some()
+            || config.anomaly_rate_window != 0
+        {
+            return Err(SpotError::UnsupportedOption);
+        }
+
+        Ok(Self {
+            q: config.q,
+            level: config.level,
+            discard_anomalies: config.discard_anomalies,
+            n: 0,
+            min_peaks: config.min_peaks,
+            reject_infinite: config.reject_infinite,
+            upper: Side::new(1.0, &config)?,
+            lower: Side::new(-1.0, &config)?,
+        })
+    }
+
+    /// Fit both tails using initial training data
+    ///
+    /// The two excess thresholds are estimated in a single pass over `data`.
+    pub fn fit(&mut self, data: &[f64]) -> SpotResult<()> {
+        let data = &*finite_training_data(data, self.reject_infinite)?;
+        let mut upper_p2 = P2Estimator::new_reference(self.level);
+        let mut lower_p2 = P2Estimator::new_reference(1.0 - self.level);
+        for &value in data {
+            upper_p2.push(value);
+            lower_p2.push(value);
+        }
+
+        self.n = data.len();
+        for (side, p2) in [(&mut self.upper, upper_p2), (&mut self.lower, lower_p2)] {
+            let et = p2.quantile_or_zero();
+            if et.is_nan() {
+                return Err(SpotError::ExcessThresholdIsNaN);
+            }
+
+            side.excess_threshold = et;
+            side.nt = 0;
+            side.tail.reset();
+            for &value in data {
+                let excess = side.beyond(value, et);
+                if excess > 0.0 {
+                    side.nt += 1;
+                    side.tail.push(excess);
+                }
+            }
+            side.tail.fit();
+
+            side.anomaly_threshold = side.quantile(self.q, self.n);
+            if side.anomaly_threshold.is_nan() {
+                return Err(SpotError::AnomalyThresholdIsNaN);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Process a single data point and return its classification in both tails
+    pub fn step(&mut self, value: f64) -> SpotResult<TwoTailedStatus> {
+        if value.is_nan() {
+            return Err(SpotError::DataIsNaN);
+        }
+        if value.is_infinite() {
+            if self.reject_infinite {
+                return Err(SpotError::DataIsInfinite);
+            }
+            // Beyond any threshold, but kept out of the model
+            return Ok(TwoTailedStatus {
+                upper: status(value > 0.0),
+                lower: status(value < 0.0),
+            });
+        }
+
+        let upper_anomaly = self.upper.beyond(value, self.upper.anomaly_threshold) > 0.0;
+        let lower_anomaly = self.lower.beyond(value, self.lower.anomaly_threshold) > 0.0;
+        if self.discard_anomalies && (upper_anomaly || lower_anomaly) {
+            return Ok(TwoTailedStatus {
+                upper: status(upper_anomaly),
+                lower: status(lower_anomaly),
+            });
+        }
+
+        // Increment number of data (without the anomalies)
+        self.n += 1;
+
+        let mut statuses = [SpotStatus::Normal; 2];
+        for (side, status) in [&mut self.upper, &mut self.lower]
+            .into_iter()
+            .zip(&mut statuses)
+        {
+            let ex = side.beyond(value, side.excess_threshold);
+            if ex >= 0.0 {
+                side.nt += 1;
+                side.tail.push(ex);
+                if side.tail.size() >= self.min_peaks {
+                    side.tail.fit();
+                    side.anomaly_threshold = side.quantile(self.q, self.n);
+                }
+                *status = SpotStatus::Excess;
+            }
+        }
+
+        let [upper, lower] = statuses;
+        Ok(TwoTailedStatus { upper, lower })
+    }
+
+    /// Get the current anomaly threshold of the upper tail
+    pub fn upper_anomaly_threshold(&self) -> f64 {
+        self.upper.anomaly_threshold
+    }
+
+    /// Get the current anomaly threshold of the lower tail
+    pub fn lower_anomaly_threshold(&self) -> f64 {
+        self.lower.anomaly_threshold
+    }
+
+    /// Get the current excess threshold of the upper tail
+    pub fn upper_excess_threshold(&self) -> f64 {
+        self.upper.excess_threshold
+    }
+
+    /// Get the current excess threshold of the lower tail
+    pub fn lower_excess_threshold(&self) -> f64 {
+        self.lower.excess_threshold
+    }
+
+    /// Get the total number of data points seen
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// Get the number of excesses in the upper and lower tails
+    pub fn nt(&self) -> (usize, usize) {
+        (self.upper.nt, self.lower.nt)
+    }
+
+    /// Get the GPD parameters (gamma, sigma) of the upper tail
+    pub fn upper_tail_parameters(&self) -> (f64, f64) {
+        (self.upper.tail.gamma(), self.upper.tail.sigma())
+    }
+
+    /// Get the GPD parameters (gamma, sigma) of the lower tail
+    pub fn lower_tail_parameters(&self) -> (f64, f64) {
+        (self.lower.tail.gamma(), self.lower.tail.sigma())
+    }
+
+    /// Get the current configuration (reconstructed, with `low_tail` unset)
+    pub fn config(&self) -> Option<SpotConfig> {
+        Some(SpotConfig {
+            q: self.q,
+            low_tail: false,
+            discard_anomalies: self.discard_anomalies,
+            level: self.level,
+            max_excess: self.upper.tail.peaks().capacity(),
+            track_excess_threshold: false,
+            pwm_estimator: self.upper.tail.uses_pwm(),
+            min_peaks: self.min_peaks,
+            grimshaw_options: self.upper.tail.grimshaw_options(),
+            warmup_steps: 0,
+            reject_infinite: self.reject_infinite,
+            adaptive_tail: None,
+            fit_strategy: self.upper.tail.fit_strategy(),
+            min_gamma: self.upper.tail.min_gamma(),
+            anomaly_rate_window: 0,
+            stable_small_gamma: self.upper.tail.uses_stable_small_gamma(),
+        })
+    }
+}
+
+/// Status of a discarded value in one tail
+fn status(anomaly: bool) -> SpotStatus {
+    if anomaly {
+        SpotStatus::Anomaly
+    } else {
+        SpotStatus::Normal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AdaptiveTail;
+    use crate::spot::SpotDetector;
+    use crate::testing::SeededExp;
+
+    /// Laplace noise, symmetric around zero: exponential variates with a sign
+    /// drawn from the next one (below ln 2 with probability 1/2)
+    fn laplace_data(n: usize, seed: u64) -> Vec<f64> {
+        let mut rng = SeededExp::new(seed);
+        (0..n)
+            .map(|_| {
+                let x = rng.next();
+                if rng.next() < core::f64::consts::LN_2 {
+                    -x
+                } else {
+                    x
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_two_tailed_fires_on_both_extremes() {
+        let config = SpotConfig {
+            level: 0.98,
+            ..SpotConfig::default()
+        };
+        let mut spot = TwoTailedSpot::new(config).unwrap();
+        spot.fit(&laplace_data(20_000, 3)).unwrap();
+
+        // The thresholds of a symmetric distribution mirror each other
+        let upper = spot.upper_anomaly_threshold();
+        let lower = spot.lower_anomaly_threshold();
+        assert!(upper > 0.0 && lower < 0.0);
+        assert!((upper + lower).abs() < 0.15 * upper, "{upper} vs {lower}");
+
+        let high = spot.step(upper + 1.0).unwrap();
+        assert_eq!(high.upper, SpotStatus::Anomaly);
+        assert_eq!(high.lower, SpotStatus::Normal);
+
+        let low = spot.step(lower - 1.0).unwrap();
+        assert_eq!(low.upper, SpotStatus::Normal);
+        assert_eq!(low.lower, SpotStatus::Anomaly);
+        assert!(low.is_anomaly());
+
+        let middle = spot.step(0.0).unwrap();
+        assert_eq!(
+            middle,
+            TwoTailedStatus {
+                upper: SpotStatus::Normal,
+                lower: SpotStatus::Normal,
+            }
+        );
+        assert_eq!(spot.step(f64::NAN), Err(SpotError::DataIsNaN));
+    }
+
+    #[test]
+    fn test_two_tailed_rejects_unsupported_options() {
+        for config in [
+            SpotConfig {
+                low_tail: true,
+                ..SpotConfig::default()
+            },
+            SpotConfig {
+                track_excess_threshold: true,
+                ..SpotConfig::default()
+            },
+            SpotConfig {
+                warmup_steps: 100,
+                ..SpotConfig::default()
+            },
+            SpotConfig {
+                adaptive_tail: Some(AdaptiveTail {
+                    max_excess: 400,
+                    min_excess_rate: 0.5,
+                }),
+                ..SpotConfig::default()
+            },
+            SpotConfig {
+                anomaly_rate_window: 100,
+                ..SpotConfig::default()
+            },
+        ] {
+            assert!(SpotDetector::new(config.clone()).is_ok());
+            assert_eq!(
+                TwoTailedSpot::new(config).map(|_| ()),
+                Err(SpotError::UnsupportedOption)
+            );
+        }
+    }
+
+    #[test]
+    fn test_two_tailed_matches_two_detectors() {
+        // Without discarding, both detectors count every value like the shared counter
+        let config = SpotConfig {
+            level: 0.98,
+            discard_anomalies: false,
+            ..SpotConfig::default()
+        };
+        let train = laplace_data(10_000, 5);
+        let stream = laplace_data(5_000, 6);
+
+        let mut both = TwoTailedSpot::new(config.clone()).unwrap();
+        let mut upper = SpotDetector::new(config.clone()).unwrap();
+        let mut lower = SpotDetector::new(SpotConfig {
+            low_tail: true,
+            ..config
+        })
+        .unwrap();
+        both.fit(&train).unwrap();
+        upper.fit(&train).unwrap();
+        lower.fit(&train).unwrap();
+
+        for &x in &stream {
+            let status = both.step(x).unwrap();
+            assert_eq!(status.upper, upper.step(x).unwrap());
+            assert_eq!(status.lower, lower.step(x).unwrap());
+        }
+
+        assert_eq!(both.upper_anomaly_threshold(), upper.anomaly_threshold());
+        assert_eq!(both.lower_anomaly_threshold(), lower.anomaly_threshold());
+        assert_eq!(both.upper_tail_parameters(), upper.tail_parameters());
+        assert_eq!(both.lower_tail_parameters(), lower.tail_parameters());
+        assert_eq!(both.nt(), (upper.nt(), lower.nt()));
+        assert_eq!(both.n(), upper.n());
+    }
+}
@@ -3,8 +3,18 @@
 //! This module implements a circular buffer that matches the C implementation exactly.
 //! The Ubend structure is a kind of circular vector that starts empty, fills up to capacity,
 //! and then overwrites older data with newer data.
+//!
+//! [`ArrayUbend`] has the same semantics with a capacity fixed at compile time
+//! and no heap allocation. Both implement the [`RingBuffer`] trait, over which
+//! [`Peaks`](crate::Peaks), [`Tail`](crate::Tail) and
+//! [`SpotDetector`](crate::SpotDetector) are generic.
+
+use alloc::{vec, vec::Vec};
+
+use core::fmt;
 
 use crate::error::{SpotError, SpotResult};
+use crate::float::Float;
 
 /// Circular buffer implementation that matches the C Ubend structure
 ///
@@ -14,23 +24,31 @@ use crate::error::{SpotError, SpotResult};
 /// This is useful for saving the state of a SPOT detector and restoring it later.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Ubend {
+#[cfg_attr(feature = "serde", serde(bound = ""))]
+pub struct Ubend<F: Float = f64> {
     /// Current position inside the container
     cursor: usize,
     /// Maximum storage capacity
     capacity: usize,
     /// Last erased value (i.e., replaced by a new one)
-    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_f64"))]
-    last_erased_data: f64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::ser::nan_safe_float"))]
+    last_erased_data: F,
     /// Container fill status (true = filled, false = not filled)
     filled: bool,
     /// Data container
-    data: Vec<f64>,
+    data: Vec<F>,
 }
 
 impl Ubend {
-    /// Initialize a new Ubend with the given capacity
+    /// Initialize a new `f64` Ubend with the given capacity
     pub fn new(capacity: usize) -> SpotResult<Self> {
+        Self::new_generic(capacity)
+    }
+}
+
+impl<F: Float> Ubend<F> {
+    /// Initialize a new Ubend of any [`Float`] type with the given capacity
+    pub fn new_generic(capacity: usize) -> SpotResult<Self> {
         if capacity == 0 {
             return Err(SpotError::MemoryAllocationFailed);
         }
@@ -39,8 +57,46 @@ impl Ubend {
             cursor: 0,
             filled: false,
             capacity,
-            last_erased_data: f64::NAN,
-            data: vec![0.0; capacity],
+            last_erased_data: F::nan(),
+            data: vec![F::zero(); capacity],
+        })
+    }
+
+    /// Initialize a new Ubend with the given capacity and push `data` into it
+    ///
+    /// Wrapping semantics are preserved: when `data` holds more than `capacity`
+    /// values, only the last `capacity` ones are kept (in insertion order) and
+    /// [`last_erased_data`](Self::last_erased_data) is the last value overwritten.
+    pub fn from_slice(capacity: usize, data: &[F]) -> SpotResult<Self> {
+        let mut ubend = Self::new_generic(capacity)?;
+        for &x in data {
+            ubend.push(x);
+        }
+        Ok(ubend)
+    }
+
+    /// Rebuild a container from its raw state (the fields of the C structure)
+    ///
+    /// `data` is the whole buffer, so its length is the capacity.
+    pub(crate) fn from_raw_parts(
+        data: Vec<F>,
+        cursor: usize,
+        filled: bool,
+        last_erased_data: F,
+    ) -> SpotResult<Self> {
+        if data.is_empty() {
+            return Err(SpotError::MemoryAllocationFailed);
+        }
+        if cursor >= data.len() {
+            return Err(SpotError::CountersOutOfBounds);
+        }
+
+        Ok(Self {
+            cursor,
+            capacity: data.len(),
+            last_erased_data,
+            filled,
+            data,
         })
     }
 
@@ -56,18 +112,38 @@ impl Ubend {
 
     /// Reset the container to its empty state, keeping the allocated buffer.
     ///
-    /// After `reset`, [`size`](Self::size) returns 0 and the next [`push`](Self::push)
+    /// After `clear`, [`size`](Self::size) returns 0 and the next [`push`](Self::push)
     /// behaves as on a freshly constructed container. The underlying `Vec`
     /// allocation is preserved (no realloc).
-    pub(crate) fn reset(&mut self) {
+    pub fn clear(&mut self) {
         self.cursor = 0;
         self.filled = false;
-        self.last_erased_data = f64::NAN;
+        self.last_erased_data = F::nan();
+    }
+
+    /// Increase the capacity of the container, keeping its values in insertion order
+    ///
+    /// The values are moved to the front of a larger buffer, so the container is
+    /// no longer filled and the next pushes do not erase anything until it is.
+    /// [`last_erased_data`](Self::last_erased_data) is kept. A `new_capacity`
+    /// not larger than the current one leaves the container unchanged.
+    pub fn grow(&mut self, new_capacity: usize) {
+        if new_capacity <= self.capacity {
+            return;
+        }
+
+        let mut data = self.data();
+        let size = data.len();
+        data.resize(new_capacity, F::zero());
+        self.data = data;
+        self.capacity = new_capacity;
+        self.cursor = size;
+        self.filled = false;
     }
 
     /// Push a new value into the container
     /// Returns the value that was erased (if any), otherwise NaN
-    pub fn push(&mut self, x: f64) -> f64 {
+    pub fn push(&mut self, x: F) -> F {
         // If the container has already been filled, we must keep in memory
         // the data we will erase
         if self.filled {
@@ -89,7 +165,7 @@ impl Ubend {
     }
 
     /// Get iterator over the data in insertion order
-    pub fn iter(&self) -> UbendIterator<'_> {
+    pub fn iter(&self) -> UbendIterator<'_, F> {
         UbendIterator {
             ubend: self,
             index: 0,
@@ -97,24 +173,40 @@ impl Ubend {
     }
 
     /// Get the data at a specific index in insertion order
-    pub fn get(&self, index: usize) -> Option<f64> {
+    pub fn get(&self, index: usize) -> Option<F> {
         let size = self.size();
         if index >= size {
             return None;
         }
 
+        Some(self.data[self.physical_index(index)])
+    }
+
+    /// Get an iterator over `(insertion_index, physical_index, value)` in
+    /// insertion order
+    ///
+    /// The physical index is the slot of the value in [`raw_data`](Self::raw_data),
+    /// which shows how the buffer wrapped around.
+    pub fn iter_indexed(&self) -> impl Iterator<Item = (usize, usize, F)> + '_ {
+        (0..self.size()).map(move |index| {
+            let physical = self.physical_index(index);
+            (index, physical, self.data[physical])
+        })
+    }
+
+    /// Slot in the buffer of the value at `index` in insertion order
+    fn physical_index(&self, index: usize) -> usize {
         if !self.filled {
             // Simple case: data is contiguous from 0 to cursor-1
-            Some(self.data[index])
+            index
         } else {
             // Complex case: data wraps around
-            let real_index = (self.cursor + index) % self.capacity;
-            Some(self.data[real_index])
+            (self.cursor + index) % self.capacity
         }
     }
 
     /// Access to raw data (for compatibility with C implementation)
-    pub fn raw_data(&self) -> &[f64] {
+    pub fn raw_data(&self) -> &[F] {
         &self.data
     }
 
@@ -134,24 +226,24 @@ impl Ubend {
     }
 
     /// Get last erased data
-    pub fn last_erased_data(&self) -> f64 {
+    pub fn last_erased_data(&self) -> F {
         self.last_erased_data
     }
 
     /// Get all data in insertion order as a vector
-    pub fn data(&self) -> Vec<f64> {
+    pub fn data(&self) -> Vec<F> {
         self.iter().collect()
     }
 }
 
 /// Iterator over Ubend data in insertion order
-pub struct UbendIterator<'a> {
-    ubend: &'a Ubend,
+pub struct UbendIterator<'a, F: Float = f64> {
+    ubend: &'a Ubend<F>,
     index: usize,
 }
 
-impl<'a> Iterator for UbendIterator<'a> {
-    type Item = f64;
+impl<'a, F: Float> Iterator for UbendIterator<'a, F> {
+    type Item = F;
 
     fn next(&mut self) -> Option<Self::Item> {
         let result = self.ubend.get(self.index);
@@ -160,19 +252,335 @@ impl<'a> Iterator for UbendIterator<'a> {
     }
 }
 
-impl<'a> ExactSizeIterator for UbendIterator<'a> {
+impl<'a, F: Float> ExactSizeIterator for UbendIterator<'a, F> {
     fn len(&self) -> usize {
         self.ubend.size()
     }
 }
 
+impl<'a, F: Float> IntoIterator for &'a Ubend<F> {
+    type Item = F;
+    type IntoIter = UbendIterator<'a, F>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Circular buffer operations shared by [`Ubend`] and [`ArrayUbend`]
+///
+/// The excess buffer of [`Peaks`](crate::Peaks), [`Tail`](crate::Tail) and
+/// [`SpotDetector`](crate::SpotDetector) is any `RingBuffer`, [`Ubend`] by
+/// default.
+pub trait RingBuffer<F: Float>: Clone + fmt::Debug {
+    /// Iterator over the stored values in insertion order
+    type Iter<'a>: ExactSizeIterator<Item = F>
+    where
+        Self: 'a;
+
+    /// Create an empty buffer holding up to `capacity` values
+    ///
+    /// Fails with [`SpotError::MemoryAllocationFailed`] if the buffer cannot
+    /// have this capacity: 0, or any other capacity than `N` for
+    /// [`ArrayUbend`].
+    fn with_capacity(capacity: usize) -> SpotResult<Self>
+    where
+        Self: Sized;
+
+    /// Push a new value, returning the last erased value (NaN until the
+    /// buffer is filled)
+    fn push(&mut self, x: F) -> F;
+
+    /// Get the value at `index` in insertion order
+    fn get(&self, index: usize) -> Option<F>;
+
+    /// Get the number of stored values
+    fn size(&self) -> usize;
+
+    /// Get the maximum number of stored values
+    fn capacity(&self) -> usize;
+
+    /// Check if the buffer is filled
+    fn is_filled(&self) -> bool;
+
+    /// Get the last erased value
+    fn last_erased_data(&self) -> F;
+
+    /// Reset the buffer to its empty state
+    fn clear(&mut self);
+
+    /// Get an iterator over the stored values in insertion order
+    fn iter(&self) -> Self::Iter<'_>;
+
+    /// Get the storage slots, whose first [`size`](Self::size) entries hold
+    /// the stored values (in insertion order until the buffer wraps around)
+    fn raw_data(&self) -> &[F];
+
+    /// Increase the capacity, keeping the stored values in insertion order
+    ///
+    /// The default implementation keeps the capacity, for buffers of fixed
+    /// size like [`ArrayUbend`].
+    fn grow(&mut self, new_capacity: usize) {
+        let _ = new_capacity;
+    }
+}
+
+impl<F: Float> RingBuffer<F> for Ubend<F> {
+    type Iter<'a> = UbendIterator<'a, F>;
+
+    fn with_capacity(capacity: usize) -> SpotResult<Self> {
+        Self::new_generic(capacity)
+    }
+
+    fn push(&mut self, x: F) -> F {
+        Ubend::push(self, x)
+    }
+
+    fn get(&self, index: usize) -> Option<F> {
+        Ubend::get(self, index)
+    }
+
+    fn size(&self) -> usize {
+        Ubend::size(self)
+    }
+
+    fn capacity(&self) -> usize {
+        Ubend::capacity(self)
+    }
+
+    fn is_filled(&self) -> bool {
+        Ubend::is_filled(self)
+    }
+
+    fn last_erased_data(&self) -> F {
+        Ubend::last_erased_data(self)
+    }
+
+    fn clear(&mut self) {
+        Ubend::clear(self)
+    }
+
+    fn iter(&self) -> UbendIterator<'_, F> {
+        Ubend::iter(self)
+    }
+
+    fn raw_data(&self) -> &[F] {
+        Ubend::raw_data(self)
+    }
+
+    fn grow(&mut self, new_capacity: usize) {
+        Ubend::grow(self, new_capacity)
+    }
+}
+
+/// Circular buffer with the semantics of [`Ubend`], backed by an array of `N`
+/// values instead of a `Vec`
+///
+/// Meant for `no_std` targets without an allocator for the buffers, when the
+/// capacity is known at compile time. `N` must be positive.
+///
+/// ```
+/// use libspot_rs::ArrayUbend;
+///
+/// let mut buffer = ArrayUbend::<3>::new();
+/// for x in [1.0, 2.0, 3.0, 4.0] {
+///     buffer.push(x);
+/// }
+/// assert_eq!(buffer.last_erased_data(), 1.0);
+/// assert!(buffer.iter().eq([2.0, 3.0, 4.0]));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ArrayUbend<const N: usize, F: Float = f64> {
+    /// Current position inside the array
+    cursor: usize,
+    /// Last erased value (i.e., replaced by a new one)
+    last_erased_data: F,
+    /// Container fill status (true = filled, false = not filled)
+    filled: bool,
+    /// Data container
+    data: [F; N],
+}
+
+impl<const N: usize, F: Float> ArrayUbend<N, F> {
+    /// Create an empty buffer
+    pub fn new() -> Self {
+        const { assert!(N > 0, "ArrayUbend capacity must be positive") };
+        Self {
+            cursor: 0,
+            last_erased_data: F::nan(),
+            filled: false,
+            data: [F::zero(); N],
+        }
+    }
+
+    /// Push a new value into the buffer
+    /// Returns the value that was erased (if any), otherwise NaN
+    pub fn push(&mut self, x: F) -> F {
+        if self.filled {
+            self.last_erased_data = self.data[self.cursor];
+        }
+        self.data[self.cursor] = x;
+
+        if self.cursor == N - 1 {
+            self.cursor = 0;
+            self.filled = true;
+        } else {
+            self.cursor += 1;
+        }
+
+        self.last_erased_data
+    }
+
+    /// Get the data at a specific index in insertion order
+    pub fn get(&self, index: usize) -> Option<F> {
+        if index >= self.size() {
+            return None;
+        }
+        Some(self.data[self.physical_index(index)])
+    }
+
+    /// Get an iterator over the data in insertion order
+    pub fn iter(&self) -> ArrayUbendIterator<'_, N, F> {
+        ArrayUbendIterator {
+            buffer: self,
+            index: 0,
+        }
+    }
+
+    /// Slot in the array of the value at `index` in insertion order
+    fn physical_index(&self, index: usize) -> usize {
+        if self.filled {
+            (self.cursor + index) % N
+        } else {
+            index
+        }
+    }
+
+    /// Get the current size of the buffer
+    pub fn size(&self) -> usize {
+        if self.filled {
+            N
+        } else {
+            self.cursor
+        }
+    }
+
+    /// Get the capacity, `N`
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Check if the buffer is filled
+    pub fn is_filled(&self) -> bool {
+        self.filled
+    }
+
+    /// Get current cursor position
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Get last erased data
+    pub fn last_erased_data(&self) -> F {
+        self.last_erased_data
+    }
+
+    /// Access to the raw array, in slot order
+    pub fn raw_data(&self) -> &[F; N] {
+        &self.data
+    }
+
+    /// Reset the buffer to its empty state
+    pub fn clear(&mut self) {
+        self.cursor = 0;
+        self.filled = false;
+        self.last_erased_data = F::nan();
+    }
+}
+
+impl<const N: usize, F: Float> Default for ArrayUbend<N, F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Iterator over [`ArrayUbend`] data in insertion order
+pub struct ArrayUbendIterator<'a, const N: usize, F: Float = f64> {
+    buffer: &'a ArrayUbend<N, F>,
+    index: usize,
+}
+
+impl<const N: usize, F: Float> Iterator for ArrayUbendIterator<'_, N, F> {
+    type Item = F;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.buffer.get(self.index);
+        self.index += 1;
+        result
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.buffer.size().saturating_sub(self.index);
+        (remaining, Some(remaining))
+    }
+}
+
+impl<const N: usize, F: Float> ExactSizeIterator for ArrayUbendIterator<'_, N, F> {}
+
+impl<const N: usize, F: Float> RingBuffer<F> for ArrayUbend<N, F> {
+    type Iter<'a> = ArrayUbendIterator<'a, N, F>;
+
+    fn with_capacity(capacity: usize) -> SpotResult<Self> {
+        if capacity != N {
+            return Err(SpotError::MemoryAllocationFailed);
+        }
+        Ok(Self::new())
+    }
+
+    fn push(&mut self, x: F) -> F {
+        ArrayUbend::push(self, x)
+    }
+
+    fn get(&self, index: usize) -> Option<F> {
+        ArrayUbend::get(self, index)
+    }
+
+    fn size(&self) -> usize {
+        ArrayUbend::size(self)
+    }
+
+    fn capacity(&self) -> usize {
+        N
+    }
+
+    fn is_filled(&self) -> bool {
+        self.filled
+    }
+
+    fn last_erased_data(&self) -> F {
+        self.last_erased_data
+    }
+
+    fn clear(&mut self) {
+        ArrayUbend::clear(self)
+    }
+
+    fn iter(&self) -> ArrayUbendIterator<'_, N, F> {
+        ArrayUbend::iter(self)
+    }
+
+    fn raw_data(&self) -> &[F] {
+        &self.data
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use approx::assert_relative_eq;
 
     #[test]
-    fn test_ubend_reset_clears_state_and_preserves_capacity() {
+    fn test_ubend_clear_clears_state_and_preserves_capacity() {
         let mut ub = Ubend::new(3).unwrap();
         // Fill past capacity so `filled = true` and `last_erased_data` is set.
         let _ = ub.push(1.0);
@@ -182,7 +590,7 @@ mod tests {
         assert_relative_eq!(erased, 1.0);
         assert_eq!(ub.size(), 3);
 
-        ub.reset();
+        ub.clear();
 
         // Empty again, but capacity (and the underlying Vec) is preserved.
         assert_eq!(ub.size(), 0);
@@ -192,22 +600,41 @@ mod tests {
         assert!(ub.last_erased_data.is_nan());
         assert_eq!(ub.data.len(), 3); // Vec not reallocated
 
-        // After reset, the next push behaves like on a fresh Ubend:
+        // After clear, the next push behaves like on a fresh Ubend:
         // it returns NaN (nothing erased) instead of the old `last_erased_data`.
-        let erased_after_reset = ub.push(10.0);
-        assert!(erased_after_reset.is_nan());
+        let erased_after_clear = ub.push(10.0);
+        assert!(erased_after_clear.is_nan());
         assert_eq!(ub.size(), 1);
     }
 
     #[test]
-    fn test_ubend_reset_is_idempotent() {
+    fn test_ubend_clear_is_idempotent() {
         let mut ub = Ubend::new(2).unwrap();
-        ub.reset();
-        ub.reset();
+        ub.clear();
+        ub.clear();
         assert_eq!(ub.size(), 0);
         assert!(ub.last_erased_data.is_nan());
     }
 
+    #[test]
+    fn test_ubend_from_slice_keeps_last_values() {
+        let ub = Ubend::from_slice(3, &[1.0_f64, 2.0, 3.0, 4.0, 5.0]).unwrap();
+        assert_eq!(ub.data(), vec![3.0, 4.0, 5.0]);
+        assert!(ub.is_filled());
+        assert_eq!(ub.cursor(), 2);
+        assert_relative_eq!(ub.last_erased_data(), 2.0);
+
+        let partial = Ubend::from_slice(4, &[1.0_f64, 2.0]).unwrap();
+        assert_eq!(partial.data(), vec![1.0, 2.0]);
+        assert!(!partial.is_filled());
+        assert!(partial.last_erased_data().is_nan());
+
+        assert_eq!(
+            Ubend::from_slice(0, &[1.0_f64]).unwrap_err(),
+            SpotError::MemoryAllocationFailed
+        );
+    }
+
     #[test]
     fn test_ubend_creation() {
         let ubend = Ubend::new(5).unwrap();
@@ -315,6 +742,105 @@ mod tests {
         assert_eq!(values, vec![2.0, 3.0, 4.0]);
     }
 
+    #[test]
+    fn test_ubend_into_iterator_matches_iter() {
+        let mut ubend = Ubend::new(3).unwrap();
+        for i in 0..5 {
+            ubend.push(i as f64);
+        }
+
+        let mut values = Vec::new();
+        for x in &ubend {
+            values.push(x);
+        }
+        assert_eq!(values, vec![2.0, 3.0, 4.0]);
+        assert_eq!(values, ubend.iter().collect::<Vec<_>>());
+        assert_eq!((&ubend).into_iter().collect::<Vec<_>>(), ubend.data());
+    }
+
+    #[test]
+    fn test_array_ubend_matches_ubend() {
+        let mut array = ArrayUbend::<3>::new();
+        let mut ubend = Ubend::new(3).unwrap();
+        assert!(array.last_erased_data().is_nan());
+        assert_eq!(array.get(0), None);
+
+        for i in 0..8 {
+            let x = i as f64 * 1.5;
+            let erased = array.push(x);
+            let expected = ubend.push(x);
+            assert!(erased == expected || (erased.is_nan() && expected.is_nan()));
+            assert_eq!(array.size(), ubend.size());
+            assert_eq!(array.cursor(), ubend.cursor());
+            assert_eq!(array.is_filled(), ubend.is_filled());
+            assert_eq!(&array.raw_data()[..], ubend.raw_data());
+            assert!(array.iter().eq(ubend.iter()));
+            assert_eq!(array.iter().len(), ubend.size());
+        }
+        assert_eq!(array.last_erased_data(), 6.0);
+        assert_eq!(array.get(0), Some(7.5));
+        assert_eq!(array.get(3), None);
+
+        // Through the trait
+        fn drain<B: RingBuffer<f64>>(buffer: &mut B) -> (usize, usize) {
+            let before = (buffer.size(), buffer.capacity());
+            buffer.clear();
+            assert_eq!(buffer.size(), 0);
+            assert!(buffer.last_erased_data().is_nan());
+            before
+        }
+        assert_eq!(drain(&mut array), drain(&mut ubend));
+        assert_eq!(array.push(1.0).is_nan(), ubend.push(1.0).is_nan());
+        assert_eq!(RingBuffer::get(&array, 0), RingBuffer::get(&ubend, 0));
+    }
+
+    #[test]
+    fn test_ubend_iter_indexed_after_wraparound() {
+        let mut ub = Ubend::new(4).unwrap();
+        for i in 0..6 {
+            ub.push(i as f64);
+        }
+
+        let indexed: Vec<_> = ub.iter_indexed().collect();
+        assert_eq!(indexed.len(), 4);
+        for (i, &(insertion, physical, value)) in indexed.iter().enumerate() {
+            assert_eq!(insertion, i);
+            assert_eq!(physical, (ub.cursor() + i) % ub.capacity());
+            assert_eq!(value, ub.raw_data()[physical]);
+            assert_eq!(Some(value), ub.get(i));
+        }
+        assert_eq!(indexed[0], (0, 2, 2.0));
+        assert_eq!(indexed[3], (3, 1, 5.0));
+    }
+
+    #[test]
+    fn test_ubend_grow_preserves_insertion_order() {
+        let mut ub = Ubend::new(4).unwrap();
+        for i in 0..6 {
+            ub.push(i as f64);
+        }
+        assert_eq!(ub.data(), vec![2.0, 3.0, 4.0, 5.0]);
+
+        ub.grow(6);
+        assert_eq!(ub.capacity(), 6);
+        assert_eq!(ub.size(), 4);
+        assert!(!ub.is_filled());
+        assert_eq!(ub.data(), vec![2.0, 3.0, 4.0, 5.0]);
+        assert_relative_eq!(ub.last_erased_data(), 1.0);
+
+        // The new slots fill up before anything is erased again
+        ub.push(6.0);
+        ub.push(7.0);
+        assert_eq!(ub.data(), vec![2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+        assert_relative_eq!(ub.push(8.0), 2.0);
+        assert_eq!(ub.data(), vec![3.0, 4.0, 5.0, 6.0, 7.0, 8.0]);
+
+        // Shrinking is a no-op
+        ub.grow(2);
+        assert_eq!(ub.capacity(), 6);
+        assert_eq!(ub.data(), vec![3.0, 4.0, 5.0, 6.0, 7.0, 8.0]);
+    }
+
     #[test]
     fn test_ubend_exact_size_iterator() {
         let mut ubend = Ubend::new(3).unwrap();
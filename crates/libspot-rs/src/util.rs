@@ -0,0 +1,66 @@
+//! Exact statistics on plain data
+//!
+//! The detector only keeps streaming approximations of the training data (the
+//! P2 estimate of the excess threshold). These helpers compute the exact
+//! values, e.g. to check those approximations on data held in memory.
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use crate::float::Float;
+
+/// Compute the exact empirical `p`-quantile of `data`
+///
+/// Sorts a copy of the data and interpolates linearly between the two closest
+/// order statistics, like [`Peaks::quantile`](crate::Peaks::quantile): `p = 0`
+/// gives the minimum and `p = 1` the maximum. NaN values are ignored. Returns
+/// NaN if no value is left or `p` is outside `[0, 1]`.
+///
+/// ```
+/// use libspot_rs::empirical_quantile;
+///
+/// assert_eq!(empirical_quantile(&[3.0, 1.0, 2.0, f64::NAN], 0.5), 2.0);
+/// assert_eq!(empirical_quantile(&[1.0, 2.0], 0.25), 1.25);
+/// ```
+pub fn empirical_quantile<F: Float>(data: &[F], p: F) -> F {
+    sorted_quantile(data.iter().copied().filter(|x| !x.is_nan()).collect(), p)
+}
+
+/// Empirical `p`-quantile of `values` (without NaN), sorting them in place
+pub(crate) fn sorted_quantile<F: Float>(mut values: Vec<F>, p: F) -> F {
+    let size = values.len();
+    if size == 0 || !(p >= F::zero() && p <= F::one()) {
+        return F::nan();
+    }
+
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    let h = p * F::from_usize(size - 1);
+    let lo = h.floor();
+    let i = lo.to_usize().unwrap_or(0);
+    if i + 1 >= size {
+        return values[size - 1];
+    }
+    values[i] + (h - lo) * (values[i + 1] - values[i])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_empirical_quantile() {
+        let data: Vec<f64> = (0..=100).rev().map(|i| i as f64).collect();
+        assert_eq!(empirical_quantile(&data, 0.0), 0.0);
+        assert_eq!(empirical_quantile(&data, 1.0), 100.0);
+        assert_relative_eq!(empirical_quantile(&data, 0.995), 99.5);
+        assert_relative_eq!(empirical_quantile(&data, 0.42), 42.0);
+
+        assert!(empirical_quantile::<f64>(&[], 0.5).is_nan());
+        assert!(empirical_quantile(&[f64::NAN], 0.5).is_nan());
+        assert!(empirical_quantile(&data, 1.5).is_nan());
+        assert!(empirical_quantile(&data, f64::NAN).is_nan());
+        assert_eq!(empirical_quantile(&[1.0f32, 3.0], 0.5), 2.0);
+    }
+}
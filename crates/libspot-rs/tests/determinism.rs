@@ -0,0 +1,99 @@
+//! Pins the numerical output of a fixed seeded run to committed bit patterns.
+//!
+//! The estimators rely on continued fractions and Brent iterations that were
+//! carefully matched to the C implementation, so any reassociation or fused
+//! multiply-add introduced by the compiler (or by a contributor) would show up
+//! as a silent drift. CI runs this test under both the `dev` and `release`
+//! profiles; the expected values must be identical for every `opt-level`.
+
+use libspot_rs::{SpotConfig, SpotDetector};
+
+/// Random number generator that matches C's rand()/srand() for reproducible results
+struct CRand {
+    seed: u32,
+}
+
+impl CRand {
+    fn new(seed: u32) -> Self {
+        Self { seed }
+    }
+
+    fn next(&mut self) -> u32 {
+        self.seed = self.seed.wrapping_mul(1103515245).wrapping_add(12345);
+        (self.seed / 65536) % 32768
+    }
+
+    fn rexp(&mut self) -> f64 {
+        let u = self.next() as f64 / 32767.0;
+        if u <= 0.0 || u >= 1.0 {
+            return 1.0; // Safe fallback for edge cases
+        }
+        -u.ln()
+    }
+}
+
+/// Fit on 20k seeded samples, then step through 200k more
+fn seeded_run() -> SpotDetector {
+    let mut detector = SpotDetector::new(SpotConfig::default()).unwrap();
+    let mut rng = CRand::new(1);
+
+    let training: Vec<f64> = (0..20_000).map(|_| rng.rexp()).collect();
+    detector.fit(&training).unwrap();
+
+    for _ in 0..200_000 {
+        detector.step(rng.rexp()).unwrap();
+    }
+    detector
+}
+
+#[test]
+#[cfg_attr(
+    feature = "std-math",
+    ignore = "the standard math functions do not reproduce the committed bits"
+)]
+fn test_seeded_run_matches_committed_bits() {
+    let detector = seeded_run();
+    let (gamma, sigma) = detector.tail_parameters();
+
+    assert_eq!(detector.n(), 219_965);
+    assert_eq!(detector.nt(), 325);
+    assert_eq!(
+        detector.excess_threshold().to_bits(),
+        0x40196bfd2d9df9a9,
+        "excess threshold drifted: {}",
+        detector.excess_threshold()
+    );
+    assert_eq!(
+        detector.anomaly_threshold().to_bits(),
+        0x401f5f7e72f70e88,
+        "anomaly threshold drifted: {}",
+        detector.anomaly_threshold()
+    );
+    assert_eq!(
+        gamma.to_bits(),
+        0xbfdec4a8563c0964,
+        "gamma drifted: {gamma}"
+    );
+    assert_eq!(
+        sigma.to_bits(),
+        0x3fef837d08808606,
+        "sigma drifted: {sigma}"
+    );
+}
+
+#[test]
+fn test_seeded_run_is_repeatable() {
+    let a = seeded_run();
+    let b = seeded_run();
+
+    assert_eq!(
+        a.anomaly_threshold().to_bits(),
+        b.anomaly_threshold().to_bits()
+    );
+    assert_eq!(
+        a.excess_threshold().to_bits(),
+        b.excess_threshold().to_bits()
+    );
+    assert_eq!(a.n(), b.n());
+    assert_eq!(a.nt(), b.nt());
+}
@@ -0,0 +1,117 @@
+//! Tests for the versioned model envelope with JSON and CBOR
+
+#![cfg(feature = "serde")]
+
+use libspot_rs::{ModelEnvelope, SpotConfig, SpotDetector, SpotError, MODEL_ENVELOPE_VERSION};
+
+fn fitted() -> SpotDetector {
+    let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+    let training_data: Vec<f64> = (0..1000).map(|i| (i as f64) / 100.0).collect();
+    spot.fit(&training_data).unwrap();
+    spot
+}
+
+fn to_cbor<T: serde::Serialize>(value: &T) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    ciborium::into_writer(value, &mut bytes).unwrap();
+    bytes
+}
+
+fn assert_same_model(loaded: &mut SpotDetector, original: &mut SpotDetector) {
+    assert_eq!(loaded.n(), original.n());
+    assert_eq!(loaded.anomaly_threshold(), original.anomaly_threshold());
+    assert_eq!(loaded.excess_threshold(), original.excess_threshold());
+    assert_eq!(loaded.tail_parameters(), original.tail_parameters());
+    for &val in &[5.0, 10.0, 50.0, 100.0] {
+        assert_eq!(loaded.step(val), original.step(val));
+    }
+}
+
+#[test]
+fn test_envelope_json_roundtrip() {
+    let mut original = fitted();
+    let json = serde_json::to_string(&original.save_envelope()).unwrap();
+    assert!(json.contains(&format!("\"version\":{MODEL_ENVELOPE_VERSION}")));
+    assert!(json.contains(&format!("\"crate_version\":\"{}\"", libspot_rs::version())));
+
+    let envelope: ModelEnvelope = serde_json::from_str(&json).unwrap();
+    let mut loaded = SpotDetector::load_envelope(envelope).unwrap();
+    assert_same_model(&mut loaded, &mut original);
+}
+
+#[test]
+fn test_envelope_cbor_roundtrip() {
+    let mut original = fitted();
+    let bytes = to_cbor(&original.save_envelope());
+
+    let envelope: ModelEnvelope = ciborium::from_reader(bytes.as_slice()).unwrap();
+    assert_eq!(envelope.version, MODEL_ENVELOPE_VERSION);
+    let mut loaded = SpotDetector::load_envelope(envelope).unwrap();
+    assert_same_model(&mut loaded, &mut original);
+
+    // NaN thresholds of an unfitted detector survive the binary format
+    let unfitted = SpotDetector::new(SpotConfig::default()).unwrap();
+    let envelope: ModelEnvelope =
+        ciborium::from_reader(to_cbor(&unfitted.save_envelope()).as_slice()).unwrap();
+    assert!(SpotDetector::load_envelope(envelope)
+        .unwrap()
+        .anomaly_threshold()
+        .is_nan());
+}
+
+#[test]
+fn test_envelope_version_mismatch() {
+    let original = fitted();
+    let mut envelope = original.save_envelope();
+    envelope.version += 1;
+
+    let from_json: ModelEnvelope =
+        serde_json::from_str(&serde_json::to_string(&envelope).unwrap()).unwrap();
+    let from_cbor: ModelEnvelope = ciborium::from_reader(to_cbor(&envelope).as_slice()).unwrap();
+
+    for decoded in [from_json, from_cbor] {
+        assert_eq!(
+            SpotDetector::load_envelope(decoded).unwrap_err(),
+            SpotError::VersionMismatch {
+                found: MODEL_ENVELOPE_VERSION + 1,
+                expected: MODEL_ENVELOPE_VERSION,
+            }
+        );
+    }
+}
+
+#[test]
+fn test_envelope_older_version_loads() {
+    let mut original = fitted();
+    let mut envelope = original.save_envelope();
+    envelope.version = 1;
+
+    let decoded: ModelEnvelope =
+        serde_json::from_str(&serde_json::to_string(&envelope).unwrap()).unwrap();
+    let mut loaded = SpotDetector::load_envelope(decoded).unwrap();
+    assert_same_model(&mut loaded, &mut original);
+}
+
+#[test]
+fn test_envelope_load_for_checks_config() {
+    let original = fitted();
+    let json = serde_json::to_string(&original.save_envelope()).unwrap();
+    let decode = || -> ModelEnvelope { serde_json::from_str(&json).unwrap() };
+
+    let config = original.config().unwrap();
+    let relaxed = SpotConfig {
+        discard_anomalies: !config.discard_anomalies,
+        ..config.clone()
+    };
+    let loaded = SpotDetector::load_envelope_for(decode(), &relaxed).unwrap();
+    assert_eq!(loaded.anomaly_threshold(), original.anomaly_threshold());
+
+    let other = SpotConfig {
+        max_excess: config.max_excess + 1,
+        ..config
+    };
+    assert_eq!(
+        SpotDetector::load_envelope_for(decode(), &other).unwrap_err(),
+        SpotError::ConfigMismatch
+    );
+}
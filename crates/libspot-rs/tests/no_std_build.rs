@@ -0,0 +1,41 @@
+//! Compile test for `no_std + alloc` targets
+//!
+//! Builds the library without the `std` feature for a bare-metal target. The
+//! target must be installed first (`rustup target add thumbv7em-none-eabihf`),
+//! so the test is ignored by default and run explicitly by a required step
+//! of the CI workflow.
+
+use std::process::Command;
+
+const TARGET: &str = "thumbv7em-none-eabihf";
+
+fn build_for_target(features: &str) -> bool {
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    Command::new(cargo)
+        .args([
+            "build",
+            "--lib",
+            "--no-default-features",
+            "--target",
+            TARGET,
+        ])
+        .args(["--features", features])
+        .arg("--target-dir")
+        .arg(concat!(env!("CARGO_TARGET_TMPDIR"), "/no_std"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .status()
+        .expect("failed to run cargo")
+        .success()
+}
+
+#[test]
+#[ignore] // Requires the thumbv7em-none-eabihf target
+fn test_no_std_build() {
+    assert!(build_for_target(""));
+}
+
+#[test]
+#[ignore] // Requires the thumbv7em-none-eabihf target
+fn test_no_std_build_with_serde() {
+    assert!(build_for_target("serde"));
+}
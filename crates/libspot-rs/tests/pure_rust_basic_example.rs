@@ -34,6 +34,7 @@ fn test_pure_rust_basic_example_behavior() {
         discard_anomalies: true,
         level: 0.998,
         max_excess: 200,
+        ..SpotConfig::default()
     };
 
     // Create and initialize SPOT detector
@@ -112,6 +113,7 @@ fn test_pure_rust_basic_example_larger() {
         discard_anomalies: true,
         level: 0.998,
         max_excess: 200,
+        ..SpotConfig::default()
     };
 
     let mut detector = SpotDetector::new(config).unwrap();
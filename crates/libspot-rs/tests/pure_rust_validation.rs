@@ -37,6 +37,7 @@ fn test_pure_rust_exact_c_behavior_1m_samples() {
         discard_anomalies: true, // flag anomalies
         level: 0.998,            // tail quantile
         max_excess: 200,         // data points to keep
+        ..SpotConfig::default()
     };
 
     // Create and initialize SPOT detector
@@ -108,6 +109,7 @@ fn test_pure_rust_matches_expected_c_pattern() {
         discard_anomalies: true,
         level: 0.998,
         max_excess: 200,
+        ..SpotConfig::default()
     };
 
     let mut detector = SpotDetector::new(config).unwrap();
@@ -7,7 +7,10 @@
 #![cfg(feature = "serde")]
 
 use approx::assert_relative_eq;
-use libspot_rs::{Peaks, SpotConfig, SpotDetector, SpotError, SpotStatus, Tail, Ubend};
+use libspot_rs::{
+    spot_approx_eq, CompactSpot, DSpotConfig, DSpotDetector, DeserializationKind, Peaks,
+    SpotConfig, SpotDetector, SpotError, SpotStatus, Tail, Ubend,
+};
 
 // ============================================================================
 // SpotConfig Serialization Tests
@@ -21,6 +24,7 @@ fn test_spot_config_json_roundtrip() {
         discard_anomalies: false,
         level: 0.99,
         max_excess: 150,
+        ..SpotConfig::default()
     };
 
     let json = serde_json::to_string(&original).unwrap();
@@ -82,6 +86,25 @@ fn test_spot_error_roundtrip() {
         SpotError::ExcessThresholdIsNaN,
         SpotError::AnomalyThresholdIsNaN,
         SpotError::DataIsNaN,
+        SpotError::DepthOutOfBounds,
+        SpotError::TooFewExcesses { have: 3, need: 10 },
+        SpotError::VersionMismatch {
+            found: 2,
+            expected: 1,
+        },
+        SpotError::ConfigMismatch,
+        SpotError::InvalidStatusCode(3),
+        SpotError::DecayOutOfBounds,
+        SpotError::AlreadyFitted,
+        SpotError::DataIsInfinite,
+        SpotError::InvalidWeights,
+        SpotError::Deserialization(DeserializationKind::Data),
+        SpotError::InvalidGpdParameters,
+        SpotError::NotFitted,
+        SpotError::InvalidGrimshawOptions,
+        SpotError::ExcessRateOutOfBounds,
+        SpotError::DegenerateData,
+        SpotError::UnsupportedOption,
     ];
 
     for error in errors {
@@ -214,6 +237,49 @@ fn test_tail_fitted_roundtrip() {
     assert_relative_eq!(deserialized.sigma(), original.sigma());
 }
 
+#[test]
+fn test_min_peaks_defaults_to_zero_for_old_models() {
+    let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+    let data: Vec<f64> = (0..1000)
+        .map(|i| ((i * 37) % 1000) as f64 / 100.0)
+        .collect();
+    spot.fit(&data).unwrap();
+
+    // Drop the field, as written before it existed
+    let mut json: serde_json::Value = serde_json::to_value(&spot).unwrap();
+    json.as_object_mut().unwrap().remove("min_peaks").unwrap();
+    let old: SpotDetector = serde_json::from_value(json).unwrap();
+    assert_eq!(old.config().unwrap().min_peaks, 0);
+
+    let mut json = serde_json::to_value(SpotConfig::default()).unwrap();
+    json.as_object_mut().unwrap().remove("min_peaks").unwrap();
+    let config: SpotConfig = serde_json::from_value(json).unwrap();
+    assert_eq!(config.min_peaks, 0);
+    assert_eq!(SpotConfig::default().min_peaks, 10);
+}
+
+#[test]
+fn test_tail_old_format_with_zero_gamma() {
+    // Written before the exponential-case flag existed, and with a stale flag
+    let peaks = r#""peaks":{"e":3.0,"e2":3.5,"min":0.5,"max":1.5,"container":{"cursor":0,"capacity":3,"last_erased_data":"NaN","filled":true,"data":[0.5,1.0,1.5]}}"#;
+    for json in [
+        format!(r#"{{"gamma":0.0,"sigma":2.0,{peaks}}}"#),
+        format!(r#"{{"gamma":0.0,"sigma":2.0,"exponential":false,{peaks}}}"#),
+    ] {
+        let tail: Tail = serde_json::from_str(&json).unwrap();
+        assert_eq!(tail.size(), 3);
+        assert_relative_eq!(tail.quantile(0.1, 0.001), 2.0 * 100f64.ln());
+        assert_relative_eq!(tail.probability(0.1, 2.0), 0.1 * (-1f64).exp());
+    }
+
+    // The flag is not written
+    let tail: Tail =
+        serde_json::from_str(&format!(r#"{{"gamma":0.0,"sigma":2.0,{peaks}}}"#)).unwrap();
+    assert!(!serde_json::to_string(&tail)
+        .unwrap()
+        .contains("exponential"));
+}
+
 // ============================================================================
 // SpotDetector Serialization Tests
 // ============================================================================
@@ -245,20 +311,49 @@ fn test_spot_detector_fitted_roundtrip() {
     let json = serde_json::to_string(&original).unwrap();
     let deserialized: SpotDetector = serde_json::from_str(&json).unwrap();
 
-    // Check fitted properties are preserved
-    assert_eq!(deserialized.n(), original.n());
-    assert_eq!(deserialized.nt(), original.nt());
-    assert_relative_eq!(
-        deserialized.anomaly_threshold(),
-        original.anomaly_threshold()
-    );
-    assert_relative_eq!(deserialized.excess_threshold(), original.excess_threshold());
+    // Check fitted properties (counters, thresholds and tail) are preserved,
+    // up to the rounding of the JSON float parsing
+    assert!(spot_approx_eq(&deserialized, &original, 1e-10));
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_spot_detector_json_methods_roundtrip() {
+    let mut original = SpotDetector::new(SpotConfig::default()).unwrap();
+    let training_data: Vec<f64> = (0..1000).map(|i| (i as f64) / 100.0).collect();
+    original.fit(&training_data).unwrap();
 
-    // Check tail parameters
-    let (orig_gamma, orig_sigma) = original.tail_parameters();
-    let (deser_gamma, deser_sigma) = deserialized.tail_parameters();
-    assert_relative_eq!(deser_gamma, orig_gamma);
-    assert_relative_eq!(deser_sigma, orig_sigma);
+    let json = original.to_json().unwrap();
+    assert_eq!(json, serde_json::to_string(&original).unwrap());
+    let deserialized = SpotDetector::from_json(&json).unwrap();
+    assert!(spot_approx_eq(&deserialized, &original, 1e-10));
+
+    let pretty = original.to_json_pretty().unwrap();
+    assert!(pretty.contains('\n'));
+    let deserialized = SpotDetector::from_json(&pretty).unwrap();
+    assert!(spot_approx_eq(&deserialized, &original, 1e-10));
+
+    // An unfitted detector (NaN thresholds) goes through as well
+    let fresh = SpotDetector::new(SpotConfig::default()).unwrap();
+    let deserialized = SpotDetector::from_json(&fresh.to_json().unwrap()).unwrap();
+    assert!(deserialized.anomaly_threshold().is_nan());
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn test_spot_detector_from_json_malformed() {
+    for (json, kind) in [
+        ("{\"invalid\": \"data\"}", DeserializationKind::Data),
+        ("{\"q\": 0.0001", DeserializationKind::Eof),
+        ("{\"q\": }", DeserializationKind::Syntax),
+        ("", DeserializationKind::Eof),
+    ] {
+        assert_eq!(
+            SpotDetector::from_json(json),
+            Err(SpotError::Deserialization(kind)),
+            "{json:?}"
+        );
+    }
 }
 
 #[test]
@@ -309,6 +404,36 @@ fn test_spot_detector_functional_after_deserialization() {
     }
 }
 
+#[test]
+fn test_spot_detector_anomaly_rate_window_roundtrip() {
+    let config = SpotConfig {
+        anomaly_rate_window: 4,
+        ..SpotConfig::default()
+    };
+    let mut original = SpotDetector::new(config).unwrap();
+    let training_data: Vec<f64> = (0..1000).map(|i| (i as f64) / 100.0).collect();
+    original.fit(&training_data).unwrap();
+
+    // Wrap around the window, with an anomaly among the kept verdicts
+    for &value in &[1e6, 1.0, 1e6, 2.0, 3.0] {
+        original.step(value).unwrap();
+    }
+    assert_eq!(original.observed_anomaly_rate(), 0.25);
+
+    let json = serde_json::to_string(&original).unwrap();
+    let mut deserialized: SpotDetector = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized.config(), original.config());
+    assert_eq!(deserialized.observed_anomaly_rate(), 0.25);
+
+    // Both forget the kept anomaly after the same two steps
+    for spot in [&mut original, &mut deserialized] {
+        spot.step(4.0).unwrap();
+        assert_eq!(spot.observed_anomaly_rate(), 0.25);
+        spot.step(5.0).unwrap();
+        assert_eq!(spot.observed_anomaly_rate(), 0.0);
+    }
+}
+
 #[test]
 fn test_spot_detector_low_tail_roundtrip() {
     let config = SpotConfig {
@@ -345,6 +470,122 @@ fn test_spot_detector_pretty_json_output() {
     assert!(pretty_json.contains("\"excess_threshold\""));
 }
 
+#[test]
+fn test_spot_detector_schema_version() {
+    let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+    let training_data: Vec<f64> = (0..100).map(|i| i as f64).collect();
+    spot.fit(&training_data).unwrap();
+    assert_eq!(spot.schema_version(), libspot_rs::model_schema_version());
+
+    let json = serde_json::to_string(&spot).unwrap();
+    let current = format!("\"schema_version\":{}", libspot_rs::model_schema_version());
+    assert!(json.starts_with(&format!("{{{current},")), "{json}");
+
+    // Models written before the schema version was embedded still load
+    let legacy = json.replacen(&format!("{current},"), "", 1);
+    let loaded: SpotDetector = serde_json::from_str(&legacy).unwrap();
+    assert_eq!(loaded.schema_version(), 0);
+    assert_eq!(loaded.anomaly_threshold(), spot.anomaly_threshold());
+    // and are written back with the current version
+    assert_eq!(serde_json::to_string(&loaded).unwrap(), json);
+
+    // A model from a newer crate is rejected
+    let newer = json.replacen(
+        &current,
+        &format!(
+            "\"schema_version\":{}",
+            libspot_rs::model_schema_version() + 1
+        ),
+        1,
+    );
+    let err = serde_json::from_str::<SpotDetector>(&newer).unwrap_err();
+    assert!(
+        err.to_string().contains(
+            &SpotError::VersionMismatch {
+                found: libspot_rs::model_schema_version() + 1,
+                expected: libspot_rs::model_schema_version(),
+            }
+            .to_string()
+        ),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_compact_spot_roundtrip() {
+    let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+    let training_data: Vec<f64> = (0..5000)
+        .map(|i| ((i * 37) % 1000) as f64 / 100.0)
+        .collect();
+    spot.fit(&training_data).unwrap();
+
+    let full = serde_json::to_string(&spot).unwrap();
+    let json = serde_json::to_string(&spot.to_compact()).unwrap();
+    assert!(json.len() < full.len() / 2);
+
+    let compact: CompactSpot = serde_json::from_str(&json).unwrap();
+    let loaded = SpotDetector::from_compact(compact).unwrap();
+    assert_eq!(loaded.anomaly_threshold(), spot.anomaly_threshold());
+    assert_eq!(loaded.excess_threshold(), spot.excess_threshold());
+    assert_eq!(loaded.tail_parameters(), spot.tail_parameters());
+    for q in [1e-3, 1e-4, 1e-5] {
+        assert_eq!(loaded.quantile(q), spot.quantile(q));
+    }
+
+    // The excesses themselves are lost
+    assert!(!spot.excess_values().is_empty());
+    assert!(loaded.excess_values().is_empty());
+}
+
+// ============================================================================
+// DSpotDetector Serialization Tests
+// ============================================================================
+
+#[test]
+fn test_dspot_config_roundtrip() {
+    let original = DSpotConfig {
+        depth: 25,
+        spot: SpotConfig {
+            level: 0.99,
+            ..SpotConfig::default()
+        },
+    };
+
+    let json = serde_json::to_string(&original).unwrap();
+    let deserialized: DSpotConfig = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(deserialized, original);
+}
+
+#[test]
+fn test_dspot_detector_fitted_roundtrip() {
+    let mut original = DSpotDetector::new(DSpotConfig::default()).unwrap();
+    // Exponential noise (a permutation of its quantiles) on top of a slow drift
+    let training_data: Vec<f64> = (0..1000)
+        .map(|i| {
+            let u = (((i * 7919) % 1000) as f64 + 0.5) / 1000.0;
+            (i as f64) / 1000.0 - (1.0 - u).ln()
+        })
+        .collect();
+    original.fit(&training_data).unwrap();
+
+    let json = serde_json::to_string(&original).unwrap();
+    let mut deserialized: DSpotDetector = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(deserialized.config(), original.config());
+    assert_eq!(deserialized.baseline(), original.baseline());
+    assert_eq!(
+        deserialized.anomaly_threshold(),
+        original.anomaly_threshold()
+    );
+
+    for i in 0..100 {
+        let value = 10.0 + ((i * 31) % 17) as f64 / 17.0;
+        assert_eq!(deserialized.step(value), original.step(value));
+    }
+    assert_eq!(deserialized.baseline(), original.baseline());
+}
+
 // ============================================================================
 // Model Persistence Workflow Tests
 // ============================================================================
@@ -475,6 +716,7 @@ mod proptest_serde {
                     max_excess,
                     low_tail,
                     discard_anomalies,
+                    ..SpotConfig::default()
                 },
             )
     }
@@ -515,26 +757,8 @@ mod proptest_serde {
             let json = serde_json::to_string(&original).unwrap();
             let loaded: SpotDetector = serde_json::from_str(&json).unwrap();
 
-            // Verify state preservation
-            prop_assert_eq!(loaded.n(), original.n());
-            prop_assert_eq!(loaded.nt(), original.nt());
-            prop_assert!((loaded.anomaly_threshold() - original.anomaly_threshold()).abs() < 1e-10);
-            prop_assert!((loaded.excess_threshold() - original.excess_threshold()).abs() < 1e-10);
-
-            // Verify tail parameters
-            let (orig_gamma, orig_sigma) = original.tail_parameters();
-            let (loaded_gamma, loaded_sigma) = loaded.tail_parameters();
-            prop_assert!((loaded_gamma - orig_gamma).abs() < 1e-10);
-            prop_assert!((loaded_sigma - orig_sigma).abs() < 1e-10);
-
-            // Verify config
-            let orig_config = original.config().unwrap();
-            let loaded_config = loaded.config().unwrap();
-            prop_assert!((loaded_config.q - orig_config.q).abs() < 1e-10);
-            prop_assert!((loaded_config.level - orig_config.level).abs() < 1e-10);
-            prop_assert_eq!(loaded_config.max_excess, orig_config.max_excess);
-            prop_assert_eq!(loaded_config.low_tail, orig_config.low_tail);
-            prop_assert_eq!(loaded_config.discard_anomalies, orig_config.discard_anomalies);
+            // Verify config, counters, thresholds and tail preservation
+            prop_assert!(spot_approx_eq(&loaded, &original, 1e-10));
         }
 
         /// Property: Loaded detector produces identical detection results
@@ -0,0 +1,254 @@
+//! Tests for binary serialization of SPOT models with bincode
+//!
+//! These tests mirror `serialization.rs` with a non-human-readable format, where
+//! NaN and infinite values are stored as raw IEEE 754 floats instead of strings.
+
+#![cfg(feature = "serde")]
+
+use libspot_rs::{
+    DSpotConfig, DSpotDetector, DeserializationKind, Peaks, SpotConfig, SpotDetector, SpotError,
+    SpotStatus, Tail, Ubend,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Serialize `value` with bincode and read it back
+fn roundtrip<T: Serialize + DeserializeOwned>(value: &T) -> T {
+    let bytes = bincode::serialize(value).unwrap();
+    bincode::deserialize(&bytes).unwrap()
+}
+
+// ============================================================================
+// SpotConfig, SpotStatus and SpotError Serialization Tests
+// ============================================================================
+
+#[test]
+fn test_spot_config_bincode_roundtrip() {
+    let original = SpotConfig {
+        q: 0.001,
+        low_tail: true,
+        discard_anomalies: false,
+        level: 0.99,
+        max_excess: 150,
+        ..SpotConfig::default()
+    };
+
+    assert_eq!(roundtrip(&original), original);
+    assert_eq!(roundtrip(&SpotConfig::default()), SpotConfig::default());
+}
+
+#[test]
+fn test_spot_status_bincode_roundtrip() {
+    for status in [SpotStatus::Normal, SpotStatus::Excess, SpotStatus::Anomaly] {
+        assert_eq!(roundtrip(&status), status);
+    }
+}
+
+#[test]
+fn test_spot_error_bincode_roundtrip() {
+    let errors = [
+        SpotError::MemoryAllocationFailed,
+        SpotError::LevelOutOfBounds,
+        SpotError::QOutOfBounds,
+        SpotError::ExcessThresholdIsNaN,
+        SpotError::AnomalyThresholdIsNaN,
+        SpotError::DataIsNaN,
+        SpotError::DepthOutOfBounds,
+        SpotError::TooFewExcesses { have: 3, need: 10 },
+        SpotError::VersionMismatch {
+            found: 2,
+            expected: 1,
+        },
+        SpotError::ConfigMismatch,
+        SpotError::InvalidStatusCode(3),
+        SpotError::DecayOutOfBounds,
+        SpotError::AlreadyFitted,
+        SpotError::DataIsInfinite,
+        SpotError::InvalidWeights,
+        SpotError::Deserialization(DeserializationKind::Data),
+        SpotError::InvalidGpdParameters,
+        SpotError::NotFitted,
+        SpotError::InvalidGrimshawOptions,
+        SpotError::ExcessRateOutOfBounds,
+        SpotError::DegenerateData,
+        SpotError::UnsupportedOption,
+    ];
+
+    for error in errors {
+        assert_eq!(roundtrip(&error), error);
+    }
+}
+
+// ============================================================================
+// Ubend, Peaks and Tail Serialization Tests
+// ============================================================================
+
+#[test]
+fn test_ubend_bincode_roundtrip() {
+    let empty = roundtrip(&Ubend::new(5).unwrap());
+    assert_eq!(empty.size(), 0);
+    assert_eq!(empty.capacity(), 5);
+    assert!(empty.last_erased_data().is_nan());
+
+    let mut original = Ubend::new(3).unwrap();
+    for v in [1.0, 2.0, 3.0, 4.0, 5.0] {
+        original.push(v);
+    }
+    let deserialized = roundtrip(&original);
+
+    assert!(deserialized.is_filled());
+    assert_eq!(deserialized.cursor(), original.cursor());
+    assert_eq!(deserialized.last_erased_data(), 2.0);
+    let data: Vec<f64> = deserialized.iter().collect();
+    assert_eq!(data, vec![3.0, 4.0, 5.0]);
+}
+
+#[test]
+fn test_peaks_bincode_roundtrip() {
+    let empty = roundtrip(&Peaks::new(10).unwrap());
+    assert_eq!(empty.size(), 0);
+    assert!(empty.min().is_nan());
+    assert!(empty.max().is_nan());
+
+    let mut original = Peaks::new(10).unwrap();
+    for v in [1.0, 2.0, 3.0, 4.0, 5.0] {
+        original.push(v);
+    }
+    let deserialized = roundtrip(&original);
+
+    assert_eq!(deserialized.size(), original.size());
+    assert_eq!(deserialized.mean(), original.mean());
+    assert_eq!(deserialized.variance(), original.variance());
+    assert_eq!(deserialized.min(), original.min());
+    assert_eq!(deserialized.max(), original.max());
+}
+
+#[test]
+fn test_tail_bincode_roundtrip() {
+    let empty = roundtrip(&Tail::new(10).unwrap());
+    assert_eq!(empty.size(), 0);
+    assert!(empty.gamma().is_nan());
+    assert!(empty.sigma().is_nan());
+
+    let mut original = Tail::new(10).unwrap();
+    for v in [0.5, 1.0, 1.5, 2.0, 2.5, 3.0, 3.5, 4.0] {
+        original.push(v);
+    }
+    original.fit();
+    let deserialized = roundtrip(&original);
+
+    assert_eq!(deserialized.size(), original.size());
+    assert_eq!(deserialized.gamma(), original.gamma());
+    assert_eq!(deserialized.sigma(), original.sigma());
+}
+
+// ============================================================================
+// SpotDetector Serialization Tests
+// ============================================================================
+
+#[test]
+fn test_spot_detector_nan_thresholds_bincode_roundtrip() {
+    let original = SpotDetector::new(SpotConfig::default()).unwrap();
+    assert!(original.anomaly_threshold().is_nan());
+
+    let deserialized = roundtrip(&original);
+
+    assert_eq!(deserialized.n(), 0);
+    assert!(deserialized.anomaly_threshold().is_nan());
+    assert!(deserialized.excess_threshold().is_nan());
+    assert_eq!(deserialized.config(), original.config());
+}
+
+#[test]
+fn test_spot_detector_fitted_bincode_roundtrip() {
+    let config = SpotConfig {
+        track_excess_threshold: true,
+        ..SpotConfig::default()
+    };
+    let mut original = SpotDetector::new(config).unwrap();
+    let training_data: Vec<f64> = (0..1000).map(|i| (i as f64) / 100.0).collect();
+    original.fit(&training_data).unwrap();
+
+    let mut deserialized = roundtrip(&original);
+
+    assert_eq!(deserialized.n(), original.n());
+    assert_eq!(deserialized.nt(), original.nt());
+    assert_eq!(
+        deserialized.anomaly_threshold(),
+        original.anomaly_threshold()
+    );
+    assert_eq!(deserialized.excess_threshold(), original.excess_threshold());
+    assert_eq!(deserialized.tail_parameters(), original.tail_parameters());
+    assert_eq!(deserialized.peaks_data(), original.peaks_data());
+
+    for &val in &[5.0, 10.0, 50.0, 100.0, 500.0] {
+        assert_eq!(deserialized.step(val), original.step(val));
+    }
+    assert_eq!(
+        deserialized.anomaly_threshold(),
+        original.anomaly_threshold()
+    );
+}
+
+#[test]
+fn test_spot_detector_bincode_smaller_than_json() {
+    let mut spot = SpotDetector::new(SpotConfig::default()).unwrap();
+    let training_data: Vec<f64> = (0..1000).map(|i| (i as f64) / 100.0).collect();
+    spot.fit(&training_data).unwrap();
+
+    let bytes = bincode::serialize(&spot).unwrap();
+    let json = serde_json::to_string(&spot).unwrap();
+    assert!(bytes.len() < json.len());
+}
+
+#[test]
+fn test_dspot_detector_bincode_roundtrip() {
+    let config = DSpotConfig {
+        depth: 25,
+        ..DSpotConfig::default()
+    };
+    assert_eq!(roundtrip(&config), config);
+
+    let mut original = DSpotDetector::new(DSpotConfig::default()).unwrap();
+    // Exponential noise (a permutation of its quantiles) on top of a slow drift
+    let training_data: Vec<f64> = (0..1000)
+        .map(|i| {
+            let u = (((i * 7919) % 1000) as f64 + 0.5) / 1000.0;
+            (i as f64) / 1000.0 - (1.0 - u).ln()
+        })
+        .collect();
+    original.fit(&training_data).unwrap();
+
+    let mut deserialized = roundtrip(&original);
+
+    assert_eq!(deserialized.config(), original.config());
+    assert_eq!(deserialized.baseline(), original.baseline());
+    for i in 0..100 {
+        let value = 10.0 + ((i * 31) % 17) as f64 / 17.0;
+        assert_eq!(deserialized.step(value), original.step(value));
+    }
+}
+
+// ============================================================================
+// Edge Cases and Error Handling
+// ============================================================================
+
+#[test]
+fn test_deserialize_truncated_bincode() {
+    let spot = SpotDetector::new(SpotConfig::default()).unwrap();
+    let bytes = bincode::serialize(&spot).unwrap();
+
+    let result: Result<SpotDetector, _> = bincode::deserialize(&bytes[..bytes.len() / 2]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_infinite_values_bincode_roundtrip() {
+    let mut original = Peaks::new(10).unwrap();
+    original.push(f64::NEG_INFINITY);
+    original.push(f64::INFINITY);
+
+    let deserialized = roundtrip(&original);
+    assert_eq!(deserialized.min(), f64::NEG_INFINITY);
+    assert_eq!(deserialized.max(), f64::INFINITY);
+    assert!(deserialized.sum().is_nan());
+}
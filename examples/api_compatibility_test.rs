@@ -39,6 +39,7 @@ fn test_configuration_api() -> Result<(), Box<dyn std::error::Error>> {
         discard_anomalies: true,
         level: 0.999,
         max_excess: 100,
+        ..libspot_rs::SpotConfig::default()
     };
 
     // Test default() method exists on both
@@ -41,6 +41,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         discard_anomalies: true,
         level: 0.998,
         max_excess: 200,
+        ..RustConfig::default()
     };
 
     let ffi_config = FFIConfig {